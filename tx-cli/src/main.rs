@@ -1,74 +1,1635 @@
-use std::env;
-use std::io::{stdout, Write};
+use std::collections::HashMap;
+use std::io::{stderr, stdout, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
 
+use clap::{Parser, ValueEnum};
+use rust_decimal::{Decimal, RoundingStrategy};
 use tokio::fs::File;
+use tracing::{error, Subscriber};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::EnvFilter;
 
+use tx_engine::tx::engine::account_query::{AccountFilter, AccountQuery};
+use tx_engine::tx::engine::amount_policy::{AmountPolicy, ExcessPrecision};
+use tx_engine::tx::engine::business_date::BusinessDate;
+use tx_engine::tx::engine::config::{AccountOpening, EngineConfig, LedgerBackend, TxIdScope};
+use tx_engine::tx::engine::dispute_window::DisputeWindow;
 use tx_engine::tx::engine::engine::TransactionEngine;
+use tx_engine::tx::engine::event::AccountEvent;
+use tx_engine::tx::engine::fee_schedule::FeeSchedule;
+use tx_engine::tx::engine::id::{ClientId, IdKind, TenantId, TxId};
+use tx_engine::tx::engine::interest::InterestConfig;
+use tx_engine::tx::engine::multi_tenant_engine::MultiTenantEngine;
 use tx_engine::tx::engine::result::{TxError, TxResult};
+use tx_engine::tx::engine::simulation::Rejection;
+use tx_engine::tx::engine::transaction::Transaction;
+use tx_engine::tx::engine::transition::TransitionStrictness;
+use tx_engine::tx::ledger::disk_ledger_store::DiskLedger;
+use tx_engine::tx::metrics::metrics::{EngineGauges, Metrics};
+use tx_engine::tx::metrics::metrics_server::MetricsServer;
 use tx_engine::tx::reports::csv_account_report::CsvAccountReport;
+use tx_engine::tx::reports::csv_accrual_report::CsvAccrualReport;
+use tx_engine::tx::reports::csv_journal_report::CsvJournalReport;
+use tx_engine::tx::reports::csv_pending_report::CsvPendingReport;
+use tx_engine::tx::reports::csv_rejection_report::CsvRejectionReport;
+use tx_engine::tx::reports::csv_rule_audit_report::CsvRuleAuditReport;
+use tx_engine::tx::reports::csv_simulation_report::CsvSimulationReport;
+use tx_engine::tx::reports::csv_stats_report::CsvStatsReport;
+use tx_engine::tx::reports::csv_trial_balance_report::CsvTrialBalanceReport;
+use tx_engine::tx::reports::ndjson_event_report::NdjsonEventReport;
+use tx_engine::tx::sources::csv_client_tier_source::CsvClientTierSource;
+use tx_engine::tx::sources::csv_credit_limit_source::CsvCreditLimitSource;
+use tx_engine::tx::sources::csv_fee_schedule_source::CsvFeeScheduleSource;
+use tx_engine::tx::sources::csv_interest_rate_source::CsvInterestRateSource;
+use tx_engine::tx::sources::csv_rule_source::CsvRuleSource;
+use tx_engine::tx::sources::csv_tenant_config_source::{CsvTenantConfigSource, TenantConfig};
 use tx_engine::tx::sources::csv_transaction_source::CsvTransactionSource;
 use tx_engine::tx::sources::transaction_source::TransactionSource;
 
+#[derive(Debug, Parser)]
+#[command(
+    about = "Executes the transactions of a CSV file and prints the resulting account states."
+)]
+struct Options {
+    /// Path to a CSV file with transaction data.
+    source: String,
+
+    /// Scope within which deposit and withdrawal ids have to be unique.
+    #[arg(long, value_enum, default_value_t = TxIdScopeOption::PerClient)]
+    tx_id_scope: TxIdScopeOption,
+
+    /// Directory for an on-disk ledger, keeps memory bounded for very large inputs. Any data in
+    /// the directory is discarded. If omitted, the ledger is kept in memory.
+    #[arg(long)]
+    ledger_dir: Option<String>,
+
+    /// Number of ledger entries the on-disk ledger keeps cached in memory.
+    #[arg(long, default_value_t = 100_000)]
+    ledger_cache_size: usize,
+
+    /// Number of subsequent transactions after which a deposit or withdrawal can no longer be
+    /// disputed.
+    #[arg(long, conflicts_with = "dispute_window_seconds")]
+    dispute_window_transactions: Option<u64>,
+
+    /// Number of seconds after which a deposit or withdrawal can no longer be disputed, requires
+    /// a timestamp column in the input.
+    #[arg(long)]
+    dispute_window_seconds: Option<u64>,
+
+    /// Number of transactions after which ledger entries outside the dispute window are removed.
+    #[arg(long, default_value_t = 100_000)]
+    compaction_interval: u64,
+
+    /// Path to a CSV file with the columns `client` and `credit_limit`, allowing the available
+    /// funds of these clients to go negative down to their limit. Applies to each tenant without
+    /// credit limits of its own in the tenant config.
+    #[arg(long)]
+    credit_limits: Option<String>,
+
+    /// Path to a CSV file with the columns `kind`, `tier`, `flat` and `percentage`, defining the
+    /// fees charged for withdrawals, transfers and chargebacks.
+    #[arg(long, requires = "house_account")]
+    fee_schedule: Option<String>,
+
+    /// Path to a CSV file with the columns `client` and `tier`, assigning clients to fee tiers.
+    #[arg(long, requires = "fee_schedule")]
+    client_tiers: Option<String>,
+
+    /// Client id of the account collected fees are credited to.
+    #[arg(long, requires = "fee_schedule")]
+    house_account: Option<ClientId>,
+
+    /// Path to a CSV file with the columns `minimum_balance` and `rate` (annual, in percent),
+    /// enabling a day of interest to be paid at each `accrual` row of the input.
+    #[arg(long)]
+    interest_rates: Option<String>,
+
+    /// Pay interest on held funds as well, not only on available funds.
+    #[arg(long, requires = "interest_rates")]
+    interest_on_held: bool,
+
+    /// Accrue a day of interest once all transactions were executed, posted with the given
+    /// transaction id.
+    #[arg(long, requires = "interest_rates")]
+    accrue_interest: Option<TxId>,
+
+    /// Path of a CSV file to write the interest posted by all accruals to.
+    #[arg(long, requires = "interest_rates")]
+    accrual_report: Option<String>,
+
+    /// Path to a CSV file with the columns `rule`, `limit`, `window` and `action`, defining
+    /// fraud and velocity checks evaluated before each transaction. Applies to each tenant
+    /// without rules of its own in the tenant config.
+    #[arg(long)]
+    rules: Option<String>,
+
+    /// Path of a CSV file to write all rule decisions to, passed and violated ones, also written
+    /// if a rule aborts the run.
+    #[arg(long, requires = "rules")]
+    rule_audit: Option<String>,
+
+    /// Path to a CSV file with all transactions executed so far, which are replayed to restore
+    /// the current state before the source file is applied.
+    #[arg(long)]
+    history: Option<String>,
+
+    /// Simulates the source file on top of the state restored from the history instead of
+    /// applying it. Prints the resulting accounts together with their changes.
+    #[arg(long, requires = "history", conflicts_with = "atomic")]
+    dry_run: bool,
+
+    /// Path of a CSV file to write the transactions rejected by a dry run to, as well as the
+    /// disputes, resolves and chargebacks that were ignored because they don't apply to their
+    /// transaction and the scheduled transactions that failed once their value date was reached.
+    /// With multiple tenants the rows get a tenant column and are indexed per tenant.
+    #[arg(long)]
+    rejections: Option<String>,
+
+    /// Rejects disputes, resolves and chargebacks that don't apply to their transaction instead
+    /// of ignoring them, e.g. resolving a transaction that is not disputed.
+    #[arg(long)]
+    strict_transitions: bool,
+
+    /// Number of times a transaction can be disputed, further disputes are rejected. If omitted,
+    /// a transaction can be disputed again after each resolve without limit.
+    #[arg(long)]
+    max_dispute_cycles: Option<u32>,
+
+    /// Unlocks an account once a representment leaves none of its transactions charged back.
+    #[arg(long)]
+    unlock_on_representment: bool,
+
+    /// Path of a CSV file to write how often disputes, resolves and chargebacks, including those
+    /// of the history, were applied or ignored to. Also written if the run is aborted.
+    #[arg(long)]
+    stats: Option<String>,
+
+    /// Applies the input all-or-nothing, no transaction is applied if any of them fails.
+    #[arg(long)]
+    atomic: bool,
+
+    /// Path of a file to write all account events to as newline delimited JSON.
+    #[arg(long)]
+    events: Option<String>,
+
+    /// Path of a CSV file to write the double-entry journal of the general ledger to.
+    #[arg(long)]
+    journal: Option<String>,
+
+    /// Path of a CSV file to write the trial balance of the general ledger to. The ledger is
+    /// reconciled against the client accounts before it is written.
+    #[arg(long)]
+    trial_balance: Option<String>,
+
+    /// The business date (YYYY-MM-DD) processing starts at. Transactions with a later value date
+    /// are kept pending until a `business_date` row reaches it.
+    #[arg(long)]
+    business_date: Option<BusinessDate>,
+
+    /// Path of a CSV file to write the transactions still waiting for their value date to.
+    #[arg(long)]
+    pending: Option<String>,
+
+    /// Which kinds of client and transaction ids the input files may use.
+    #[arg(long, value_enum, default_value_t = IdKindOption::Numeric)]
+    id_kind: IdKindOption,
+
+    /// Which transactions open the account of a client that has none yet.
+    #[arg(long, value_enum, default_value_t = AccountOpeningOption::OnFirstDeposit)]
+    account_opening: AccountOpeningOption,
+
+    /// Number of subsequent transactions without deposits, withdrawals, transfers or
+    /// authorizations of a client after which its account becomes dormant.
+    #[arg(long, conflicts_with = "dormant_after_seconds")]
+    dormant_after_transactions: Option<u64>,
+
+    /// Number of seconds without deposits, withdrawals, transfers or authorizations of a client
+    /// after which its account becomes dormant, requires a timestamp column in the input.
+    #[arg(long)]
+    dormant_after_seconds: Option<u64>,
+
+    /// Maximum number of decimal places of amounts in the input, amounts with more are rejected
+    /// unless a rounding is given.
+    #[arg(long)]
+    max_decimal_places: Option<u32>,
+
+    /// Rounds amounts with more than the maximum number of decimal places instead of rejecting
+    /// them.
+    #[arg(long, value_enum, requires = "max_decimal_places")]
+    rounding: Option<RoundingOption>,
+
+    /// Rejects amounts of deposits, withdrawals, transfers and authorizations below this amount.
+    #[arg(long)]
+    min_amount: Option<Decimal>,
+
+    /// Rejects amounts of deposits, withdrawals, transfers and authorizations above this amount.
+    #[arg(long)]
+    max_amount: Option<Decimal>,
+
+    /// Rejects amounts of deposits, withdrawals, transfers and authorizations that are zero, or
+    /// are rounded to zero. Partial amounts of disputes, chargebacks and captures may be zero.
+    #[arg(long)]
+    reject_zero_amounts: bool,
+
+    /// Only prints the accounts matching the filter, one of `locked`, `negative-available`,
+    /// `held` or `total-above=<amount>`. Repeat it to print the accounts matching all filters.
+    #[arg(long = "filter", value_name = "FILTER", conflicts_with = "dry_run")]
+    filters: Vec<AccountFilter>,
+
+    /// Address (e.g. 127.0.0.1:9100) to serve metrics in the Prometheus text format at
+    /// `/metrics` while the input is processed.
+    #[arg(long)]
+    metrics_addr: Option<String>,
+
+    /// Path of a file to write metrics in the Prometheus text format to, rewritten periodically
+    /// while the input is processed and once it is done.
+    #[arg(long)]
+    metrics_file: Option<String>,
+
+    /// Number of transactions after which the account gauges are refreshed and the metrics file
+    /// is rewritten.
+    #[arg(long, default_value_t = 1_000)]
+    metrics_interval: u64,
+
+    /// Executes the transactions of each tenant with a separate engine, so tenants never share
+    /// accounts even if their client ids overlap. The tenant of a row is taken from the `tenant`
+    /// column, the printed accounts get a tenant column as well.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "history",
+            "dry_run",
+            "atomic",
+            "rule_audit",
+            "stats",
+            "events",
+            "journal",
+            "trial_balance",
+            "pending",
+            "accrue_interest",
+            "accrual_report",
+        ]
+    )]
+    multi_tenant: bool,
+
+    /// Tenant of the rows without a value in the `tenant` column, or of all rows if the input has
+    /// no such column.
+    #[arg(long, requires = "multi_tenant")]
+    default_tenant: Option<TenantId>,
+
+    /// Directory to write a CSV file with the accounts of each tenant to, named after the tenant.
+    #[arg(long, requires = "multi_tenant")]
+    tenant_reports: Option<String>,
+
+    /// Path to a CSV file with the columns `tenant`, `rules` and `credit_limits`, giving the
+    /// files with the rules and credit limits of each tenant. Relative paths are resolved
+    /// against the directory of the file, empty ones fall back to `--rules` and
+    /// `--credit-limits`.
+    #[arg(long, requires = "multi_tenant")]
+    tenant_config: Option<String>,
+
+    /// Log filter, either a level (error, warn, info, debug, trace, off) or directives like
+    /// `tx_engine=debug`. The activity of one client is traced with `[execute{client=7}]=trace`.
+    #[arg(long, default_value = "warn")]
+    log_level: String,
+
+    /// Format of the log written to stderr.
+    #[arg(long, value_enum, default_value_t = LogFormatOption::Text)]
+    log_format: LogFormatOption,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum TxIdScopeOption {
+    PerClient,
+    Global,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum IdKindOption {
+    /// Unsigned integers only, leading zeros are ignored.
+    Numeric,
+    /// Integers, UUIDs and alphanumeric references.
+    Mixed,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum AccountOpeningOption {
+    /// Deposits, incoming transfers and `open` rows open accounts.
+    OnFirstDeposit,
+    /// Only `open` rows open accounts.
+    Explicit,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum LogFormatOption {
+    /// Human readable lines.
+    Text,
+    /// One JSON object per line, including the fields of all enclosing spans.
+    Json,
+}
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum RoundingOption {
+    /// Rounds to the nearest value, midpoints to the even one (banker's rounding).
+    HalfEven,
+    /// Rounds to the nearest value, midpoints up.
+    HalfUp,
+    /// Truncates the excess decimal places.
+    Down,
+    /// Rounds up if there is any excess.
+    Up,
+}
+
+impl Options {
+    fn account_query(&self) -> AccountQuery {
+        AccountQuery {
+            filters: self.filters.clone(),
+            ..AccountQuery::default()
+        }
+    }
+
+    fn id_kind(&self) -> IdKind {
+        match self.id_kind {
+            IdKindOption::Numeric => IdKind::Numeric,
+            IdKindOption::Mixed => IdKind::Mixed,
+        }
+    }
+
+    fn amount_policy(&self) -> AmountPolicy {
+        AmountPolicy {
+            max_decimal_places: self.max_decimal_places,
+            excess_precision: match self.rounding {
+                None => ExcessPrecision::Reject,
+                Some(RoundingOption::HalfEven) => {
+                    ExcessPrecision::Round(RoundingStrategy::MidpointNearestEven)
+                }
+                Some(RoundingOption::HalfUp) => {
+                    ExcessPrecision::Round(RoundingStrategy::MidpointAwayFromZero)
+                }
+                Some(RoundingOption::Down) => ExcessPrecision::Round(RoundingStrategy::ToZero),
+                Some(RoundingOption::Up) => ExcessPrecision::Round(RoundingStrategy::AwayFromZero),
+            },
+            min_amount: self.min_amount,
+            max_amount: self.max_amount,
+            reject_zero: self.reject_zero_amounts,
+        }
+    }
+
+    async fn engine_config(&self, metrics: Option<Metrics>) -> TxResult<EngineConfig> {
+        Ok(EngineConfig {
+            tx_id_scope: match self.tx_id_scope {
+                TxIdScopeOption::PerClient => TxIdScope::PerClient,
+                TxIdScopeOption::Global => TxIdScope::Global,
+            },
+            ledger: match &self.ledger_dir {
+                Some(path) => {
+                    LedgerBackend::Disk(DiskLedger::create(path, self.ledger_cache_size)?)
+                }
+                None => LedgerBackend::InMemory,
+            },
+            dispute_window: match (
+                self.dispute_window_transactions,
+                self.dispute_window_seconds,
+            ) {
+                (Some(count), _) => Some(DisputeWindow::Transactions(count)),
+                (_, Some(seconds)) => Some(DisputeWindow::Elapsed(Duration::from_secs(seconds))),
+                _ => None,
+            },
+            compaction_interval: Some(self.compaction_interval),
+            fees: self.fee_schedule().await?,
+            interest: self.interest().await?,
+            general_ledger: self.journal.is_some() || self.trial_balance.is_some(),
+            business_date: self.business_date,
+            account_opening: match self.account_opening {
+                AccountOpeningOption::OnFirstDeposit => AccountOpening::OnFirstDeposit,
+                AccountOpeningOption::Explicit => AccountOpening::Explicit,
+            },
+            dormancy: match (self.dormant_after_transactions, self.dormant_after_seconds) {
+                (Some(count), _) => Some(DisputeWindow::Transactions(count)),
+                (_, Some(seconds)) => Some(DisputeWindow::Elapsed(Duration::from_secs(seconds))),
+                _ => None,
+            },
+            transition_strictness: if self.strict_transitions {
+                TransitionStrictness::Strict
+            } else {
+                TransitionStrictness::Lenient
+            },
+            max_dispute_cycles: self.max_dispute_cycles,
+            unlock_on_representment: self.unlock_on_representment,
+            metrics,
+        })
+    }
+
+    fn log_subscriber<M>(&self, make_writer: M) -> TxResult<Box<dyn Subscriber + Send + Sync>>
+    where
+        M: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let filter = EnvFilter::try_new(&self.log_level).map_err(|e| {
+            TxError::InvalidArgument(format!(
+                "Could not parse log level [{}]: {}.",
+                self.log_level, e
+            ))
+        })?;
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(make_writer);
+
+        Ok(match self.log_format {
+            LogFormatOption::Text => Box::new(builder.finish()),
+            LogFormatOption::Json => Box::new(builder.json().with_span_list(true).finish()),
+        })
+    }
+
+    async fn interest(&self) -> TxResult<Option<InterestConfig>> {
+        let Some(interest_rates_path) = &self.interest_rates else {
+            return Ok(None);
+        };
+        let mut interest = InterestConfig {
+            include_held: self.interest_on_held,
+            ..InterestConfig::default()
+        };
+
+        let mut rate_source =
+            CsvInterestRateSource::from_reader(open_file(interest_rates_path).await?).await?;
+        while let Some(rate) = rate_source.read().await? {
+            interest.rates.push(rate);
+        }
+
+        Ok(Some(interest))
+    }
+
+    async fn fee_schedule(&self) -> TxResult<Option<FeeSchedule>> {
+        let (Some(fee_schedule_path), Some(house_account)) =
+            (&self.fee_schedule, self.house_account)
+        else {
+            return Ok(None);
+        };
+        let mut fees = FeeSchedule::new(house_account);
+
+        let mut fee_source =
+            CsvFeeScheduleSource::from_reader(open_file(fee_schedule_path).await?).await?;
+        while let Some(entry) = fee_source.read().await? {
+            fees.set_fee(entry.kind, entry.tier.as_deref(), entry.fee);
+        }
+
+        if let Some(client_tiers_path) = &self.client_tiers {
+            let mut tier_source =
+                CsvClientTierSource::from_reader(open_file(client_tiers_path).await?)
+                    .await?
+                    .with_id_kind(self.id_kind());
+            while let Some(client_tier) = tier_source.read().await? {
+                fees.set_client_tier(client_tier.client_id, &client_tier.tier);
+            }
+        }
+
+        Ok(Some(fees))
+    }
+
+    async fn tenant_configs(&self) -> TxResult<HashMap<TenantId, TenantConfig>> {
+        let mut configs = HashMap::new();
+        let Some(tenant_config_path) = &self.tenant_config else {
+            return Ok(configs);
+        };
+        let directory = Path::new(tenant_config_path)
+            .parent()
+            .unwrap_or(Path::new(""));
+        let resolve = |path: String| directory.join(path).to_string_lossy().into_owned();
+
+        let mut config_source =
+            CsvTenantConfigSource::from_reader(open_file(tenant_config_path).await?).await?;
+        while let Some(config) = config_source.read().await? {
+            configs.insert(
+                config.tenant_id,
+                TenantConfig {
+                    rules: config.rules.map(resolve),
+                    credit_limits: config.credit_limits.map(resolve),
+                    ..config
+                },
+            );
+        }
+
+        Ok(configs)
+    }
+}
+
 #[tokio::main]
 async fn main() {
-    let args = env::args().collect::<Vec<_>>();
-    if args.len() != 2 {
-        eprintln!("[ERROR]: This application requires a path to a CSV file with transaction data as argument.");
-        return;
-    }
+    let options = Options::parse();
+
+    let subscriber = match options.log_subscriber(stderr) {
+        Ok(subscriber) => subscriber,
+        Err(err) => {
+            eprintln!("[ERROR]: {:?}", err);
+            return;
+        }
+    };
+    tracing::subscriber::set_global_default(subscriber).expect("no other subscriber is set");
 
-    if let Err(err) = run(args[1].as_str(), stdout()).await {
-        eprintln!("[ERROR]: {:?}", err);
-        return;
+    if let Err(err) = run(&options, stdout()).await {
+        error!(error = ?err, "run aborted");
     }
 }
 
-async fn run<W>(csv_source_path: &str, output_sink: W) -> TxResult<W>
+async fn run<W>(options: &Options, output_sink: W) -> TxResult<W>
 where
     W: Write + Send + Unpin,
 {
-    let csv_source_file = File::open(csv_source_path).await.map_err(|e| {
-        TxError::IoError(format!(
-            "Unable to open source file [{}]: {}",
-            csv_source_path, e
-        ))
-    })?;
-    let mut csv_source = CsvTransactionSource::from_reader(csv_source_file).await?;
-    let mut engine = TransactionEngine::new();
-    while let Some(record) = csv_source.read().await? {
-        engine.execute(record)?;
+    let metrics =
+        (options.metrics_addr.is_some() || options.metrics_file.is_some()).then(Metrics::new);
+    let _metrics_server = match (&options.metrics_addr, &metrics) {
+        (Some(address), Some(metrics)) => {
+            Some(MetricsServer::bind(address.as_str(), metrics.clone()).await?)
+        }
+        _ => None,
+    };
+
+    if options.multi_tenant {
+        return run_multi_tenant(options, &metrics, output_sink).await;
+    }
+
+    let csv_source_file = open_file(&options.source).await?;
+    let mut csv_source = CsvTransactionSource::from_reader(csv_source_file)
+        .await?
+        .with_id_kind(options.id_kind())
+        .with_amount_policy(options.amount_policy());
+    if let Some(metrics) = &metrics {
+        csv_source = csv_source.with_metrics(metrics.clone());
+    }
+    let mut engine = TransactionEngine::with_config(options.engine_config(metrics.clone()).await?);
+
+    if let Some(credit_limits_path) = &options.credit_limits {
+        set_credit_limits(&mut engine, credit_limits_path, options.id_kind()).await?;
+    }
+
+    if let Some(rules_path) = &options.rules {
+        add_rules(&mut engine, rules_path).await?;
+    }
+
+    if let Some(history_path) = &options.history {
+        let mut history_source = CsvTransactionSource::from_reader(open_file(history_path).await?)
+            .await?
+            .with_id_kind(options.id_kind())
+            .with_amount_policy(options.amount_policy());
+        while let Some(record) = history_source.read().await? {
+            engine.execute(record)?;
+        }
+    }
+
+    // transitions ignored while replaying the history are not reported
+    let first_sequence = engine.executed();
+    let first_ignored = engine.ignored_transitions().len();
+    let first_settlement_failure = engine.settlement_failures().len();
+
+    let mut event_report = match &options.events {
+        Some(events_path) => {
+            let (sender, receiver) = mpsc::channel();
+            engine.subscribe(Box::new(sender));
+            Some((
+                receiver,
+                NdjsonEventReport::from_writer(create_file(events_path)?)?,
+            ))
+        }
+        None => None,
+    };
+
+    let mut simulation = None;
+    let executed: TxResult<()> = async {
+        if options.dry_run {
+            let mut transactions = read_all(&mut csv_source).await?;
+            if let Some(tx_id) = options.accrue_interest {
+                transactions.push(Transaction::new_interest_accrual(tx_id));
+            }
+            simulation = Some(engine.simulate(transactions)?);
+            return Ok(());
+        }
+
+        if options.atomic {
+            engine.execute_batch(read_all(&mut csv_source).await?)?;
+        } else {
+            while let Some(record) = csv_source.read().await? {
+                engine.execute(record)?;
+                write_events(&mut event_report)?;
+                if engine
+                    .executed()
+                    .is_multiple_of(options.metrics_interval.max(1))
+                {
+                    write_metrics(options, &metrics, || engine.gauges())?;
+                }
+            }
+        }
+
+        if let Some(tx_id) = options.accrue_interest {
+            engine.execute(Transaction::new_interest_accrual(tx_id))?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    // events of a failed transaction are written as well, e.g. if a rule locked the account
+    write_events(&mut event_report)?;
+    write_metrics(options, &metrics, || engine.gauges())?;
+    if let Some((_, report)) = &mut event_report {
+        report.flush()?;
+    }
+
+    if let Some(rule_audit_path) = &options.rule_audit {
+        let mut rule_audit = CsvRuleAuditReport::from_writer(create_file(rule_audit_path)?)?;
+        engine
+            .rule_decisions()
+            .iter()
+            .try_for_each(|decision| rule_audit.write_decision(decision))?;
+        rule_audit.flush()?;
+    }
+
+    if let Some(stats_path) = &options.stats {
+        let mut stats = CsvStatsReport::from_writer(create_file(stats_path)?)?;
+        engine
+            .transition_stats()
+            .counts()
+            .iter()
+            .try_for_each(|count| stats.write_count(count))?;
+        stats.flush()?;
+    }
+
+    if let (Some(rejections_path), false) = (&options.rejections, options.dry_run) {
+        let mut rejections = CsvRejectionReport::from_writer(create_file(rejections_path)?)?;
+        rejected(
+            &engine,
+            first_sequence,
+            first_ignored,
+            first_settlement_failure,
+        )
+        .iter()
+        .try_for_each(|rejection| rejections.write_rejection(rejection))?;
+        rejections.flush()?;
+    }
+
+    executed?;
+
+    if let Some(accrual_report_path) = &options.accrual_report {
+        let mut accrual_report = CsvAccrualReport::from_writer(create_file(accrual_report_path)?)?;
+        engine
+            .accruals()
+            .iter()
+            .try_for_each(|accrual| accrual_report.write_accrual(accrual))?;
+        accrual_report.flush()?;
+    }
+
+    if let Some(pending_path) = &options.pending {
+        let mut pending = CsvPendingReport::from_writer(create_file(pending_path)?)?;
+        engine
+            .pending()
+            .iter()
+            .try_for_each(|transaction| pending.write_transaction(transaction))?;
+        pending.flush()?;
+    }
+
+    if let Some(general_ledger) = engine.general_ledger() {
+        general_ledger.reconcile(&engine.account_summary())?;
+
+        if let Some(journal_path) = &options.journal {
+            let mut journal = CsvJournalReport::from_writer(create_file(journal_path)?)?;
+            general_ledger
+                .journal()
+                .iter()
+                .try_for_each(|entry| journal.write_entry(entry))?;
+            journal.flush()?;
+        }
+
+        if let Some(trial_balance_path) = &options.trial_balance {
+            let mut trial_balance =
+                CsvTrialBalanceReport::from_writer(create_file(trial_balance_path)?)?;
+            general_ledger
+                .trial_balance()
+                .iter()
+                .try_for_each(|line| trial_balance.write_line(line))?;
+            trial_balance.flush()?;
+        }
+    }
+
+    if let Some(simulation) = simulation {
+        if let Some(rejections_path) = &options.rejections {
+            let mut rejections = CsvRejectionReport::from_writer(create_file(rejections_path)?)?;
+            simulation
+                .rejections
+                .iter()
+                .try_for_each(|rejection| rejections.write_rejection(rejection))?;
+            rejections.flush()?;
+        }
+
+        let mut csv_report = CsvSimulationReport::from_writer(output_sink)?;
+        simulation
+            .accounts
+            .iter()
+            .try_for_each(|change| csv_report.write_change(change))?;
+
+        return csv_report.flush();
     }
 
     let mut csv_report = CsvAccountReport::from_writer(output_sink)?;
     engine
-        .account_summary()
-        .iter()
-        .try_for_each(|account| csv_report.write_account(account))?;
+        .query(&options.account_query())
+        .try_for_each(|account| csv_report.write_account(&account))?;
 
     csv_report.flush()
 }
 
+/// Executes the source with a separate engine per tenant, all of them configured alike except
+/// for the rules and credit limits given by the tenant config.
+async fn run_multi_tenant<W>(
+    options: &Options,
+    metrics: &Option<Metrics>,
+    output_sink: W,
+) -> TxResult<W>
+where
+    W: Write + Send + Unpin,
+{
+    let mut csv_source = CsvTransactionSource::from_reader(open_file(&options.source).await?)
+        .await?
+        .with_id_kind(options.id_kind())
+        .with_amount_policy(options.amount_policy());
+    if let Some(tenant_id) = options.default_tenant {
+        csv_source = csv_source.with_default_tenant(tenant_id);
+    }
+    if let Some(metrics) = metrics {
+        csv_source = csv_source.with_metrics(metrics.clone());
+    }
+    let mut engine = MultiTenantEngine::new(options.engine_config(metrics.clone()).await?);
+    let tenant_configs = options.tenant_configs().await?;
+
+    let executed: TxResult<()> = async {
+        let mut count = 0u64;
+        while let Some((tenant_id, record)) = csv_source.read_with_tenant().await? {
+            if engine.engine(tenant_id).is_none() {
+                configure_tenant(
+                    options,
+                    engine.engine_mut(tenant_id),
+                    tenant_configs.get(&tenant_id),
+                )
+                .await?;
+            }
+            engine.execute(tenant_id, record)?;
+            count += 1;
+            if count.is_multiple_of(options.metrics_interval.max(1)) {
+                write_metrics(options, metrics, || engine.gauges())?;
+            }
+        }
+
+        Ok(())
+    }
+    .await;
+
+    write_metrics(options, metrics, || engine.gauges())?;
+
+    if let Some(rejections_path) = &options.rejections {
+        let mut rejections =
+            CsvRejectionReport::from_writer_with_tenants(create_file(rejections_path)?)?;
+        for tenant_id in engine.tenants() {
+            engine
+                .engine(tenant_id)
+                .into_iter()
+                .flat_map(|engine| rejected(engine, 0, 0, 0))
+                .try_for_each(|rejection| {
+                    rejections.write_tenant_rejection(tenant_id, &rejection)
+                })?;
+        }
+        rejections.flush()?;
+    }
+
+    executed?;
+
+    if let Some(tenant_reports_path) = &options.tenant_reports {
+        std::fs::create_dir_all(tenant_reports_path).map_err(|e| {
+            TxError::IoError(format!(
+                "Unable to create report directory [{}]: {}",
+                tenant_reports_path, e
+            ))
+        })?;
+
+        for tenant_id in engine.tenants() {
+            let report_path = Path::new(tenant_reports_path).join(format!("{}.csv", tenant_id));
+            let mut report =
+                CsvAccountReport::from_writer(create_file(&report_path.to_string_lossy())?)?;
+            let query = options.account_query();
+            engine
+                .engine(tenant_id)
+                .into_iter()
+                .flat_map(|engine| engine.query(&query))
+                .try_for_each(|account| report.write_account(&account))?;
+            report.flush()?;
+        }
+    }
+
+    let mut csv_report = CsvAccountReport::from_writer_with_tenants(output_sink)?;
+    engine
+        .query(&options.account_query())
+        .try_for_each(|account| csv_report.write_tenant_account(&account))?;
+
+    csv_report.flush()
+}
+
+/// Sets up the credit limits and rules of the engine of a tenant, either those of its tenant
+/// config or the ones given for all tenants.
+async fn configure_tenant(
+    options: &Options,
+    engine: &mut TransactionEngine,
+    config: Option<&TenantConfig>,
+) -> TxResult<()> {
+    let credit_limits = config
+        .and_then(|config| config.credit_limits.as_ref())
+        .or(options.credit_limits.as_ref());
+    if let Some(credit_limits_path) = credit_limits {
+        set_credit_limits(engine, credit_limits_path, options.id_kind()).await?;
+    }
+
+    let rules = config
+        .and_then(|config| config.rules.as_ref())
+        .or(options.rules.as_ref());
+    if let Some(rules_path) = rules {
+        add_rules(engine, rules_path).await?;
+    }
+
+    Ok(())
+}
+
+async fn set_credit_limits(
+    engine: &mut TransactionEngine,
+    credit_limits_path: &str,
+    id_kind: IdKind,
+) -> TxResult<()> {
+    let mut limits_source = CsvCreditLimitSource::from_reader(open_file(credit_limits_path).await?)
+        .await?
+        .with_id_kind(id_kind);
+    while let Some(credit_limit) = limits_source.read().await? {
+        engine.set_credit_limit(credit_limit.client_id, credit_limit.limit)?;
+    }
+
+    Ok(())
+}
+
+async fn add_rules(engine: &mut TransactionEngine, rules_path: &str) -> TxResult<()> {
+    let mut rule_source = CsvRuleSource::from_reader(open_file(rules_path).await?).await?;
+    while let Some(configured_rule) = rule_source.read().await? {
+        engine.add_rule(configured_rule.rule, configured_rule.action);
+    }
+
+    Ok(())
+}
+
+/// The dispute steps the engine ignored and the scheduled transactions that failed once their
+/// value date was reached, skipping those before the given positions (e.g. of the history),
+/// ordered by their index relative to the first sequence.
+fn rejected(
+    engine: &TransactionEngine,
+    first_sequence: u64,
+    first_ignored: usize,
+    first_settlement_failure: usize,
+) -> Vec<Rejection> {
+    let ignored = engine.ignored_transitions()[first_ignored..]
+        .iter()
+        .map(|ignored| Rejection {
+            index: (ignored.sequence - first_sequence) as usize,
+            tx_id: ignored.tx_id,
+            client_id: ignored.client_id,
+            error: ignored.error(),
+        });
+    let failed = engine.settlement_failures()[first_settlement_failure..]
+        .iter()
+        .map(|failure| Rejection {
+            index: (failure.sequence - first_sequence) as usize,
+            tx_id: failure.tx_id,
+            client_id: failure.client_id,
+            error: failure.error.clone(),
+        });
+    let mut rows = ignored.chain(failed).collect::<Vec<_>>();
+    rows.sort_by_key(|rejection| rejection.index);
+
+    rows
+}
+
+async fn read_all<S: TransactionSource>(source: &mut S) -> TxResult<Vec<Transaction>> {
+    let mut transactions = Vec::new();
+    while let Some(record) = source.read().await? {
+        transactions.push(record);
+    }
+
+    Ok(transactions)
+}
+
+async fn open_file(path: &str) -> TxResult<File> {
+    File::open(path)
+        .await
+        .map_err(|e| TxError::IoError(format!("Unable to open source file [{}]: {}", path, e)))
+}
+
+type EventReport = (
+    mpsc::Receiver<AccountEvent>,
+    NdjsonEventReport<std::fs::File>,
+);
+
+fn write_events(event_report: &mut Option<EventReport>) -> TxResult<()> {
+    if let Some((receiver, report)) = event_report {
+        receiver
+            .try_iter()
+            .try_for_each(|event| report.write_event(&event))?;
+    }
+
+    Ok(())
+}
+
+/// Refreshes the account gauges and rewrites the metrics file, if metrics are enabled. The file
+/// is replaced at once, so readers never see a partially written file.
+fn write_metrics(
+    options: &Options,
+    metrics: &Option<Metrics>,
+    gauges: impl FnOnce() -> EngineGauges,
+) -> TxResult<()> {
+    let Some(metrics) = metrics else {
+        return Ok(());
+    };
+    metrics.set_gauges(gauges());
+
+    if let Some(metrics_path) = &options.metrics_file {
+        let temporary_path = format!("{}.tmp", metrics_path);
+        create_file(&temporary_path)?
+            .write_all(metrics.render().as_bytes())
+            .and_then(|_| std::fs::rename(&temporary_path, metrics_path))
+            .map_err(|e| {
+                TxError::IoError(format!(
+                    "Unable to write metrics file [{}]: {}",
+                    metrics_path, e
+                ))
+            })?;
+    }
+
+    Ok(())
+}
+
+fn create_file(path: &str) -> TxResult<std::fs::File> {
+    std::fs::File::create(path)
+        .map_err(|e| TxError::IoError(format!("Unable to create report file [{}]: {}", path, e)))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use clap::Parser;
+    use serde_json::json;
+    use tracing_subscriber::fmt::MakeWriter;
     use tx_engine::test_resource_path;
 
-    use crate::run;
+    use crate::{run, Options};
 
     #[tokio::test]
     async fn test_happy_path() {
-        let csv_report = String::from_utf8(
-            run(
-                test_resource_path!("sources/valid/given-example.csv"),
-                Vec::<u8>::new(),
-            )
-            .await
-            .unwrap(),
-        )
-        .unwrap();
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,1.5,0,0,1.5,false,open,0,0\n2,1.0,0,0,1.0,false,open,0,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_global_tx_id_scope_rejects_reused_ids() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/reused-tx-ids.csv"),
+            "--tx-id-scope",
+            "global",
+        ]);
+
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "DuplicateTransaction(\"Attempt to execute a transaction [1] for account [2], but its id was already used.\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uuid_and_alphanumeric_ids() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/mixed-ids.csv"),
+            "--id-kind",
+            "mixed",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n70000,0.0,2.0,0,2.0,false,open,0,0\n1f0c2c8e-6a57-4c3e-9a5b-2b8d7d0f3e11,3.5,0,0,3.5,false,open,0,0\nacme-ltd,6.0,0.0,0,6.0,false,open,0,0\n"
+        );
+
+        // only integers are accepted by default
+        let options =
+            Options::parse_from(["tx-cli", test_resource_path!("sources/valid/mixed-ids.csv")]);
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [ INV-0001] for column [tx]: invalid digit found in string (line: 4, byte: 91, record: 3).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_card_authorizations() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/card-payments.csv"),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,13.5,0,0.0,13.5,false,open,0,0\n2,0.5,0,2.5,3.0,false,open,0,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_transactions() {
+        let directory = tempfile::tempdir().unwrap();
+        let pending_path = directory.path().join("pending.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/value-dates.csv"),
+            "--business-date",
+            "2024-03-01",
+            "--pending",
+            pending_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,12.0,0,0,12.0,false,open,0,0\n2,1.0,0,0,1.0,false,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(pending_path).unwrap(),
+            "value_date,type,client,tx,amount,to_client\n2024-03-05,transfer,1,6,2.0,2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_settlements_are_reported() {
+        let directory = tempfile::tempdir().unwrap();
+        let rejections_path = directory.path().join("rejections.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/failed-settlement.csv"),
+            "--business-date",
+            "2024-03-01",
+            "--rejections",
+            rejections_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,7.0,0,0,7.0,false,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rejections_path).unwrap(),
+            "index,tx,client,error\n2,2,1,\"InvalidArgument(\"\"Attempt to withdraw an amount [12.0] greater than balance [10.0] in transaction [2] for account [1].\"\")\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_lifecycle() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/account-lifecycle.csv"),
+            "--account-opening",
+            "explicit",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,0.0,0,0,0,false,closed,0,0\n2,0.0,0,0,0,false,closed,0,0\n3,0,0,0,0,false,open,0,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_representments() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/representments.csv"),
+            "--unlock-on-representment",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,6.0,0,0,6.0,false,open,0,0\n2,5.0,0,0,5.0,false,open,0,0\n"
+        );
+
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/representments.csv"),
+            "--max-dispute-cycles",
+            "1",
+        ]);
+        let error = run(&options, Vec::<u8>::new()).await.unwrap_err();
+
+        assert_eq!(
+            format!("{:?}", error),
+            "InvalidOperation(\"Attempt to dispute transaction [3] for account [2] more than [1] times.\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_amount_precision() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/amount-precision.csv"),
+            "--max-decimal-places",
+            "4",
+            "--rounding",
+            "half-even",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,0.7346,0,0,0.7346,false,open,0,0\n"
+        );
+
+        for (args, expected_error) in [
+            (vec!["--max-decimal-places", "4"], "InvalidArgument(\"Could not parse value [1.23456] for column [amount]: Expected at most [4] decimal places (line: 3, byte: 42, record: 2).\")"),
+            (
+                vec![
+                    "--max-decimal-places",
+                    "4",
+                    "--rounding",
+                    "down",
+                    "--reject-zero-amounts",
+                ],
+                "InvalidArgument(\"Could not parse value [0.00004] for column [amount]: Zero amounts are not allowed (line: 4, byte: 62, record: 3).\")",
+            ),
+            (
+                vec!["--min-amount", "1"],
+                "InvalidArgument(\"Could not parse value [0.00004] for column [amount]: Expected an amount of at least [1] (line: 4, byte: 62, record: 3).\")",
+            ),
+        ] {
+            let options = Options::parse_from(
+                [
+                    "tx-cli",
+                    test_resource_path!("sources/valid/amount-precision.csv"),
+                ]
+                .into_iter()
+                .chain(args),
+            );
+            let error = run(&options, Vec::<u8>::new()).await.unwrap_err();
+
+            assert_eq!(format!("{:?}", error), expected_error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disk_ledger() {
+        let directory = tempfile::tempdir().unwrap();
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--ledger-dir",
+            directory.path().to_str().unwrap(),
+            "--ledger-cache-size",
+            "1",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,1.5,0,0,1.5,false,open,0,0\n2,1.0,0,0,1.0,false,open,0,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispute_window() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/late-dispute.csv"),
+            "--dispute-window-seconds",
+            "86400",
+        ]);
+
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "DisputeWindowExpired(\"Attempt to dispute transaction [1] for account [1] after its dispute window has passed.\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_credit_limits() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--credit-limits",
+            test_resource_path!("limits/valid/credit-limits.csv"),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
 
         assert_eq!(
             csv_report.as_str(),
-            "client,available,held,total,locked\n1,1.5,0,1.5,false\n2,1.0,0,1.0,false\n"
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,1.5,0,0,1.5,false,open,0,0\n2,1.0,0,0,1.0,false,open,10,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fees() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--fee-schedule",
+            test_resource_path!("fees/valid/fee-schedule.csv"),
+            "--client-tiers",
+            test_resource_path!("fees/valid/client-tiers.csv"),
+            "--house-account",
+            "9",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,1.4,0,0,1.4,false,open,0,0.1\n2,1.0,0,0,1.0,false,open,0,0\n9,0.1,0,0,0.1,false,open,0,0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interest_accrual() {
+        let directory = tempfile::tempdir().unwrap();
+        let accrual_report_path = directory.path().join("accruals.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/accruals.csv"),
+            "--interest-rates",
+            test_resource_path!("interest/valid/interest-rates.csv"),
+            "--accrue-interest",
+            "101",
+            "--accrual-report",
+            accrual_report_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,1000.2,0,0,1000.2,false,open,0,0\n2,0.5,0,0,0.5,false,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(accrual_report_path).unwrap(),
+            "tx,client,balance,rate,interest\n100,1,1000,3.65,0.1\n101,1,1000.1,3.65,0.1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rules() {
+        let directory = tempfile::tempdir().unwrap();
+        let rule_audit_path = directory.path().join("rule-audit.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/rapid-withdrawals.csv"),
+            "--rules",
+            test_resource_path!("rules/valid/rules.csv"),
+            "--rule-audit",
+            rule_audit_path.to_str().unwrap(),
+        ]);
+
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "RuleViolation(\"Transaction [4] for account [2] was rejected by rule [max_withdrawals]: Client already made [1] withdrawals within the last [10] transactions.\")"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rule_audit_path).unwrap(),
+            "tx,client,rule,outcome,action,reason\n\
+             1,1,max_deposit,passed,flag,\n\
+             1,1,max_withdrawals,passed,reject,\n\
+             2,2,max_deposit,violated,flag,Deposit of [4.0] exceeds the maximum of [3]\n\
+             2,2,max_withdrawals,passed,reject,\n\
+             3,2,max_deposit,passed,flag,\n\
+             3,2,max_withdrawals,passed,reject,\n\
+             4,2,max_deposit,passed,flag,\n\
+             4,2,max_withdrawals,violated,reject,Client already made [1] withdrawals within the last [10] transactions\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let directory = tempfile::tempdir().unwrap();
+        let events_path = directory.path().join("events.ndjson");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--events",
+            events_path.to_str().unwrap(),
+        ]);
+        run(&options, Vec::<u8>::new()).await.unwrap();
+
+        let events = std::fs::read_to_string(events_path).unwrap();
+        let lines = events.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[4],
+            "{\"type\":\"debited\",\"amount\":\"3.0\",\"tx\":5,\"client\":2,\"before\":{\"client\":2,\"available\":\"4.0\",\"held\":\"0\",\"authorized\":\"0\",\"total\":\"4.0\",\"locked\":false,\"status\":\"open\",\"credit_limit\":\"0\",\"fees\":\"0\"},\"after\":{\"client\":2,\"available\":\"1.0\",\"held\":\"0\",\"authorized\":\"0\",\"total\":\"1.0\",\"locked\":false,\"status\":\"open\",\"credit_limit\":\"0\",\"fees\":\"0\"}}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_general_ledger() {
+        let directory = tempfile::tempdir().unwrap();
+        let journal_path = directory.path().join("journal.csv");
+        let trial_balance_path = directory.path().join("trial-balance.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--journal",
+            journal_path.to_str().unwrap(),
+            "--trial-balance",
+            trial_balance_path.to_str().unwrap(),
+        ]);
+        run(&options, Vec::<u8>::new()).await.unwrap();
+
+        let journal = std::fs::read_to_string(journal_path).unwrap();
+        assert_eq!(journal.lines().count(), 11);
+        assert_eq!(journal.lines().nth(1), Some("1,1,settlement,1.0,0"));
+        assert_eq!(
+            std::fs::read_to_string(trial_balance_path).unwrap(),
+            "account,debit,credit\nsettlement,2.5,0\nclient:1,0,1.5\nclient:2,0,1.0\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_atomic_input_is_rolled_back() {
+        let directory = tempfile::tempdir().unwrap();
+        let events_path = directory.path().join("events.ndjson");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/overdraft.csv"),
+            "--atomic",
+            "--events",
+            events_path.to_str().unwrap(),
+        ]);
+
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "BatchRolledBack(\"Batch was rolled back as transaction [3] at index [2] failed: InvalidArgument(\\\"Attempt to withdraw an amount [10.0] greater than balance [5.0] in transaction [3] for account [1].\\\")\")"
+        );
+        assert_eq!(std::fs::read_to_string(events_path).unwrap(), "");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let directory = tempfile::tempdir().unwrap();
+        let rejections_path = directory.path().join("rejections.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/corrections.csv"),
+            "--history",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--dry-run",
+            "--rejections",
+            rejections_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,total,locked,status,available_delta,held_delta,total_delta\n1,1.5,0,1.5,false,open,0.0,0,0.0\n2,-3.0,4.0,1.0,false,open,-4.0,4.0,0.0\n3,0.5,0,0.5,false,open,0.5,0,0.5\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rejections_path).unwrap(),
+            "index,tx,client,error\n2,7,1,\"InvalidArgument(\"\"Attempt to withdraw an amount [5.0] greater than balance [1.5] in transaction [7] for account [1].\"\")\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ignored_transitions() {
+        let directory = tempfile::tempdir().unwrap();
+        let rejections_path = directory.path().join("rejections.csv");
+        let stats_path = directory.path().join("stats.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/ignored-disputes.csv"),
+            "--rejections",
+            rejections_path.to_str().unwrap(),
+            "--stats",
+            stats_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,10.0,0.0,0,10.0,false,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rejections_path).unwrap(),
+            "index,tx,client,error\n1,1,1,\"InvalidTransition(\"\"Attempt to resolve transaction [1] for account [1] in state [normal].\"\")\"\n3,1,1,\"InvalidTransition(\"\"Attempt to dispute transaction [1] for account [1] in state [disputed].\"\")\"\n4,2,1,\"InvalidTransition(\"\"Attempt to charge back unknown transaction [2] for account [1].\"\")\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(stats_path).unwrap(),
+            "event,outcome,from,count\ndispute,applied,,1\ndispute,ignored_invalid_state,disputed,1\nresolve,applied,,1\nresolve,ignored_invalid_state,normal,1\nchargeback,ignored_unknown_tx,,1\n"
+        );
+
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/ignored-disputes.csv"),
+            "--strict-transitions",
+        ]);
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "InvalidTransition(\"Attempt to resolve transaction [1] for account [1] in state [normal].\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let metrics_path = directory.path().join("metrics.prom");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/ignored-disputes.csv"),
+            "--metrics-file",
+            metrics_path.to_str().unwrap(),
+            "--metrics-addr",
+            "127.0.0.1:0",
+            "--strict-transitions",
+        ]);
+
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "InvalidTransition(\"Attempt to resolve transaction [1] for account [1] in state [normal].\")"
+        );
+
+        let metrics = std::fs::read_to_string(metrics_path).unwrap();
+        assert!(metrics.contains("tx_transactions_total{kind=\"deposit\"} 1\n"));
+        assert!(metrics.contains("tx_transactions_total{kind=\"resolve\"} 1\n"));
+        assert!(metrics.contains("tx_rejections_total{error=\"invalid_transition\"} 1\n"));
+        assert!(metrics.contains("tx_accounts 1\n"));
+        assert!(metrics.contains("tx_ledger_entries 1\n"));
+        assert!(metrics.contains("tx_parse_duration_seconds_count 2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_multi_tenant() {
+        let directory = tempfile::tempdir().unwrap();
+        let tenant_reports_path = directory.path().join("tenants");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/tenants.csv"),
+            "--multi-tenant",
+            "--default-tenant",
+            "initech",
+            "--tenant-reports",
+            tenant_reports_path.to_str().unwrap(),
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "tenant,client,available,held,authorized,total,locked,status,credit_limit,fees\nacme,1,7.0,0,0,7.0,false,open,0,0\nglobex,1,0,0.0,0,0,true,open,0,0\ninitech,2,1.0,0,0,1.0,false,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(tenant_reports_path.join("globex.csv")).unwrap(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,0,0.0,0,0,true,open,0,0\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(tenant_reports_path.join("initech.csv")).unwrap(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n2,1.0,0,0,1.0,false,open,0,0\n"
+        );
+
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/tenants.csv"),
+            "--multi-tenant",
+        ]);
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "InvalidArgument(\"Expected a value for column [tenant] (line: 8, byte: 158, record: 7).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_tenant_config() {
+        let directory = tempfile::tempdir().unwrap();
+        let rejections_path = directory.path().join("rejections.csv");
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/tenant-rules.csv"),
+            "--multi-tenant",
+            "--tenant-config",
+            test_resource_path!("tenants/valid/tenant-config.csv"),
+            "--rejections",
+            rejections_path.to_str().unwrap(),
+        ]);
+
+        // the withdrawal rule only applies to acme, the credit limit only to globex
+        assert_eq!(
+            format!("{:?}", run(&options, Vec::<u8>::new()).await.unwrap_err()),
+            "RuleViolation(\"Transaction [3] for account [2] was rejected by rule [max_withdrawals]: Client already made [1] withdrawals within the last [10] transactions.\")"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rejections_path).unwrap(),
+            "tenant,index,tx,client,error\nglobex,3,1,2,\"InvalidTransition(\"\"Attempt to resolve transaction [1] for account [2] in state [normal].\"\")\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/account-states.csv"),
+            "--filter",
+            "locked",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n3,0,0.0,0,0,true,open,0,0\n"
+        );
+
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/account-states.csv"),
+            "--filter",
+            "held",
+            "--filter",
+            "negative-available",
+        ]);
+        let csv_report = String::from_utf8(run(&options, Vec::<u8>::new()).await.unwrap()).unwrap();
+
+        assert_eq!(
+            csv_report.as_str(),
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n4,-4.0,5.0,0,1.0,false,open,0,0\n"
+        );
+
+        let error = Options::try_parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/account-states.csv"),
+            "--filter",
+            "total-above=x",
+        ])
+        .unwrap_err();
+
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_log_of_one_client() {
+        let options = Options::parse_from([
+            "tx-cli",
+            test_resource_path!("sources/valid/given-example.csv"),
+            "--log-level",
+            "[execute{client=2}]=trace",
+            "--log-format",
+            "json",
+        ]);
+        let logs = LogBuffer::default();
+        let _default =
+            tracing::subscriber::set_default(options.log_subscriber(logs.clone()).unwrap());
+
+        run(&options, Vec::<u8>::new()).await.unwrap();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let spans = logs
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["span"].clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            spans,
+            vec![
+                json!({"name": "execute", "client": 2, "tx": 2, "kind": "deposit"}),
+                json!({"name": "execute", "client": 2, "tx": 5, "kind": "withdrawal"}),
+            ]
         );
     }
 }
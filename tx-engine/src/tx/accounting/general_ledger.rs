@@ -0,0 +1,481 @@
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+
+use crate::tx::accounting::gl_account::GlAccount;
+use crate::tx::accounting::journal_entry::{JournalEntry, Posting};
+use crate::tx::engine::account::AccountSummary;
+use crate::tx::engine::id::TxId;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+
+/// A line of the trial balance, the balance of an account is either on the debit or on the
+/// credit side.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TrialBalanceLine {
+    pub account: GlAccount,
+    pub debit: Decimal,
+    pub credit: Decimal,
+}
+
+/// A double-entry bookkeeping of all changes of the client accounts. The available funds of each
+/// client are a liability of its own while held funds are pooled in [`GlAccount::DisputesHeld`]
+/// and [`GlAccount::AuthorizationsHeld`].
+/// Any change of the client funds is offset in [`GlAccount::Settlement`], so the settlement
+/// account always matches the total of all client accounts. Funds the bank credits out of its
+/// own pocket are additionally booked as an expense against [`GlAccount::BankFunds`].
+#[derive(Debug, Default)]
+pub struct GeneralLedger {
+    balances: BTreeMap<GlAccount, Decimal>,
+    journal: Vec<JournalEntry>,
+    /// The funds each journal entry moved into or out of the client accounts, derived from the
+    /// transactions instead of the accounts, e.g. the amount of a deposit. Used to cross-check
+    /// the settlement account in [`GeneralLedger::reconcile`].
+    flows: Vec<Decimal>,
+}
+
+impl GeneralLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Posts a journal entry, which is rejected if its debits and credits are not balanced or
+    /// if any balance would exceed the supported range. Nothing is posted in that case.
+    pub fn post(&mut self, tx_id: TxId, postings: Vec<Posting>) -> TxResult<()> {
+        self.post_with_flow(tx_id, postings, Decimal::ZERO)
+    }
+
+    fn post_with_flow(
+        &mut self,
+        tx_id: TxId,
+        postings: Vec<Posting>,
+        flow: Decimal,
+    ) -> TxResult<()> {
+        let overflow = || Self::overflow_error(tx_id);
+        let debits =
+            Self::checked_sum(postings.iter().map(|posting| posting.debit)).ok_or_else(overflow)?;
+        let credits = Self::checked_sum(postings.iter().map(|posting| posting.credit))
+            .ok_or_else(overflow)?;
+
+        if debits != credits {
+            return Err(TxError::InvalidOperation(format!(
+                "Attempt to post an unbalanced journal entry for transaction [{}] with debits [{}] and credits [{}].",
+                tx_id, debits, credits
+            )));
+        }
+
+        let mut balances = BTreeMap::new();
+        for posting in &postings {
+            let balance = balances
+                .get(&posting.account)
+                .copied()
+                .unwrap_or_else(|| self.balance(posting.account));
+            let balance = balance.checked_add(posting.amount()).ok_or_else(overflow)?;
+            balances.insert(posting.account, balance);
+        }
+
+        self.balances.extend(balances);
+        self.journal.push(JournalEntry {
+            entry_id: self.journal.len() as u64 + 1,
+            tx_id,
+            postings,
+        });
+        self.flows.push(flow);
+
+        Ok(())
+    }
+
+    fn checked_sum(amounts: impl IntoIterator<Item = Decimal>) -> Option<Decimal> {
+        amounts
+            .into_iter()
+            .try_fold(Decimal::ZERO, |sum, amount| sum.checked_add(amount))
+    }
+
+    fn overflow_error(tx_id: TxId) -> TxError {
+        TxError::Overflow(format!(
+            "Attempt to book transaction [{}] to the general ledger beyond the supported range.",
+            tx_id
+        ))
+    }
+
+    /// Books the changes a transaction caused to client accounts, given as their states before
+    /// and after the transaction.
+    pub fn record(
+        &mut self,
+        transaction: &Transaction,
+        changes: &[(AccountSummary, AccountSummary)],
+    ) -> TxResult<()> {
+        let overflow = || Self::overflow_error(transaction.tx_id());
+        let mut amounts = BTreeMap::<GlAccount, Decimal>::new();
+        let mut add = |account: GlAccount, amount: Decimal| -> TxResult<()> {
+            let sum = amounts.entry(account).or_default();
+            *sum = sum.checked_add(amount).ok_or_else(overflow)?;
+            Ok(())
+        };
+        let mut client_funds = Decimal::ZERO;
+
+        for (before, after) in changes {
+            let available = after.available.checked_sub(before.available);
+            let held = after.held.checked_sub(before.held);
+            let authorized = after.authorized.checked_sub(before.authorized);
+            let (Some(available), Some(held), Some(authorized)) = (available, held, authorized)
+            else {
+                return Err(overflow());
+            };
+
+            // liabilities increase with credits
+            add(GlAccount::Client(after.id), -available)?;
+            add(GlAccount::DisputesHeld, -held)?;
+            add(GlAccount::AuthorizationsHeld, -authorized)?;
+            client_funds = Self::checked_sum([client_funds, available, held, authorized])
+                .ok_or_else(overflow)?;
+        }
+        add(GlAccount::Settlement, client_funds)?;
+
+        let expense = match transaction.kind() {
+            TransactionKind::Chargeback(_) if client_funds > Decimal::ZERO => {
+                Some(GlAccount::ChargebackLoss)
+            }
+            // recovers the loss booked when a withdrawal was charged back
+            TransactionKind::Representment if client_funds < Decimal::ZERO => {
+                Some(GlAccount::ChargebackLoss)
+            }
+            TransactionKind::AccrueInterest if client_funds > Decimal::ZERO => {
+                Some(GlAccount::InterestExpense)
+            }
+            _ => None,
+        };
+        if let Some(expense) = expense {
+            add(expense, client_funds)?;
+            add(GlAccount::BankFunds, -client_funds)?;
+        }
+
+        // deposits and withdrawals are counted with their own amount while transfers and
+        // disputes only move funds between client accounts, fees go to the house account
+        let flow = match transaction.kind() {
+            _ if client_funds.is_zero() => Decimal::ZERO,
+            TransactionKind::Deposit(amount) => amount,
+            TransactionKind::Withdrawal(amount) => -amount,
+            TransactionKind::Transfer { .. } | TransactionKind::Dispute(_) => Decimal::ZERO,
+            // e.g. the payout of an account once a resolve completes its closing
+            _ => client_funds,
+        };
+
+        let postings = amounts
+            .into_iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(account, amount)| Posting::signed(account, amount))
+            .collect::<Vec<_>>();
+
+        if postings.is_empty() {
+            return Ok(());
+        }
+
+        self.post_with_flow(transaction.tx_id(), postings, flow)
+    }
+
+    /// Removes all journal entries but the first `len` ones and reverses their postings, e.g. if
+    /// the transactions they were booked for are rolled back.
+    pub fn truncate(&mut self, len: usize) {
+        self.flows.truncate(len);
+        for entry in self.journal.drain(len.min(self.journal.len())..) {
+            for posting in &entry.postings {
+                *self.balances.entry(posting.account).or_default() -= posting.amount();
+            }
+        }
+    }
+
+    /// The balance of an account with debits being positive and credits negative.
+    pub fn balance(&self, account: GlAccount) -> Decimal {
+        self.balances.get(&account).copied().unwrap_or_default()
+    }
+
+    pub fn journal(&self) -> &[JournalEntry] {
+        &self.journal
+    }
+
+    /// Returns the balances of all accounts, ordered by account. Debits and credits of all
+    /// lines always sum up to the same amount.
+    pub fn trial_balance(&self) -> Vec<TrialBalanceLine> {
+        self.balances
+            .iter()
+            .filter(|(_, balance)| !balance.is_zero())
+            .map(|(account, balance)| {
+                let posting = Posting::signed(*account, *balance);
+                TrialBalanceLine {
+                    account: *account,
+                    debit: posting.debit,
+                    credit: posting.credit,
+                }
+            })
+            .collect()
+    }
+
+    /// The funds moved into the client accounts minus the funds moved out of them, summed up
+    /// from the booked transactions rather than from the balances of the accounts.
+    pub fn net_flow(&self) -> TxResult<Decimal> {
+        Self::checked_sum(self.flows.iter().copied()).ok_or_else(|| {
+            TxError::Overflow(
+                "Attempt to sum up the funds moved into client accounts beyond the supported range."
+                    .to_string(),
+            )
+        })
+    }
+
+    /// Verifies that the ledger matches the given client accounts, i.e. that the settlement
+    /// account equals the total of all client accounts as well as the funds deposited minus the
+    /// funds withdrawn by the booked transactions.
+    pub fn reconcile(&self, accounts: &[AccountSummary]) -> TxResult<()> {
+        let sum = |amount: fn(&AccountSummary) -> Decimal, name: &str| {
+            Self::checked_sum(accounts.iter().map(amount)).ok_or_else(|| {
+                TxError::Overflow(format!(
+                    "Attempt to sum up the {} funds of all client accounts beyond the supported range.",
+                    name
+                ))
+            })
+        };
+        let total = sum(|account| account.total, "total")?;
+        let held = sum(|account| account.held, "held")?;
+        let authorized = sum(|account| account.authorized, "authorized")?;
+        let net_flow = self.net_flow()?;
+
+        if net_flow != self.balance(GlAccount::Settlement) {
+            return Err(TxError::InvalidOperation(format!(
+                "The funds moved into client accounts [{}] do not match the settlement account [{}].",
+                net_flow,
+                self.balance(GlAccount::Settlement)
+            )));
+        }
+
+        if total != self.balance(GlAccount::Settlement) {
+            return Err(TxError::InvalidOperation(format!(
+                "The total of all client accounts [{}] does not match the settlement account [{}].",
+                total,
+                self.balance(GlAccount::Settlement)
+            )));
+        }
+
+        if held != -self.balance(GlAccount::DisputesHeld) {
+            return Err(TxError::InvalidOperation(format!(
+                "The held funds of all client accounts [{}] do not match the disputes held account [{}].",
+                held,
+                -self.balance(GlAccount::DisputesHeld)
+            )));
+        }
+
+        if authorized != -self.balance(GlAccount::AuthorizationsHeld) {
+            return Err(TxError::InvalidOperation(format!(
+                "The authorized funds of all client accounts [{}] do not match the authorizations held account [{}].",
+                authorized,
+                -self.balance(GlAccount::AuthorizationsHeld)
+            )));
+        }
+
+        for account in accounts {
+            let balance = -self.balance(GlAccount::Client(account.id));
+            if account.available != balance {
+                return Err(TxError::InvalidOperation(format!(
+                    "The available funds [{}] of account [{}] do not match its ledger account [{}].",
+                    account.available, account.id, balance
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use crate::tx::accounting::general_ledger::GeneralLedger;
+    use crate::tx::accounting::gl_account::GlAccount;
+    use crate::tx::accounting::journal_entry::Posting;
+    use crate::tx::engine::account::Account;
+    use crate::tx::engine::transaction::Transaction;
+
+    #[test]
+    fn test_rejects_unbalanced_entries() {
+        let mut ledger = GeneralLedger::new();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                ledger
+                    .post(
+                        1.into(),
+                        vec![
+                            Posting::signed(GlAccount::Settlement, dec!(10)),
+                            Posting::signed(GlAccount::Client(1.into()), dec!(-9)),
+                        ]
+                    )
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to post an unbalanced journal entry for transaction [1] with debits [10] and credits [9].\")"
+        );
+        assert!(ledger.journal().is_empty());
+    }
+
+    fn book<F>(ledger: &mut GeneralLedger, account: &mut Account, transaction: Transaction, op: F)
+    where
+        F: FnOnce(&mut Account),
+    {
+        let before = account.summary();
+        op(account);
+        ledger
+            .record(&transaction, &[(before, account.summary())])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_withdrawal_chargeback_is_a_loss() {
+        let mut ledger = GeneralLedger::new();
+        let mut account = Account::new(1);
+
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_deposit(1, 1, dec!(10)),
+            |account| account.deposit(1.into(), dec!(10)).unwrap(),
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_withdrawal(2, 1, dec!(4)),
+            |account| account.withdraw(2.into(), dec!(4)).unwrap(),
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_dispute(2, 1),
+            |account| {
+                account.dispute(2.into()).unwrap();
+            },
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_charge_back(2, 1),
+            |account| {
+                account.chargeback(2.into()).unwrap();
+            },
+        );
+
+        assert_eq!(ledger.journal().len(), 4);
+        assert_eq!(ledger.balance(GlAccount::Settlement), dec!(10));
+        assert_eq!(ledger.balance(GlAccount::Client(1.into())), dec!(-10));
+        assert_eq!(ledger.balance(GlAccount::DisputesHeld), dec!(0));
+        assert_eq!(ledger.balance(GlAccount::ChargebackLoss), dec!(4));
+        assert_eq!(ledger.balance(GlAccount::BankFunds), dec!(-4));
+        ledger.reconcile(&[account.summary()]).unwrap();
+
+        let trial_balance = ledger.trial_balance();
+        assert_eq!(
+            trial_balance.iter().map(|line| line.debit).sum::<Decimal>(),
+            trial_balance
+                .iter()
+                .map(|line| line.credit)
+                .sum::<Decimal>()
+        );
+    }
+
+    #[test]
+    fn test_representment_recovers_the_chargeback_loss() {
+        let mut ledger = GeneralLedger::new();
+        let mut account = Account::new(1);
+
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_deposit(1, 1, dec!(10)),
+            |account| account.deposit(1.into(), dec!(10)).unwrap(),
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_withdrawal(2, 1, dec!(4)),
+            |account| account.withdraw(2.into(), dec!(4)).unwrap(),
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_dispute(2, 1),
+            |account| {
+                account.dispute(2.into()).unwrap();
+            },
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_charge_back(2, 1),
+            |account| {
+                account.chargeback(2.into()).unwrap();
+            },
+        );
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_representment(2, 1),
+            |account| {
+                account.represent(2.into()).unwrap();
+            },
+        );
+
+        assert_eq!(ledger.journal().len(), 5);
+        assert_eq!(ledger.balance(GlAccount::Settlement), dec!(6));
+        assert_eq!(ledger.balance(GlAccount::Client(1.into())), dec!(-6));
+        assert_eq!(ledger.balance(GlAccount::ChargebackLoss), dec!(0));
+        assert_eq!(ledger.balance(GlAccount::BankFunds), dec!(0));
+        ledger.reconcile(&[account.summary()]).unwrap();
+    }
+
+    #[test]
+    fn test_overflow_is_rejected_before_posting() {
+        let mut ledger = GeneralLedger::new();
+        let amount = Decimal::MAX - dec!(1);
+
+        for client_id in [1, 2] {
+            let mut account = Account::new(client_id);
+            let before = account.summary();
+            account.deposit(client_id.into(), amount).unwrap();
+            let result = ledger.record(
+                &Transaction::new_deposit(client_id, client_id, amount),
+                &[(before, account.summary())],
+            );
+
+            if client_id == 2 {
+                assert_eq!(
+                    format!("{:?}", result.unwrap_err()),
+                    "Overflow(\"Attempt to book transaction [2] to the general ledger beyond the supported range.\")"
+                );
+            }
+        }
+
+        assert_eq!(ledger.journal().len(), 1);
+        assert_eq!(ledger.balance(GlAccount::Settlement), amount);
+        assert_eq!(ledger.balance(GlAccount::Client(2.into())), dec!(0));
+        assert_eq!(ledger.net_flow().unwrap(), amount);
+    }
+
+    #[test]
+    fn test_reconcile_checks_the_booked_transactions() {
+        let mut ledger = GeneralLedger::new();
+        let mut account = Account::new(1);
+
+        // the account and the ledger agree with each other, but not with the deposit
+        book(
+            &mut ledger,
+            &mut account,
+            Transaction::new_deposit(1, 1, dec!(10)),
+            |account| account.deposit(1.into(), dec!(9)).unwrap(),
+        );
+
+        assert_eq!(ledger.balance(GlAccount::Settlement), dec!(9));
+        assert_eq!(
+            format!("{:?}", ledger.reconcile(&[account.summary()]).unwrap_err()),
+            "InvalidOperation(\"The funds moved into client accounts [10] do not match the settlement account [9].\")"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter};
+
+use crate::tx::engine::id::ClientId;
+
+/// An account of the general ledger. Balances are kept with debits being positive, so asset and
+/// expense accounts have positive balances while liability accounts have negative ones.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum GlAccount {
+    /// Asset, the funds held for all clients.
+    Settlement,
+    /// Liability, the funds of all clients that are held because of disputes.
+    DisputesHeld,
+    /// Liability, the funds of all clients that are held by card authorizations.
+    AuthorizationsHeld,
+    /// Expense, funds the bank credited to clients because of withdrawal chargebacks.
+    ChargebackLoss,
+    /// Expense, interest paid to clients.
+    InterestExpense,
+    /// Equity, the bank's own funds paying for chargeback losses and interest.
+    BankFunds,
+    /// Liability, the available funds of a client.
+    Client(ClientId),
+}
+
+impl Display for GlAccount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlAccount::Settlement => write!(f, "settlement"),
+            GlAccount::DisputesHeld => write!(f, "disputes_held"),
+            GlAccount::AuthorizationsHeld => write!(f, "authorizations_held"),
+            GlAccount::ChargebackLoss => write!(f, "chargeback_loss"),
+            GlAccount::InterestExpense => write!(f, "interest_expense"),
+            GlAccount::BankFunds => write!(f, "bank_funds"),
+            GlAccount::Client(client_id) => write!(f, "client:{}", client_id),
+        }
+    }
+}
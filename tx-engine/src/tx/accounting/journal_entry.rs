@@ -0,0 +1,36 @@
+use rust_decimal::Decimal;
+
+use crate::tx::accounting::gl_account::GlAccount;
+use crate::tx::engine::id::TxId;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Posting {
+    pub account: GlAccount,
+    pub debit: Decimal,
+    pub credit: Decimal,
+}
+
+/// The postings of a single transaction, debits and credits always sum up to the same amount.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JournalEntry {
+    /// Consecutive number of the entry within the journal, starting at 1.
+    pub entry_id: u64,
+    pub tx_id: TxId,
+    pub postings: Vec<Posting>,
+}
+
+impl Posting {
+    /// Creates a posting from an amount with debits being positive and credits negative.
+    pub fn signed(account: GlAccount, amount: Decimal) -> Self {
+        Self {
+            account,
+            debit: amount.max(Decimal::ZERO),
+            credit: (-amount).max(Decimal::ZERO),
+        }
+    }
+
+    /// The amount of the posting with debits being positive and credits negative.
+    pub fn amount(&self) -> Decimal {
+        self.debit - self.credit
+    }
+}
@@ -0,0 +1,3 @@
+pub mod general_ledger;
+pub mod gl_account;
+pub mod journal_entry;
@@ -1,64 +1,219 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::Serialize;
+use tracing::{info, instrument};
 
+use crate::tx::engine::dispute_window::{DisputeWindow, LedgerPosition};
+use crate::tx::engine::fee_schedule::Fee;
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::ledger_entry::{LedgerEntry, LedgerEntryKind, LedgerEntryState};
 use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::transition::{DisputeEvent, TransitionOutcome};
+use crate::tx::ledger::in_memory_ledger_store::InMemoryLedgerStore;
+use crate::tx::ledger::ledger_store::LedgerStore;
+
+/// The stage of its lifecycle an account is in, independent of whether it is locked.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    #[default]
+    Open,
+    /// The client had no activity for the dormancy period. Withdrawals, outgoing transfers and
+    /// authorizations are rejected until the client deposits again or the account is reopened.
+    Dormant,
+    /// Closing was requested, only disputes, captures and releases are executed until all of
+    /// them are settled and the remaining funds are paid out.
+    Closing,
+    /// The remaining funds were paid out, no further transactions are executed.
+    Closed,
+}
+
+impl Display for AccountStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountStatus::Open => write!(f, "open"),
+            AccountStatus::Dormant => write!(f, "dormant"),
+            AccountStatus::Closing => write!(f, "closing"),
+            AccountStatus::Closed => write!(f, "closed"),
+        }
+    }
+}
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
 pub struct AccountSummary {
-    pub id: u16,
+    #[serde(rename = "client")]
+    pub id: ClientId,
     pub available: Decimal,
     pub held: Decimal,
+    pub authorized: Decimal,
     pub total: Decimal,
+    #[serde(rename = "locked")]
     pub is_locked: bool,
+    pub status: AccountStatus,
+    pub credit_limit: Decimal,
+    pub fees: Decimal,
 }
 
-#[derive(Debug, Clone)]
+/// The statuses in which disputes, captures and releases are still executed.
+const SETTLING: &[AccountStatus] = &[
+    AccountStatus::Open,
+    AccountStatus::Dormant,
+    AccountStatus::Closing,
+];
+
+#[derive(Debug)]
 pub struct Account {
-    ledger: HashMap<u32, LedgerEntry>,
-    id: u16,
+    ledger: Box<dyn LedgerStore>,
+    id: ClientId,
     available: Decimal,
     held: Decimal,
+    authorized: Decimal,
     is_locked: bool,
+    // locked by [`Account::lock`] rather than by a chargeback, a representment doesn't unlock it
+    is_locked_permanently: bool,
+    credit_limit: Decimal,
+    fees: Decimal,
+    open_disputes: usize,
+    // ledger entries with a charged back amount that was not represented
+    chargebacks: usize,
+    dispute_window: Option<DisputeWindow>,
+    max_dispute_cycles: Option<u32>,
+    unlock_on_representment: bool,
+    position: LedgerPosition,
+    // only `Open`, `Closing` or `Closed`, dormancy is derived from the last activity
+    status: AccountStatus,
+    dormancy: Option<DisputeWindow>,
+    last_activity: Option<LedgerPosition>,
+    // the transaction the remaining funds are paid out with once the account is closed
+    closing_tx_id: Option<TxId>,
+    // nested checkpoints, the innermost one last
+    checkpoints: Vec<Checkpoint>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-enum LedgerEntryState {
-    Normal,
-    Disputed,
-    ChargedBack,
+/// The balances of an account, or the changes made to them by an operation.
+#[derive(Debug, Copy, Clone, Default)]
+struct Balances {
+    available: Decimal,
+    held: Decimal,
+    authorized: Decimal,
+    fees: Decimal,
 }
 
-#[derive(Debug, Clone)]
-struct LedgerEntry {
-    amount: Decimal,
-    state: LedgerEntryState,
+/// The state of an account when a checkpoint was taken, see [`Account::checkpoint`].
+#[derive(Debug)]
+struct Checkpoint {
+    available: Decimal,
+    held: Decimal,
+    authorized: Decimal,
+    is_locked: bool,
+    is_locked_permanently: bool,
+    credit_limit: Decimal,
+    fees: Decimal,
+    open_disputes: usize,
+    chargebacks: usize,
+    position: LedgerPosition,
+    status: AccountStatus,
+    last_activity: Option<LedgerPosition>,
+    closing_tx_id: Option<TxId>,
+    // ledger entries changed since the checkpoint as they were before, `None` for new entries
+    entries: HashMap<TxId, Option<LedgerEntry>>,
 }
 
 impl Account {
-    pub fn new(id: u16) -> Self {
+    pub fn new(id: impl Into<ClientId>) -> Self {
+        Self::with_ledger(id.into(), Box::new(InMemoryLedgerStore::new()))
+    }
+
+    pub fn with_ledger(id: ClientId, ledger: Box<dyn LedgerStore>) -> Self {
         Self {
-            ledger: HashMap::new(),
+            ledger,
             available: dec!(0),
             held: dec!(0),
+            authorized: dec!(0),
             is_locked: false,
+            is_locked_permanently: false,
+            credit_limit: dec!(0),
+            fees: dec!(0),
+            open_disputes: 0,
+            chargebacks: 0,
+            dispute_window: None,
+            max_dispute_cycles: None,
+            unlock_on_representment: false,
+            position: LedgerPosition::default(),
+            status: AccountStatus::Open,
+            dormancy: None,
+            last_activity: None,
+            closing_tx_id: None,
+            checkpoints: Vec::new(),
             id,
         }
     }
 
+    /// Allows withdrawals and outgoing transfers to take the available funds negative down to
+    /// `-limit`. Disputes are always applied, even if they exceed the limit.
+    pub fn set_credit_limit(&mut self, limit: Decimal) -> TxResult<()> {
+        if limit < dec!(0) {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to set a negative credit limit [{}] for account [{}].",
+                limit, self.id
+            )));
+        }
+
+        self.credit_limit = limit;
+
+        Ok(())
+    }
+
+    pub fn set_dispute_window(&mut self, dispute_window: Option<DisputeWindow>) {
+        self.dispute_window = dispute_window;
+    }
+
+    /// How often a ledger entry can be disputed, further disputes are rejected. If unset,
+    /// entries can be disputed again after each resolve without limit.
+    pub fn set_max_dispute_cycles(&mut self, max_dispute_cycles: Option<u32>) {
+        self.max_dispute_cycles = max_dispute_cycles;
+    }
+
+    /// Whether a representment unlocks the account once no other entry is charged back. An
+    /// account locked by [`Account::lock`] stays locked.
+    pub fn set_unlock_on_representment(&mut self, unlock_on_representment: bool) {
+        self.unlock_on_representment = unlock_on_representment;
+    }
+
+    /// Open accounts without deposits, withdrawals, transfers or authorizations for this long
+    /// become dormant. If unset, accounts never become dormant.
+    pub fn set_dormancy(&mut self, dormancy: Option<DisputeWindow>) {
+        self.dormancy = dormancy;
+    }
+
+    /// Moves the account to the given position in the input. New ledger entries are recorded at
+    /// this position and the dispute window is measured from it. The dormancy period of a new
+    /// account starts with the first position.
+    pub fn advance(&mut self, position: LedgerPosition) {
+        self.position = position;
+        self.last_activity.get_or_insert(position);
+    }
+
     pub fn summary(&self) -> AccountSummary {
         AccountSummary {
             id: self.id(),
             available: self.available(),
             held: self.held(),
+            authorized: self.authorized(),
             total: self.total(),
             is_locked: self.is_locked(),
+            status: self.status(),
+            credit_limit: self.credit_limit(),
+            fees: self.fees(),
         }
     }
 
     pub fn total(&self) -> Decimal {
-        self.available + self.held
+        self.available + self.held + self.authorized
     }
 
     pub fn available(&self) -> Decimal {
@@ -69,16 +224,151 @@ impl Account {
         self.held
     }
 
+    /// The funds held by card authorizations that were neither captured nor released yet. Kept
+    /// apart from the funds held by disputes.
+    pub fn authorized(&self) -> Decimal {
+        self.authorized
+    }
+
     pub fn is_locked(&self) -> bool {
         self.is_locked
     }
 
-    pub fn id(&self) -> u16 {
+    pub fn status(&self) -> AccountStatus {
+        self.status_at(self.position)
+    }
+
+    /// The status of the account at a later position, an open account becomes dormant once the
+    /// dormancy period since its last activity has passed.
+    pub fn status_at(&self, position: LedgerPosition) -> AccountStatus {
+        match (self.status, self.dormancy, self.last_activity) {
+            (AccountStatus::Open, Some(dormancy), Some(last_activity))
+                if dormancy.is_expired(last_activity, position) =>
+            {
+                AccountStatus::Dormant
+            }
+            (status, ..) => status,
+        }
+    }
+
+    pub fn credit_limit(&self) -> Decimal {
+        self.credit_limit
+    }
+
+    /// The fees charged to the account so far, less the fees refunded on chargebacks.
+    pub fn fees(&self) -> Decimal {
+        self.fees
+    }
+
+    /// The number of ledger entries that are currently disputed.
+    pub fn open_disputes(&self) -> usize {
+        self.open_disputes
+    }
+
+    /// The number of ledger entries kept for the account, see [`Account::compact`].
+    pub fn ledger_entries(&self) -> usize {
+        self.ledger.len()
+    }
+
+    /// The number of ledger entries that were charged back, fully or partially, and not
+    /// represented.
+    pub fn chargebacks(&self) -> usize {
+        self.chargebacks
+    }
+
+    /// Locks the account, no further transactions are executed for it.
+    pub fn lock(&mut self) {
+        if !self.is_locked {
+            info!(client = self.id.as_field(), "account locked");
+        }
+        self.is_locked = true;
+        self.is_locked_permanently = true;
+    }
+
+    pub fn id(&self) -> ClientId {
         self.id
     }
 
-    fn require_unique_transaction(&self, tx_id: u32) -> TxResult<()> {
-        if self.ledger.contains_key(&tx_id) {
+    /// Remembers the current state of the account, so all subsequent changes can be undone by
+    /// [`Account::rollback`] until they are kept by [`Account::commit`]. Checkpoints can be
+    /// nested, e.g. for a single transaction within a batch.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(Checkpoint {
+            available: self.available,
+            held: self.held,
+            authorized: self.authorized,
+            is_locked: self.is_locked,
+            is_locked_permanently: self.is_locked_permanently,
+            credit_limit: self.credit_limit,
+            fees: self.fees,
+            open_disputes: self.open_disputes,
+            chargebacks: self.chargebacks,
+            position: self.position,
+            status: self.status,
+            last_activity: self.last_activity,
+            closing_tx_id: self.closing_tx_id,
+            entries: HashMap::new(),
+        });
+    }
+
+    /// Keeps all changes since the last checkpoint, they can still be undone by rolling back an
+    /// enclosing checkpoint.
+    pub fn commit(&mut self) {
+        let Some(checkpoint) = self.checkpoints.pop() else {
+            return;
+        };
+
+        if let Some(outer) = self.checkpoints.last_mut() {
+            for (tx_id, entry) in checkpoint.entries {
+                outer.entries.entry(tx_id).or_insert(entry);
+            }
+        }
+    }
+
+    /// Undoes all changes since the last checkpoint, including the ledger entries. Does nothing
+    /// if there is no checkpoint.
+    pub fn rollback(&mut self) -> TxResult<()> {
+        let Some(checkpoint) = self.checkpoints.pop() else {
+            return Ok(());
+        };
+
+        for (tx_id, entry) in checkpoint.entries {
+            match entry {
+                Some(entry) => self.ledger.put(tx_id, entry)?,
+                None => self.ledger.remove(tx_id)?,
+            }
+        }
+
+        self.available = checkpoint.available;
+        self.held = checkpoint.held;
+        self.authorized = checkpoint.authorized;
+        self.is_locked = checkpoint.is_locked;
+        self.is_locked_permanently = checkpoint.is_locked_permanently;
+        self.credit_limit = checkpoint.credit_limit;
+        self.fees = checkpoint.fees;
+        self.open_disputes = checkpoint.open_disputes;
+        self.chargebacks = checkpoint.chargebacks;
+        self.position = checkpoint.position;
+        self.status = checkpoint.status;
+        self.last_activity = checkpoint.last_activity;
+        self.closing_tx_id = checkpoint.closing_tx_id;
+
+        Ok(())
+    }
+
+    /// Records a ledger entry, remembering the previous entry if there is a checkpoint.
+    fn put_entry(&mut self, tx_id: TxId, entry: LedgerEntry) -> TxResult<()> {
+        if let Some(checkpoint) = self.checkpoints.last_mut() {
+            if let Entry::Vacant(vacant) = checkpoint.entries.entry(tx_id) {
+                vacant.insert(self.ledger.get(tx_id)?);
+            }
+        }
+
+        self.ledger.put(tx_id, entry)
+    }
+
+    fn require_unique_transaction(&mut self, tx_id: TxId) -> TxResult<()> {
+        if self.ledger.contains(tx_id)? || self.ledger.is_expired(tx_id)? {
             Err(TxError::InvalidOperation(format!(
                 "Attempt to execute a transaction [{}] twice for account [{}].",
                 tx_id, self.id
@@ -88,6 +378,49 @@ impl Account {
         }
     }
 
+    fn require_disputable(&self, tx_id: TxId, entry: &LedgerEntry) -> TxResult<()> {
+        if entry.kind == LedgerEntryKind::Interest {
+            Err(TxError::InvalidOperation(format!(
+                "Attempt to dispute interest [{}] posted to account [{}].",
+                tx_id, self.id
+            )))
+        } else if entry.kind == LedgerEntryKind::Payout {
+            Err(TxError::InvalidOperation(format!(
+                "Attempt to dispute the payout [{}] of closing account [{}].",
+                tx_id, self.id
+            )))
+        } else if entry.kind != LedgerEntryKind::Transaction {
+            Err(TxError::InvalidOperation(format!(
+                "Attempt to dispute authorization [{}] of account [{}] which was not captured.",
+                tx_id, self.id
+            )))
+        } else if self.is_expired(entry) {
+            Err(self.dispute_window_expired_error(tx_id))
+        } else if let Some(max) = self
+            .max_dispute_cycles
+            .filter(|max| entry.dispute_cycles >= *max)
+        {
+            Err(TxError::InvalidOperation(format!(
+                "Attempt to dispute transaction [{}] for account [{}] more than [{}] times.",
+                tx_id, self.id, max
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn dispute_window_expired_error(&self, tx_id: TxId) -> TxError {
+        TxError::DisputeWindowExpired(format!(
+            "Attempt to dispute transaction [{}] for account [{}] after its dispute window has passed.",
+            tx_id, self.id
+        ))
+    }
+
+    fn is_expired(&self, entry: &LedgerEntry) -> bool {
+        self.dispute_window
+            .is_some_and(|window| window.is_expired(entry.recorded_at, self.position))
+    }
+
     fn require_unlocked(&self) -> TxResult<()> {
         if self.is_locked {
             Err(TxError::InvalidOperation(format!(
@@ -99,9 +432,91 @@ impl Account {
         }
     }
 
-    pub fn withdraw(&mut self, tx_id: u32, amount: Decimal) -> TxResult<()> {
+    fn require_status(&self, tx_id: TxId, allowed: &[AccountStatus]) -> TxResult<()> {
+        let status = self.status();
+        if allowed.contains(&status) {
+            Ok(())
+        } else {
+            Err(TxError::InvalidOperation(format!(
+                "Attempt to execute a transaction [{}] on {} account [{}].",
+                tx_id, status, self.id
+            )))
+        }
+    }
+
+    /// Deposits, withdrawals, transfers and authorizations keep the account from becoming
+    /// dormant, or make a dormant account open again.
+    fn record_activity(&mut self) {
+        self.last_activity = Some(self.position);
+    }
+
+    fn overflow_error(&self) -> TxError {
+        TxError::Overflow(format!(
+            "Attempt to change the balance of account [{}] beyond the supported range.",
+            self.id
+        ))
+    }
+
+    fn checked(&self, value: Option<Decimal>) -> TxResult<Decimal> {
+        value.ok_or_else(|| self.overflow_error())
+    }
+
+    /// Applies a change to the balances with checked arithmetic, without changing the account.
+    /// Fails if any balance or the total would exceed the supported range.
+    fn balances_after(&self, change: Balances) -> TxResult<Balances> {
+        let balances = Balances {
+            available: self.checked(self.available.checked_add(change.available))?,
+            held: self.checked(self.held.checked_add(change.held))?,
+            authorized: self.checked(self.authorized.checked_add(change.authorized))?,
+            fees: self.checked(self.fees.checked_add(change.fees))?,
+        };
+        self.checked(
+            balances
+                .available
+                .checked_add(balances.held)
+                .and_then(|total| total.checked_add(balances.authorized)),
+        )?;
+
+        Ok(balances)
+    }
+
+    fn set_balances(&mut self, balances: Balances) {
+        self.available = balances.available;
+        self.held = balances.held;
+        self.authorized = balances.authorized;
+        self.fees = balances.fees;
+    }
+
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = %amount)
+    )]
+    pub fn withdraw(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
+        self.require_withdrawable(tx_id, amount, dec!(0))?;
+        let balances = self.balances_after(Balances {
+            available: -amount,
+            ..Balances::default()
+        })?;
+
+        self.put_entry(tx_id, LedgerEntry::new(-amount, self.position))?;
+        self.set_balances(balances);
+        self.record_activity();
+
+        Ok(())
+    }
+
+    /// Checks whether [`Account::withdraw`] followed by [`Account::charge_fee`] would succeed,
+    /// without changing the account.
+    pub fn require_withdrawable(
+        &mut self,
+        tx_id: TxId,
+        amount: Decimal,
+        fee: Decimal,
+    ) -> TxResult<()> {
         self.require_unique_transaction(tx_id)?;
         self.require_unlocked()?;
+        self.require_status(tx_id, &[AccountStatus::Open])?;
 
         if amount < dec!(0) {
             return Err(TxError::InvalidArgument(format!(
@@ -110,28 +525,55 @@ impl Account {
             )));
         }
 
-        if amount > self.available {
-            return Err(TxError::InvalidArgument(format!(
-                "Attempt to withdraw an amount [{}] greater than balance [{}] in transaction [{}] for account [{}].",
-                amount, self.available, tx_id, self.id
-            )));
+        let debit = self.checked(amount.checked_add(fee))?;
+        let funds = self.checked(self.available.checked_add(self.credit_limit))?;
+        if debit > funds {
+            let mut message = format!("Attempt to withdraw an amount [{}]", amount);
+            if fee != dec!(0) {
+                message += &format!(" plus fee [{}]", fee);
+            }
+            message += &format!(" greater than balance [{}]", self.available);
+            if self.credit_limit != dec!(0) {
+                message += &format!(" plus credit limit [{}]", self.credit_limit);
+            }
+            message += &format!(" in transaction [{}] for account [{}].", tx_id, self.id);
+
+            return Err(TxError::InvalidArgument(message));
         }
 
-        self.ledger.insert(
-            tx_id,
-            LedgerEntry {
-                amount: -amount,
-                state: LedgerEntryState::Normal,
-            },
-        );
-        self.available -= amount;
+        self.balances_after(Balances {
+            available: -debit,
+            fees: fee,
+            ..Balances::default()
+        })?;
+
+        Ok(())
+    }
+
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = %amount)
+    )]
+    pub fn deposit(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
+        self.require_depositable(tx_id, amount)?;
+        let balances = self.balances_after(Balances {
+            available: amount,
+            ..Balances::default()
+        })?;
+
+        self.put_entry(tx_id, LedgerEntry::new(amount, self.position))?;
+        self.set_balances(balances);
+        self.record_activity();
 
         Ok(())
     }
 
-    pub fn deposit(&mut self, tx_id: u32, amount: Decimal) -> TxResult<()> {
+    /// Checks whether [`Account::deposit`] would succeed, without changing the account.
+    pub fn require_depositable(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
         self.require_unique_transaction(tx_id)?;
         self.require_unlocked()?;
+        self.require_status(tx_id, &[AccountStatus::Open, AccountStatus::Dormant])?;
 
         if amount < dec!(0) {
             return Err(TxError::InvalidArgument(format!(
@@ -140,105 +582,730 @@ impl Account {
             )));
         }
 
-        self.ledger.insert(
-            tx_id,
-            LedgerEntry {
-                amount,
-                state: LedgerEntryState::Normal,
-            },
-        );
-        self.available += amount;
+        self.balances_after(Balances {
+            available: amount,
+            ..Balances::default()
+        })?;
 
         Ok(())
     }
 
-    pub fn dispute(&mut self, tx_id: u32) -> TxResult<()> {
-        self.require_unlocked()?;
+    /// Posts interest as a ledger entry of its own, see [`LedgerEntryKind::Interest`].
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = %amount)
+    )]
+    pub fn post_interest(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
+        self.require_depositable(tx_id, amount)?;
+        let balances = self.balances_after(Balances {
+            available: amount,
+            ..Balances::default()
+        })?;
+
+        let mut entry = LedgerEntry::new(amount, self.position);
+        entry.kind = LedgerEntryKind::Interest;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
 
-        if let Ok(entry) = self.get_tx_record(tx_id).cloned() {
-            if entry.state != LedgerEntryState::Normal {
-                return Ok(());
-            }
+        Ok(())
+    }
 
-            // disputing a deposit means the bank doesn't wanna unlock the credited funds yet
-            self.get_tx_record(tx_id)?.state = LedgerEntryState::Disputed;
-            self.available -= entry.amount;
-            self.held += entry.amount;
-        }
+    /// Moves the amount from the available funds into a hold until the authorization is captured
+    /// or released. Funds are checked like for withdrawals.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = %amount)
+    )]
+    pub fn authorize(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
+        self.require_withdrawable(tx_id, amount, dec!(0))?;
+        let balances = self.balances_after(Balances {
+            available: -amount,
+            authorized: amount,
+            ..Balances::default()
+        })?;
+
+        let mut entry = LedgerEntry::new(-amount, self.position);
+        entry.kind = LedgerEntryKind::Authorization;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.record_activity();
 
         Ok(())
     }
 
-    pub fn resolve(&mut self, tx_id: u32) -> TxResult<()> {
+    pub fn capture(&mut self, tx_id: TxId) -> TxResult<()> {
+        self.capture_with_fee(tx_id, None, Fee::default())
+            .map(|_| ())
+    }
+
+    /// Captures only a part of an authorization, the remainder is released.
+    pub fn capture_partially(&mut self, tx_id: TxId, amount: Decimal) -> TxResult<()> {
+        self.capture_with_fee(tx_id, Some(amount), Fee::default())
+            .map(|_| ())
+    }
+
+    /// Withdraws the given amount of an authorization (or all of it) and charges the withdrawal
+    /// fee for it, the remainder of the authorization is returned to the available funds. From
+    /// then on the entry is a withdrawal recorded at the current position, which can be disputed.
+    /// Returns the charged fee.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        name = "capture",
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = amount.map(display))
+    )]
+    pub fn capture_with_fee(
+        &mut self,
+        tx_id: TxId,
+        amount: Option<Decimal>,
+        fee: Fee,
+    ) -> TxResult<Decimal> {
+        self.require_unlocked()?;
+        self.require_status(tx_id, SETTLING)?;
+
+        let mut entry = self.open_authorization("capture", tx_id)?;
+        let authorized = -entry.amount;
+        let captured = amount.unwrap_or(authorized);
+        self.require_valid_partial_amount("capture", tx_id, captured, authorized)?;
+
+        let released = authorized - captured;
+        let charged_fee = fee.amount_for(captured)?;
+        let funds = self.checked(
+            self.available
+                .checked_add(released)
+                .and_then(|funds| funds.checked_add(self.credit_limit)),
+        )?;
+        if charged_fee > funds {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to capture an amount [{}] plus fee [{}] greater than balance [{}] in transaction [{}] for account [{}].",
+                captured,
+                charged_fee,
+                self.available + released,
+                tx_id,
+                self.id
+            )));
+        }
+
+        let balances = self.balances_after(Balances {
+            available: released - charged_fee,
+            authorized: -authorized,
+            fees: charged_fee,
+            ..Balances::default()
+        })?;
+
+        entry.kind = LedgerEntryKind::Transaction;
+        entry.amount = -captured;
+        entry.recorded_at = self.position;
+        entry.fee = self.checked(entry.fee.checked_add(charged_fee))?;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.finish_closing()?;
+
+        Ok(charged_fee)
+    }
+
+    /// Returns the funds of an authorization to the available funds, it can't be captured
+    /// anymore.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field())
+    )]
+    pub fn release(&mut self, tx_id: TxId) -> TxResult<()> {
         self.require_unlocked()?;
+        self.require_status(tx_id, SETTLING)?;
 
-        if let Ok(entry) = self.get_tx_record(tx_id).cloned() {
-            if entry.state != LedgerEntryState::Disputed {
-                return Ok(());
-            }
+        let mut entry = self.open_authorization("release", tx_id)?;
+        let authorized = -entry.amount;
+        let balances = self.balances_after(Balances {
+            available: authorized,
+            authorized: -authorized,
+            ..Balances::default()
+        })?;
+
+        entry.kind = LedgerEntryKind::ReleasedAuthorization;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+
+        self.finish_closing()
+    }
 
-            // resolving a deposit dispute means the bank doesn't unlocked the credited funds
-            self.get_tx_record(tx_id)?.state = LedgerEntryState::Normal;
-            self.available += entry.amount;
-            self.held -= entry.amount;
+    fn open_authorization(&mut self, operation: &str, tx_id: TxId) -> TxResult<LedgerEntry> {
+        match self.ledger.get(tx_id)? {
+            Some(entry) if entry.kind == LedgerEntryKind::Authorization => Ok(entry),
+            _ => Err(TxError::InvalidOperation(format!(
+                "Attempt to {} transaction [{}] for account [{}] which is not an open authorization.",
+                operation, tx_id, self.id
+            ))),
         }
+    }
+
+    /// Withdraws the amount as the outgoing leg of a transfer to another account.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            client = self.id.as_field(),
+            tx = tx_id.as_field(),
+            to_client = to_client.as_field(),
+            amount = %amount,
+        )
+    )]
+    pub fn transfer_out(
+        &mut self,
+        tx_id: TxId,
+        to_client: ClientId,
+        amount: Decimal,
+    ) -> TxResult<()> {
+        self.require_withdrawable(tx_id, amount, dec!(0))?;
+        let balances = self.balances_after(Balances {
+            available: -amount,
+            ..Balances::default()
+        })?;
+
+        let mut entry = LedgerEntry::new(-amount, self.position);
+        entry.counterparty = Some(to_client);
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.record_activity();
+
+        Ok(())
+    }
+
+    /// Deposits the amount as the incoming leg of a transfer from another account.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            client = self.id.as_field(),
+            tx = tx_id.as_field(),
+            from_client = from_client.as_field(),
+            amount = %amount,
+        )
+    )]
+    pub fn transfer_in(
+        &mut self,
+        tx_id: TxId,
+        from_client: ClientId,
+        amount: Decimal,
+    ) -> TxResult<()> {
+        self.require_depositable(tx_id, amount)?;
+        let balances = self.balances_after(Balances {
+            available: amount,
+            ..Balances::default()
+        })?;
+
+        let mut entry = LedgerEntry::new(amount, self.position);
+        entry.counterparty = Some(from_client);
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.record_activity();
+
+        Ok(())
+    }
+
+    /// Charges a fee for an executed transaction, the fee is refunded if the transaction gets
+    /// charged back. Funds need to be checked beforehand, see [`Account::require_withdrawable`].
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), fee = %fee)
+    )]
+    pub fn charge_fee(&mut self, tx_id: TxId, fee: Decimal) -> TxResult<()> {
+        let mut entry = self
+            .ledger
+            .get(tx_id)?
+            .ok_or(TxError::InvalidOperation(format!(
+                "Attempt to charge a fee for unknown transaction [{}] for account [{}].",
+                tx_id, self.id
+            )))?;
+
+        let balances = self.balances_after(Balances {
+            available: -fee,
+            fees: fee,
+            ..Balances::default()
+        })?;
+
+        entry.fee = self.checked(entry.fee.checked_add(fee))?;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+
+        Ok(())
+    }
+
+    /// Books fees collected from (or refunded to, if negative) other accounts as revenue of this
+    /// account. Revenue is booked even if the account is locked.
+    #[instrument(level = "trace", skip_all, fields(client = self.id.as_field(), amount = %amount))]
+    pub fn book_fee_revenue(&mut self, amount: Decimal) -> TxResult<()> {
+        let balances = self.balances_after_fee_revenue(amount)?;
+        self.set_balances(balances);
 
         Ok(())
     }
 
-    pub fn chargeback(&mut self, tx_id: u32) -> TxResult<()> {
+    /// Checks that fee revenue can be booked to this account without exceeding the supported
+    /// range and that refunds don't exceed the available funds, so other accounts are only
+    /// changed if it can.
+    pub fn require_fee_revenue(&self, amount: Decimal) -> TxResult<()> {
+        self.balances_after_fee_revenue(amount).map(|_| ())
+    }
+
+    fn balances_after_fee_revenue(&self, amount: Decimal) -> TxResult<Balances> {
+        let balances = self.balances_after(Balances {
+            available: amount,
+            ..Balances::default()
+        })?;
+        if amount < dec!(0) && balances.available < dec!(0) {
+            return Err(TxError::InvalidOperation(format!(
+                "Attempt to refund fees [{}] greater than the available balance [{}] of account [{}].",
+                -amount, self.available, self.id
+            )));
+        }
+
+        Ok(balances)
+    }
+
+    /// Returns the other account involved if the transaction is a transfer.
+    pub fn counterparty(&mut self, tx_id: TxId) -> TxResult<Option<ClientId>> {
+        Ok(self.ledger.get(tx_id)?.and_then(|entry| entry.counterparty))
+    }
+
+    pub fn dispute(&mut self, tx_id: TxId) -> TxResult<TransitionOutcome> {
+        self.dispute_amount(tx_id, None)
+    }
+
+    /// Disputes only a part of a deposit or withdrawal, the amount must not exceed what was not
+    /// charged back yet.
+    pub fn dispute_partially(
+        &mut self,
+        tx_id: TxId,
+        amount: Decimal,
+    ) -> TxResult<TransitionOutcome> {
+        self.dispute_amount(tx_id, Some(amount))
+    }
+
+    #[instrument(
+        level = "trace",
+        skip_all,
+        name = "dispute",
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = amount.map(display))
+    )]
+    fn dispute_amount(
+        &mut self,
+        tx_id: TxId,
+        amount: Option<Decimal>,
+    ) -> TxResult<TransitionOutcome> {
         self.require_unlocked()?;
+        self.require_status(tx_id, SETTLING)?;
 
-        if let Ok(entry) = self.get_tx_record(tx_id).cloned() {
-            if entry.state != LedgerEntryState::Disputed {
-                return Ok(());
-            }
+        let entry = self.ledger.get(tx_id)?;
+        if entry.is_none() && self.ledger.is_expired(tx_id)? {
+            return Err(self.dispute_window_expired_error(tx_id));
+        }
+        let outcome = TransitionOutcome::of(
+            entry.as_ref().map(|entry| entry.state),
+            DisputeEvent::Dispute,
+        );
+        let Some(mut entry) = entry.filter(|_| outcome == TransitionOutcome::Applied) else {
+            return Ok(outcome);
+        };
+
+        self.require_disputable(tx_id, &entry)?;
+
+        let disputed = amount.unwrap_or(entry.disputable_amount());
+        self.require_valid_partial_amount("dispute", tx_id, disputed, entry.disputable_amount())?;
+
+        // disputing a deposit means the bank doesn't wanna unlock the credited funds yet
+        let balances = self.balances_after(Balances {
+            available: -entry.directed(disputed),
+            held: entry.directed(disputed),
+            ..Balances::default()
+        })?;
+
+        entry.state = LedgerEntryState::Disputed;
+        entry.disputed = disputed;
+        entry.dispute_cycles = entry.dispute_cycles.saturating_add(1);
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.open_disputes += 1;
+
+        Ok(TransitionOutcome::Applied)
+    }
+
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field())
+    )]
+    pub fn resolve(&mut self, tx_id: TxId) -> TxResult<TransitionOutcome> {
+        self.require_unlocked()?;
+        self.require_status(tx_id, SETTLING)?;
+
+        let entry = self.ledger.get(tx_id)?;
+        let outcome = TransitionOutcome::of(
+            entry.as_ref().map(|entry| entry.state),
+            DisputeEvent::Resolve,
+        );
+        let Some(mut entry) = entry.filter(|_| outcome == TransitionOutcome::Applied) else {
+            return Ok(outcome);
+        };
+
+        // resolving a deposit dispute means the bank doesn't unlocked the credited funds
+        let disputed = entry.directed(entry.disputed);
+        let balances = self.balances_after(Balances {
+            available: disputed,
+            held: -disputed,
+            ..Balances::default()
+        })?;
+
+        entry.disputed = dec!(0);
+        entry.state = entry.settled_state();
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.open_disputes -= 1;
+        self.finish_closing()?;
+
+        Ok(TransitionOutcome::Applied)
+    }
+
+    pub fn chargeback(&mut self, tx_id: TxId) -> TxResult<TransitionOutcome> {
+        self.chargeback_with_fee(tx_id, None, Fee::default())
+            .map(|(outcome, _)| outcome)
+    }
+
+    /// Charges back only a part of the disputed amount, the remainder of the dispute is resolved.
+    /// The entry can be disputed again as long as it was not charged back completely.
+    pub fn chargeback_partially(
+        &mut self,
+        tx_id: TxId,
+        amount: Decimal,
+    ) -> TxResult<TransitionOutcome> {
+        self.chargeback_with_fee(tx_id, Some(amount), Fee::default())
+            .map(|(outcome, _)| outcome)
+    }
+
+    /// Charges back the given amount of the dispute (or all of it) and charges the chargeback
+    /// fee for the charged back amount. The fee charged for the transaction itself is refunded
+    /// once the entry is charged back completely, partial chargebacks don't refund any of it.
+    /// Returns the outcome together with the net fee revenue, i.e. the charged fee less the
+    /// refunded fee.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        name = "chargeback",
+        fields(client = self.id.as_field(), tx = tx_id.as_field(), amount = amount.map(display))
+    )]
+    pub fn chargeback_with_fee(
+        &mut self,
+        tx_id: TxId,
+        amount: Option<Decimal>,
+        fee: Fee,
+    ) -> TxResult<(TransitionOutcome, Decimal)> {
+        self.require_unlocked()?;
+        self.require_status(tx_id, SETTLING)?;
 
-            // deposit charge back means the bank didn't accept the funds
-            self.get_tx_record(tx_id)?.state = LedgerEntryState::ChargedBack;
-            self.held -= entry.amount;
+        let entry = self.ledger.get(tx_id)?;
+        let outcome = TransitionOutcome::of(
+            entry.as_ref().map(|entry| entry.state),
+            DisputeEvent::Chargeback,
+        );
+        let Some(mut entry) = entry.filter(|_| outcome == TransitionOutcome::Applied) else {
+            return Ok((outcome, dec!(0)));
+        };
+
+        let charged_back = amount.unwrap_or(entry.disputed);
+        self.require_valid_partial_amount("charge back", tx_id, charged_back, entry.disputed)?;
+
+        // deposit charge back means the bank didn't accept the funds
+        let disputed = entry.directed(entry.disputed);
+        let released = entry.directed(entry.disputed - charged_back);
+        let refunded_fee = if charged_back == entry.disputable_amount() {
+            entry.fee
+        } else {
+            dec!(0)
+        };
+        let charged_fee = fee.amount_for(charged_back)?;
+        let balances = self.balances_after(Balances {
+            available: self.checked(
+                released
+                    .checked_add(refunded_fee)
+                    .and_then(|available| available.checked_sub(charged_fee)),
+            )?,
+            held: -disputed,
+            fees: charged_fee - refunded_fee,
+            ..Balances::default()
+        })?;
+
+        if entry.charged_back == dec!(0) && charged_back > dec!(0) {
+            self.chargebacks += 1;
+        }
+        entry.charged_back = self.checked(entry.charged_back.checked_add(charged_back))?;
+        entry.disputed = dec!(0);
+        entry.chargeback_fee = self.checked(entry.chargeback_fee.checked_add(charged_fee))?;
+        entry.state = entry.settled_state();
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.open_disputes -= 1;
+        if charged_back > dec!(0) {
             self.is_locked = true;
         }
+        self.finish_closing()?;
+
+        Ok((TransitionOutcome::Applied, charged_fee - refunded_fee))
+    }
+
+    pub fn represent(&mut self, tx_id: TxId) -> TxResult<TransitionOutcome> {
+        self.represent_with_fee(tx_id).map(|(outcome, _)| outcome)
+    }
+
+    /// Reverses the chargeback of an entry after the merchant contested it successfully. The
+    /// charged back amount is returned to the available funds, the fee of the transaction is
+    /// charged again if it was refunded by the chargeback, chargeback fees are kept as they are.
+    /// Returns the outcome together with the fee revenue, i.e. the charged transaction fee. Can
+    /// be executed on a locked account, which is unlocked once no other entry is charged back if
+    /// enabled by [`Account::set_unlock_on_representment`].
+    #[instrument(
+        level = "trace",
+        skip_all,
+        name = "represent",
+        fields(client = self.id.as_field(), tx = tx_id.as_field())
+    )]
+    pub fn represent_with_fee(&mut self, tx_id: TxId) -> TxResult<(TransitionOutcome, Decimal)> {
+        self.require_status(tx_id, SETTLING)?;
+
+        let entry = self.ledger.get(tx_id)?;
+        let outcome = TransitionOutcome::of(
+            entry.as_ref().map(|entry| entry.state),
+            DisputeEvent::Representment,
+        );
+        let Some(mut entry) = entry.filter(|_| outcome == TransitionOutcome::Applied) else {
+            return Ok((outcome, dec!(0)));
+        };
+
+        // only a complete chargeback refunds the fee of the transaction
+        let charged_fee = if entry.state == LedgerEntryState::ChargedBack {
+            entry.fee
+        } else {
+            dec!(0)
+        };
+        let balances = self.balances_after(Balances {
+            available: self.checked(entry.directed(entry.charged_back).checked_sub(charged_fee))?,
+            fees: charged_fee,
+            ..Balances::default()
+        })?;
+
+        // entries charged back with a zero amount were never counted as chargebacks
+        if entry.charged_back > dec!(0) {
+            self.chargebacks -= 1;
+        }
+        entry.charged_back = dec!(0);
+        entry.state = LedgerEntryState::Represented;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        if self.unlock_on_representment && self.chargebacks == 0 && !self.is_locked_permanently {
+            self.is_locked = false;
+        }
+        self.finish_closing()?;
+
+        Ok((TransitionOutcome::Applied, charged_fee))
+    }
+
+    /// Reopens a dormant account, as if the client had some activity.
+    #[instrument(level = "trace", skip_all, fields(client = self.id.as_field()))]
+    pub fn reopen(&mut self) -> TxResult<()> {
+        match self.status() {
+            AccountStatus::Dormant => {
+                self.record_activity();
+                Ok(())
+            }
+            status => Err(TxError::InvalidOperation(format!(
+                "Attempt to open account [{}] which is {}.",
+                self.id, status
+            ))),
+        }
+    }
+
+    /// Starts closing the account. Once all disputes are resolved or charged back and all
+    /// authorizations are captured or released, the remaining available funds are paid out
+    /// with the id of this transaction and the account is closed.
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(client = self.id.as_field(), tx = tx_id.as_field())
+    )]
+    pub fn close(&mut self, tx_id: TxId) -> TxResult<()> {
+        self.require_unique_transaction(tx_id)?;
+        self.require_unlocked()?;
+        self.require_status(tx_id, &[AccountStatus::Open, AccountStatus::Dormant])?;
+
+        if self.available < dec!(0) {
+            return Err(TxError::InvalidOperation(format!(
+                "Attempt to close account [{}] with a negative balance [{}] in transaction [{}].",
+                self.id, self.available, tx_id
+            )));
+        }
+
+        let mut entry = LedgerEntry::new(dec!(0), self.position);
+        entry.kind = LedgerEntryKind::Payout;
+        self.put_entry(tx_id, entry)?;
+        self.status = AccountStatus::Closing;
+        self.closing_tx_id = Some(tx_id);
+
+        self.finish_closing()
+    }
+
+    /// Pays out the remaining funds and closes the account if it is closing and nothing is
+    /// left to settle. Funds going negative through a chargeback keep the account closing, as
+    /// does a lock until a representment lifts it.
+    fn finish_closing(&mut self) -> TxResult<()> {
+        let Some(tx_id) = self
+            .closing_tx_id
+            .filter(|_| self.status == AccountStatus::Closing)
+        else {
+            return Ok(());
+        };
+        if self.is_locked
+            || self.open_disputes > 0
+            || self.authorized != dec!(0)
+            || self.available < dec!(0)
+        {
+            return Ok(());
+        }
+
+        let balances = self.balances_after(Balances {
+            available: -self.available,
+            ..Balances::default()
+        })?;
+        let mut entry = LedgerEntry::new(-self.available, self.position);
+        entry.kind = LedgerEntryKind::Payout;
+        self.put_entry(tx_id, entry)?;
+        self.set_balances(balances);
+        self.status = AccountStatus::Closed;
 
         Ok(())
     }
 
-    fn get_tx_record(&mut self, tx_id: u32) -> TxResult<&mut LedgerEntry> {
-        self.ledger.get_mut(&tx_id)
-            .ok_or(TxError::InvalidArgument(format!(
-                "Transaction [{}] is not known, was not a deposit/withdrawal or does not belong to account [{}].",
-                tx_id, self.id
+    fn require_valid_partial_amount(
+        &self,
+        operation: &str,
+        tx_id: TxId,
+        amount: Decimal,
+        maximum: Decimal,
+    ) -> TxResult<()> {
+        if amount < dec!(0) {
+            Err(TxError::InvalidArgument(format!(
+                "Attempt to {} a negative amount [{}] of transaction [{}] for account [{}].",
+                operation, amount, tx_id, self.id
             )))
+        } else if amount > maximum {
+            Err(TxError::InvalidArgument(format!(
+                "Attempt to {} an amount [{}] greater than the remaining amount [{}] of transaction [{}] for account [{}].",
+                operation, amount, maximum, tx_id, self.id
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes ledger entries whose dispute window has passed, as they can no longer be disputed.
+    /// Entries that are currently disputed are kept until they are resolved or charged back,
+    /// charged back entries until they are represented and open authorizations until they are
+    /// captured or released.
+    /// Returns the number of removed entries.
+    ///
+    /// The ledger keeps the ids of removed entries, so they can't be reused and late disputes are
+    /// still rejected as expired. Nothing is removed while a checkpoint is taken, as a rollback
+    /// could not restore removed entries.
+    #[instrument(level = "trace", skip_all, fields(client = self.id.as_field()))]
+    pub fn compact(&mut self) -> TxResult<usize> {
+        let Some(window) = self.dispute_window.filter(|_| self.checkpoints.is_empty()) else {
+            return Ok(0);
+        };
+        let position = self.position;
+
+        self.ledger.retain(&mut |_, entry| {
+            matches!(
+                entry.state,
+                LedgerEntryState::Disputed
+                    | LedgerEntryState::ChargedBack
+                    | LedgerEntryState::PartiallyChargedBack
+            ) || entry.kind == LedgerEntryKind::Authorization
+                || !window.is_expired(entry.recorded_at, position)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+    use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
 
-    use crate::tx::engine::account::Account;
+    use crate::tx::engine::account::{Account, AccountStatus};
+    use crate::tx::engine::dispute_window::{DisputeWindow, LedgerPosition};
+    use crate::tx::engine::fee_schedule::Fee;
+    use crate::tx::engine::ledger_entry::LedgerEntryState;
+    use crate::tx::engine::result::{TxError, TxResult};
+    use crate::tx::engine::transition::{DisputeEvent, TransitionOutcome};
 
     #[test]
     fn test_disputes_dont_fail_if_tx_does_not_exist() {
         let mut account = Account::new(1);
 
-        account.chargeback(82).unwrap();
-        account.resolve(82).unwrap();
-        account.dispute(82).unwrap();
+        assert_eq!(
+            account.chargeback(82.into()).unwrap(),
+            TransitionOutcome::IgnoredUnknownTx
+        );
+        assert_eq!(
+            account.resolve(82.into()).unwrap(),
+            TransitionOutcome::IgnoredUnknownTx
+        );
+        assert_eq!(
+            account.dispute(82.into()).unwrap(),
+            TransitionOutcome::IgnoredUnknownTx
+        );
+    }
+
+    #[test]
+    fn test_disputes_report_invalid_transitions() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+
+        assert_eq!(
+            account.resolve(1.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Normal,
+                event: DisputeEvent::Resolve,
+            }
+        );
+        assert_eq!(
+            account.dispute(1.into()).unwrap(),
+            TransitionOutcome::Applied
+        );
+        assert_eq!(
+            account.dispute(1.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Disputed,
+                event: DisputeEvent::Dispute,
+            }
+        );
+        assert_eq!(account.held(), dec!(10));
     }
 
     #[test]
     fn test_can_dispute_deposit() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(123.23)).unwrap();
+        account.deposit(23.into(), dec!(123.23)).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(123.23));
         assert_eq!(account.total(), dec!(123.23));
         assert!(!account.is_locked());
 
-        account.dispute(23).unwrap();
+        account.dispute(23.into()).unwrap();
 
         assert_eq!(account.held(), dec!(123.23));
         assert_eq!(account.available(), dec!(0));
@@ -250,10 +1317,13 @@ mod tests {
     fn test_accepts_each_transaction_only_once() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(123.23)).unwrap();
+        account.deposit(23.into(), dec!(123.23)).unwrap();
 
         assert_eq!(
-            format!("{:?}", account.deposit(23, dec!(123.23)).unwrap_err()),
+            format!(
+                "{:?}",
+                account.deposit(23.into(), dec!(123.23)).unwrap_err()
+            ),
             "InvalidOperation(\"Attempt to execute a transaction [23] twice for account [1].\")"
         );
     }
@@ -262,12 +1332,12 @@ mod tests {
     fn test_can_not_withdraw_more_than_balance() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(10)).unwrap();
+        account.deposit(23.into(), dec!(10)).unwrap();
 
         assert_eq!(
             format!(
                 "{:?}",
-                account.withdraw(24, dec!(10.0001)).unwrap_err()
+                account.withdraw(24.into(), dec!(10.0001)).unwrap_err()
             ),
             "InvalidArgument(\"Attempt to withdraw an amount [10.0001] greater than balance [10] in transaction [24] for account [1].\")"
         );
@@ -277,13 +1347,13 @@ mod tests {
     fn test_can_not_withdraw_held_funds() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(10)).unwrap();
-        account.dispute(23).unwrap();
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.dispute(23.into()).unwrap();
 
         assert_eq!(
             format!(
                 "{:?}",
-                account.withdraw(24, dec!(1)).unwrap_err()
+                account.withdraw(24.into(), dec!(1)).unwrap_err()
             ),
             "InvalidArgument(\"Attempt to withdraw an amount [1] greater than balance [0] in transaction [24] for account [1].\")"
         );
@@ -293,15 +1363,15 @@ mod tests {
     fn test_can_dispute_deposit_after_withdrawal() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(100)).unwrap();
-        account.withdraw(24, dec!(64)).unwrap();
+        account.deposit(23.into(), dec!(100)).unwrap();
+        account.withdraw(24.into(), dec!(64)).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(36));
         assert_eq!(account.total(), dec!(36));
         assert!(!account.is_locked());
 
-        account.dispute(23).unwrap();
+        account.dispute(23.into()).unwrap();
 
         assert_eq!(account.held(), dec!(100));
         assert_eq!(account.available(), dec!(-64));
@@ -313,17 +1383,17 @@ mod tests {
     fn test_can_dispute_deposits_after_withdrawal() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(100)).unwrap();
-        account.deposit(24, dec!(200)).unwrap();
-        account.withdraw(25, dec!(164)).unwrap();
+        account.deposit(23.into(), dec!(100)).unwrap();
+        account.deposit(24.into(), dec!(200)).unwrap();
+        account.withdraw(25.into(), dec!(164)).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(136));
         assert_eq!(account.total(), dec!(136));
         assert!(!account.is_locked());
 
-        account.dispute(23).unwrap();
-        account.dispute(24).unwrap();
+        account.dispute(23.into()).unwrap();
+        account.dispute(24.into()).unwrap();
 
         assert_eq!(account.held(), dec!(300));
         assert_eq!(account.available(), dec!(-164));
@@ -335,15 +1405,15 @@ mod tests {
     fn test_can_dispute_withdrawal_after_deposit() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(100)).unwrap();
-        account.withdraw(24, dec!(64)).unwrap();
+        account.deposit(23.into(), dec!(100)).unwrap();
+        account.withdraw(24.into(), dec!(64)).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(36));
         assert_eq!(account.total(), dec!(36));
         assert!(!account.is_locked());
 
-        account.dispute(24).unwrap();
+        account.dispute(24.into()).unwrap();
 
         assert_eq!(account.held(), dec!(-64));
         assert_eq!(account.available(), dec!(100));
@@ -355,9 +1425,9 @@ mod tests {
     fn test_can_resolve_disputed_deposit() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(123.23)).unwrap();
-        account.dispute(23).unwrap();
-        account.resolve(23).unwrap();
+        account.deposit(23.into(), dec!(123.23)).unwrap();
+        account.dispute(23.into()).unwrap();
+        account.resolve(23.into()).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(123.23));
@@ -369,10 +1439,10 @@ mod tests {
     fn test_can_resolve_disputed_withdrawal() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(132)).unwrap();
-        account.withdraw(24, dec!(32)).unwrap();
-        account.dispute(24).unwrap();
-        account.resolve(24).unwrap();
+        account.deposit(23.into(), dec!(132)).unwrap();
+        account.withdraw(24.into(), dec!(32)).unwrap();
+        account.dispute(24.into()).unwrap();
+        account.resolve(24.into()).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(100));
@@ -384,9 +1454,9 @@ mod tests {
     fn test_can_chargeback_disputed_deposit() {
         let mut account = Account::new(1);
 
-        account.deposit(23, dec!(123.23)).unwrap();
-        account.dispute(23).unwrap();
-        account.chargeback(23).unwrap();
+        account.deposit(23.into(), dec!(123.23)).unwrap();
+        account.dispute(23.into()).unwrap();
+        account.chargeback(23.into()).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(0));
@@ -395,27 +1465,27 @@ mod tests {
 
         // ensure no further transactions can be executed
         assert_eq!(
-            format!("{:?}", account.dispute(0).unwrap_err()),
+            format!("{:?}", account.dispute(0.into()).unwrap_err()),
             "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
         );
 
         assert_eq!(
-            format!("{:?}", account.chargeback(0).unwrap_err()),
+            format!("{:?}", account.chargeback(0.into()).unwrap_err()),
             "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
         );
 
         assert_eq!(
-            format!("{:?}", account.resolve(0).unwrap_err()),
+            format!("{:?}", account.resolve(0.into()).unwrap_err()),
             "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
         );
 
         assert_eq!(
-            format!("{:?}", account.deposit(0, dec!(0)).unwrap_err()),
+            format!("{:?}", account.deposit(0.into(), dec!(0)).unwrap_err()),
             "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
         );
 
         assert_eq!(
-            format!("{:?}", account.withdraw(0, dec!(0)).unwrap_err()),
+            format!("{:?}", account.withdraw(0.into(), dec!(0)).unwrap_err()),
             "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
         );
     }
@@ -424,14 +1494,752 @@ mod tests {
     fn test_can_chargeback_disputed_withdrawal() {
         let mut account = Account::new(1);
 
-        account.deposit(22, dec!(123.23)).unwrap();
-        account.withdraw(23, dec!(100)).unwrap();
-        account.dispute(23).unwrap();
-        account.chargeback(23).unwrap();
+        account.deposit(22.into(), dec!(123.23)).unwrap();
+        account.withdraw(23.into(), dec!(100)).unwrap();
+        account.dispute(23.into()).unwrap();
+        account.chargeback(23.into()).unwrap();
 
         assert_eq!(account.held(), dec!(0));
         assert_eq!(account.available(), dec!(123.23));
         assert_eq!(account.total(), dec!(123.23));
         assert!(account.is_locked());
     }
+
+    #[test]
+    fn test_can_not_dispute_after_dispute_window() {
+        let mut account = Account::new(1);
+        account.set_dispute_window(Some(DisputeWindow::Transactions(2)));
+
+        account.advance(position(0));
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.advance(position(1));
+        account.deposit(24.into(), dec!(20)).unwrap();
+        account.advance(position(3));
+
+        assert_eq!(
+            format!("{:?}", account.dispute(23.into()).unwrap_err()),
+            "DisputeWindowExpired(\"Attempt to dispute transaction [23] for account [1] after its dispute window has passed.\")"
+        );
+
+        account.dispute(24.into()).unwrap();
+
+        assert_eq!(account.held(), dec!(20));
+        assert_eq!(account.available(), dec!(10));
+    }
+
+    #[test]
+    fn test_compact_keeps_disputable_and_disputed_entries() {
+        let mut account = Account::new(1);
+        account.set_dispute_window(Some(DisputeWindow::Transactions(2)));
+
+        account.advance(position(0));
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.deposit(24.into(), dec!(20)).unwrap();
+        account.dispute(24.into()).unwrap();
+        account.advance(position(2));
+        account.deposit(25.into(), dec!(30)).unwrap();
+        account.advance(position(3));
+
+        assert_eq!(account.compact().unwrap(), 1);
+
+        account.dispute(25.into()).unwrap();
+        account.resolve(24.into()).unwrap();
+
+        assert_eq!(account.held(), dec!(30));
+        assert_eq!(account.available(), dec!(30));
+    }
+
+    #[test]
+    fn test_compacted_ids_stay_used_and_expired() {
+        let mut account = Account::new(1);
+        account.set_dispute_window(Some(DisputeWindow::Transactions(2)));
+
+        account.advance(position(0));
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.advance(position(3));
+
+        // removed entries could not be restored by a rollback
+        account.checkpoint();
+        assert_eq!(account.compact().unwrap(), 0);
+        account.commit();
+
+        assert_eq!(account.compact().unwrap(), 1);
+        assert_eq!(
+            format!("{:?}", account.deposit(23.into(), dec!(10)).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction [23] twice for account [1].\")"
+        );
+        assert_eq!(
+            format!("{:?}", account.dispute(23.into()).unwrap_err()),
+            "DisputeWindowExpired(\"Attempt to dispute transaction [23] for account [1] after its dispute window has passed.\")"
+        );
+        assert_eq!(account.available(), dec!(10));
+        assert_eq!(account.held(), dec!(0));
+    }
+
+    fn position(sequence: u64) -> LedgerPosition {
+        LedgerPosition {
+            sequence,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_can_partially_dispute_and_chargeback_deposit() {
+        let mut account = Account::new(1);
+
+        account.deposit(23.into(), dec!(100)).unwrap();
+        account.dispute_partially(23.into(), dec!(40)).unwrap();
+
+        assert_eq!(account.held(), dec!(40));
+        assert_eq!(account.available(), dec!(60));
+
+        account.chargeback_partially(23.into(), dec!(15)).unwrap();
+
+        assert_eq!(account.held(), dec!(0));
+        assert_eq!(account.available(), dec!(85));
+        assert_eq!(account.total(), dec!(85));
+        assert!(account.is_locked());
+    }
+
+    #[test]
+    fn test_can_partially_dispute_and_chargeback_withdrawal() {
+        let mut account = Account::new(1);
+
+        account.deposit(22.into(), dec!(50)).unwrap();
+        account.withdraw(23.into(), dec!(30)).unwrap();
+        account.dispute_partially(23.into(), dec!(10)).unwrap();
+
+        assert_eq!(account.held(), dec!(-10));
+        assert_eq!(account.available(), dec!(30));
+
+        account.chargeback(23.into()).unwrap();
+
+        assert_eq!(account.held(), dec!(0));
+        assert_eq!(account.available(), dec!(30));
+        assert_eq!(
+            format!("{:?}", account.dispute(23.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction on locked account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_can_not_dispute_more_than_remaining_amount() {
+        let mut account = Account::new(1);
+
+        account.deposit(23.into(), dec!(100)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                account.dispute_partially(23.into(), dec!(100.01)).unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to dispute an amount [100.01] greater than the remaining amount [100] of transaction [23] for account [1].\")"
+        );
+
+        account.dispute_partially(23.into(), dec!(30)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                account.chargeback_partially(23.into(), dec!(31)).unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to charge back an amount [31] greater than the remaining amount [30] of transaction [23] for account [1].\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                account.chargeback_partially(23.into(), dec!(-1)).unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to charge back a negative amount [-1] of transaction [23] for account [1].\")"
+        );
+
+        account.resolve(23.into()).unwrap();
+
+        assert_eq!(account.held(), dec!(0));
+        assert_eq!(account.available(), dec!(100));
+    }
+
+    #[test]
+    fn test_dispute_cycles_are_limited() {
+        let mut account = Account::new(1);
+        account.set_max_dispute_cycles(Some(2));
+
+        account.deposit(1.into(), dec!(10)).unwrap();
+        for _ in 0..2 {
+            account.dispute(1.into()).unwrap();
+            account.resolve(1.into()).unwrap();
+        }
+
+        assert_eq!(
+            format!("{:?}", account.dispute(1.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to dispute transaction [1] for account [1] more than [2] times.\")"
+        );
+        assert_eq!(account.available(), dec!(10));
+        assert_eq!(account.held(), dec!(0));
+    }
+
+    #[test]
+    fn test_representment_restores_charged_back_funds() {
+        let mut account = Account::new(1);
+
+        account.deposit(1.into(), dec!(100)).unwrap();
+        account.withdraw(2.into(), dec!(30)).unwrap();
+        account.dispute(2.into()).unwrap();
+        account.chargeback(2.into()).unwrap();
+
+        assert_eq!(account.available(), dec!(100));
+        assert_eq!(account.chargebacks(), 1);
+
+        assert_eq!(
+            account.represent(2.into()).unwrap(),
+            TransitionOutcome::Applied
+        );
+        assert_eq!(account.available(), dec!(70));
+        assert_eq!(account.held(), dec!(0));
+        assert_eq!(account.chargebacks(), 0);
+        // unlocking is not enabled
+        assert!(account.is_locked());
+
+        assert_eq!(
+            account.represent(2.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Represented,
+                event: DisputeEvent::Representment,
+            }
+        );
+        assert_eq!(
+            account.represent(1.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Normal,
+                event: DisputeEvent::Representment,
+            }
+        );
+    }
+
+    #[test]
+    fn test_representment_of_partial_chargeback() {
+        let mut account = Account::new(1);
+        account.set_unlock_on_representment(true);
+
+        account.deposit(1.into(), dec!(100)).unwrap();
+        account.dispute(1.into()).unwrap();
+        account.chargeback_partially(1.into(), dec!(40)).unwrap();
+        assert_eq!(account.available(), dec!(60));
+        assert_eq!(account.chargebacks(), 1);
+        assert!(account.is_locked());
+
+        account.represent(1.into()).unwrap();
+        assert_eq!(account.available(), dec!(100));
+        assert_eq!(account.chargebacks(), 0);
+        assert!(!account.is_locked());
+        assert_eq!(
+            account.represent(1.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Represented,
+                event: DisputeEvent::Representment,
+            }
+        );
+    }
+
+    #[test]
+    fn test_zero_amount_partial_chargeback_does_not_lock_account() {
+        let mut account = Account::new(1);
+
+        account.deposit(1.into(), dec!(100)).unwrap();
+        account.dispute(1.into()).unwrap();
+        account.chargeback_partially(1.into(), dec!(0)).unwrap();
+        assert_eq!(account.available(), dec!(100));
+        assert_eq!(account.chargebacks(), 0);
+        assert!(!account.is_locked());
+
+        account.deposit(2.into(), dec!(10)).unwrap();
+        assert_eq!(account.available(), dec!(110));
+    }
+
+    #[test]
+    fn test_compact_keeps_charged_back_entries() {
+        for charged_back in [dec!(100), dec!(40)] {
+            let mut account = Account::new(1);
+            account.set_dispute_window(Some(DisputeWindow::Transactions(2)));
+            account.set_unlock_on_representment(true);
+
+            account.advance(position(0));
+            account.deposit(1.into(), dec!(100)).unwrap();
+            account.dispute(1.into()).unwrap();
+            account
+                .chargeback_partially(1.into(), charged_back)
+                .unwrap();
+            account.advance(position(3));
+
+            assert_eq!(account.compact().unwrap(), 0);
+
+            account.represent(1.into()).unwrap();
+            assert_eq!(account.available(), dec!(100));
+            assert_eq!(account.chargebacks(), 0);
+            assert!(!account.is_locked());
+        }
+    }
+
+    #[test]
+    fn test_representment_unlocks_account() {
+        let mut account = Account::new(1);
+        account.set_unlock_on_representment(true);
+
+        account.deposit(1.into(), dec!(100)).unwrap();
+        account.dispute(1.into()).unwrap();
+        account.chargeback(1.into()).unwrap();
+        assert_eq!(account.available(), dec!(0));
+        assert!(account.is_locked());
+
+        account.represent(1.into()).unwrap();
+        assert_eq!(account.available(), dec!(100));
+        assert!(!account.is_locked());
+
+        assert_eq!(
+            account.dispute(1.into()).unwrap(),
+            TransitionOutcome::IgnoredInvalidState {
+                from: LedgerEntryState::Represented,
+                event: DisputeEvent::Dispute,
+            }
+        );
+
+        // locks that were not caused by a chargeback are kept
+        account.deposit(2.into(), dec!(10)).unwrap();
+        account.dispute(2.into()).unwrap();
+        account.chargeback(2.into()).unwrap();
+        account.lock();
+        account.represent(2.into()).unwrap();
+        assert_eq!(account.available(), dec!(110));
+        assert!(account.is_locked());
+    }
+
+    #[test]
+    fn test_transfer_legs() {
+        let mut account_a = Account::new(1);
+        let mut account_b = Account::new(2);
+
+        account_a.deposit(1.into(), dec!(10)).unwrap();
+        account_a.transfer_out(2.into(), 2.into(), dec!(4)).unwrap();
+        account_b.transfer_in(2.into(), 1.into(), dec!(4)).unwrap();
+
+        assert_eq!(account_a.available(), dec!(6));
+        assert_eq!(account_b.available(), dec!(4));
+        assert_eq!(account_a.counterparty(2.into()).unwrap(), Some(2.into()));
+        assert_eq!(account_b.counterparty(2.into()).unwrap(), Some(1.into()));
+        assert_eq!(account_a.counterparty(1.into()).unwrap(), None);
+
+        assert_eq!(
+            format!("{:?}", account_a.transfer_out(3.into(), 2.into(), dec!(6.1)).unwrap_err()),
+            "InvalidArgument(\"Attempt to withdraw an amount [6.1] greater than balance [6] in transaction [3] for account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_can_withdraw_up_to_credit_limit() {
+        let mut account = Account::new(1);
+
+        account.set_credit_limit(dec!(50)).unwrap();
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.withdraw(24.into(), dec!(40)).unwrap();
+
+        assert_eq!(account.available(), dec!(-30));
+        assert_eq!(
+            format!("{:?}", account.withdraw(25.into(), dec!(20.01)).unwrap_err()),
+            "InvalidArgument(\"Attempt to withdraw an amount [20.01] greater than balance [-30] plus credit limit [50] in transaction [25] for account [1].\")"
+        );
+
+        account.withdraw(25.into(), dec!(20)).unwrap();
+
+        assert_eq!(account.available(), dec!(-50));
+        assert_eq!(account.summary().credit_limit, dec!(50));
+    }
+
+    #[test]
+    fn test_disputes_are_not_bound_by_credit_limit() {
+        let mut account = Account::new(1);
+
+        account.set_credit_limit(dec!(5)).unwrap();
+        account.deposit(23.into(), dec!(10)).unwrap();
+        account.withdraw(24.into(), dec!(15)).unwrap();
+        account.dispute(23.into()).unwrap();
+
+        assert_eq!(account.available(), dec!(-15));
+        assert_eq!(account.held(), dec!(10));
+        assert_eq!(
+            format!("{:?}", account.withdraw(25.into(), dec!(0.01)).unwrap_err()),
+            "InvalidArgument(\"Attempt to withdraw an amount [0.01] greater than balance [-15] plus credit limit [5] in transaction [25] for account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_can_not_set_negative_credit_limit() {
+        let mut account = Account::new(1);
+
+        assert_eq!(
+            format!("{:?}", account.set_credit_limit(dec!(-1)).unwrap_err()),
+            "InvalidArgument(\"Attempt to set a negative credit limit [-1] for account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_fees_are_refunded_on_chargeback() {
+        let mut account = Account::new(1);
+
+        account.deposit(22.into(), dec!(100)).unwrap();
+        account
+            .require_withdrawable(23.into(), dec!(50), dec!(1.5))
+            .unwrap();
+        account.withdraw(23.into(), dec!(50)).unwrap();
+        account.charge_fee(23.into(), dec!(1.5)).unwrap();
+
+        assert_eq!(account.available(), dec!(48.5));
+        assert_eq!(account.fees(), dec!(1.5));
+        assert_eq!(
+            format!(
+                "{:?}",
+                account
+                    .require_withdrawable(24.into(), dec!(48), dec!(1))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to withdraw an amount [48] plus fee [1] greater than balance [48.5] in transaction [24] for account [1].\")"
+        );
+
+        account.dispute(23.into()).unwrap();
+
+        let fee = Fee {
+            flat: dec!(5),
+            percentage: dec!(10),
+        };
+        assert_eq!(
+            account.chargeback_with_fee(23.into(), None, fee).unwrap(),
+            (TransitionOutcome::Applied, dec!(8.5))
+        );
+        assert_eq!(account.available(), dec!(90));
+        assert_eq!(account.fees(), dec!(10));
+    }
+
+    #[test]
+    fn test_partial_chargeback_keeps_transaction_fee() {
+        let mut account = Account::new(1);
+
+        account.deposit(22.into(), dec!(100)).unwrap();
+        account.withdraw(23.into(), dec!(50)).unwrap();
+        account.charge_fee(23.into(), dec!(1.5)).unwrap();
+        account.dispute(23.into()).unwrap();
+
+        let fee = Fee {
+            flat: dec!(0.5),
+            percentage: dec!(0),
+        };
+        assert_eq!(
+            account
+                .chargeback_with_fee(23.into(), Some(dec!(0.01)), fee)
+                .unwrap(),
+            (TransitionOutcome::Applied, dec!(0.5))
+        );
+        assert_eq!(account.available(), dec!(48.01));
+        assert_eq!(account.fees(), dec!(2));
+
+        let entry = account.ledger.get(23.into()).unwrap().unwrap();
+        assert_eq!(entry.fee, dec!(1.5));
+        assert_eq!(entry.chargeback_fee, dec!(0.5));
+    }
+
+    #[test]
+    fn test_rollback_restores_checkpoint() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        let summary = account.summary();
+
+        account.checkpoint();
+        account.dispute(1.into()).unwrap();
+        account.deposit(2.into(), dec!(5)).unwrap();
+        account.chargeback(1.into()).unwrap();
+        account.rollback().unwrap();
+
+        assert_eq!(account.summary(), summary);
+        assert_eq!(account.open_disputes(), 0);
+        account.deposit(2.into(), dec!(5)).unwrap();
+        account.dispute(1.into()).unwrap();
+        assert_eq!(account.held(), dec!(10));
+
+        // changes are kept once committed
+        account.checkpoint();
+        account.resolve(1.into()).unwrap();
+        account.commit();
+        account.rollback().unwrap();
+        assert_eq!(account.available(), dec!(15));
+    }
+
+    #[test]
+    fn test_authorization_is_held_until_captured() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        account.authorize(2.into(), dec!(4)).unwrap();
+
+        assert_eq!(account.available(), dec!(6));
+        assert_eq!(account.held(), dec!(0));
+        assert_eq!(account.authorized(), dec!(4));
+        assert_eq!(account.total(), dec!(10));
+        assert_eq!(
+            format!("{:?}", account.withdraw(3.into(), dec!(7)).unwrap_err()),
+            "InvalidArgument(\"Attempt to withdraw an amount [7] greater than balance [6] in transaction [3] for account [1].\")"
+        );
+        assert_eq!(
+            format!("{:?}", account.dispute(2.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to dispute authorization [2] of account [1] which was not captured.\")"
+        );
+
+        account.capture_partially(2.into(), dec!(3)).unwrap();
+
+        assert_eq!(account.available(), dec!(7));
+        assert_eq!(account.authorized(), dec!(0));
+        assert_eq!(account.total(), dec!(7));
+
+        // the captured amount is a regular withdrawal
+        account.dispute(2.into()).unwrap();
+        assert_eq!(account.held(), dec!(-3));
+        assert_eq!(account.available(), dec!(10));
+    }
+
+    #[test]
+    fn test_released_authorization_can_not_be_captured() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        account.authorize(2.into(), dec!(4)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                account.capture_partially(2.into(), dec!(5)).unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to capture an amount [5] greater than the remaining amount [4] of transaction [2] for account [1].\")"
+        );
+
+        account.release(2.into()).unwrap();
+
+        assert_eq!(account.available(), dec!(10));
+        assert_eq!(account.authorized(), dec!(0));
+        assert_eq!(
+            format!("{:?}", account.capture(2.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to capture transaction [2] for account [1] which is not an open authorization.\")"
+        );
+        assert_eq!(
+            format!("{:?}", account.release(1.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to release transaction [1] for account [1] which is not an open authorization.\")"
+        );
+    }
+
+    #[test]
+    fn test_capture_charges_fee() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        account.authorize(2.into(), dec!(10)).unwrap();
+
+        let fee = Fee {
+            flat: dec!(1),
+            percentage: dec!(10),
+        };
+        assert_eq!(
+            format!(
+                "{:?}",
+                account.capture_with_fee(2.into(), None, fee).unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to capture an amount [10] plus fee [2] greater than balance [0] in transaction [2] for account [1].\")"
+        );
+        assert_eq!(
+            account
+                .capture_with_fee(2.into(), Some(dec!(5)), fee)
+                .unwrap(),
+            dec!(1.5)
+        );
+        assert_eq!(account.available(), dec!(3.5));
+        assert_eq!(account.fees(), dec!(1.5));
+    }
+
+    #[test]
+    fn test_closing_waits_for_disputes_and_authorizations() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        account.deposit(2.into(), dec!(5)).unwrap();
+        account.dispute(2.into()).unwrap();
+        account.authorize(3.into(), dec!(4)).unwrap();
+
+        account.close(4.into()).unwrap();
+
+        assert_eq!(account.status(), AccountStatus::Closing);
+        assert_eq!(
+            format!("{:?}", account.deposit(5.into(), dec!(1)).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction [5] on closing account [1].\")"
+        );
+
+        account.capture_partially(3.into(), dec!(1)).unwrap();
+        assert_eq!(account.status(), AccountStatus::Closing);
+
+        account.resolve(2.into()).unwrap();
+        assert_eq!(account.status(), AccountStatus::Closed);
+        assert_eq!(account.available(), dec!(0));
+        assert_eq!(account.total(), dec!(0));
+        assert_eq!(
+            format!("{:?}", account.dispute(1.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction [1] on closed account [1].\")"
+        );
+        assert_eq!(
+            format!("{:?}", account.close(6.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction [6] on closed account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_locked_account_is_not_paid_out() {
+        let mut account = Account::new(1);
+        account.set_unlock_on_representment(true);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        account.deposit(2.into(), dec!(5)).unwrap();
+        account.dispute(2.into()).unwrap();
+        account.close(3.into()).unwrap();
+
+        account.chargeback(2.into()).unwrap();
+        assert_eq!(account.status(), AccountStatus::Closing);
+        assert_eq!(account.available(), dec!(10));
+
+        account.represent(2.into()).unwrap();
+        assert_eq!(account.status(), AccountStatus::Closed);
+        assert_eq!(account.total(), dec!(0));
+    }
+
+    #[test]
+    fn test_can_not_close_account_with_negative_balance() {
+        let mut account = Account::new(1);
+        account.set_credit_limit(dec!(10)).unwrap();
+        account.withdraw(1.into(), dec!(5)).unwrap();
+
+        assert_eq!(
+            format!("{:?}", account.close(2.into()).unwrap_err()),
+            "InvalidOperation(\"Attempt to close account [1] with a negative balance [-5] in transaction [2].\")"
+        );
+        assert_eq!(account.status(), AccountStatus::Open);
+    }
+
+    #[test]
+    fn test_account_becomes_dormant_without_activity() {
+        let mut account = Account::new(1);
+        account.set_dormancy(Some(DisputeWindow::Transactions(2)));
+        account.advance(position(1));
+        account.deposit(1.into(), dec!(10)).unwrap();
+
+        account.advance(position(3));
+        assert_eq!(account.status(), AccountStatus::Open);
+        account.advance(position(4));
+        assert_eq!(account.status(), AccountStatus::Dormant);
+        assert_eq!(
+            format!("{:?}", account.withdraw(2.into(), dec!(1)).unwrap_err()),
+            "InvalidOperation(\"Attempt to execute a transaction [2] on dormant account [1].\")"
+        );
+
+        account.deposit(3.into(), dec!(1)).unwrap();
+        assert_eq!(account.status(), AccountStatus::Open);
+
+        account.advance(position(7));
+        assert_eq!(account.status(), AccountStatus::Dormant);
+        account.reopen().unwrap();
+        account.withdraw(4.into(), dec!(1)).unwrap();
+        assert_eq!(
+            format!("{:?}", account.reopen().unwrap_err()),
+            "InvalidOperation(\"Attempt to open account [1] which is open.\")"
+        );
+    }
+
+    #[test]
+    fn test_overflow_leaves_account_unchanged() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), Decimal::MAX).unwrap();
+        let summary = account.summary();
+
+        assert_eq!(
+            format!("{:?}", account.deposit(2.into(), dec!(1)).unwrap_err()),
+            "Overflow(\"Attempt to change the balance of account [1] beyond the supported range.\")"
+        );
+        assert_eq!(account.summary(), summary);
+
+        // the deposit was never recorded, so its id can be used again
+        account.withdraw(2.into(), dec!(1)).unwrap();
+        account.withdraw(3.into(), dec!(2)).unwrap();
+        account.dispute(3.into()).unwrap();
+        assert_eq!(account.available(), Decimal::MAX - dec!(1));
+        assert_eq!(account.held(), dec!(-2));
+
+        // the total stays in range as well
+        account.deposit(4.into(), dec!(1)).unwrap();
+        let summary = account.summary();
+        assert_eq!(
+            format!("{:?}", account.deposit(5.into(), dec!(1)).unwrap_err()),
+            "Overflow(\"Attempt to change the balance of account [1] beyond the supported range.\")"
+        );
+        assert_eq!(account.summary(), summary);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Operation {
+        Deposit(Decimal),
+        Withdraw(Decimal),
+        Dispute(u64),
+        Resolve(u64),
+        Chargeback(u64),
+    }
+
+    /// Amounts close to `Decimal::MAX`, mixed with small ones.
+    fn amount() -> impl Strategy<Value = Decimal> {
+        prop_oneof![
+            Just(Decimal::MAX),
+            any::<u64>().prop_map(|offset| Decimal::MAX - Decimal::from(offset)),
+            any::<u64>().prop_map(|divisor| Decimal::MAX / Decimal::from(divisor.max(1))),
+            (0u32..1000).prop_map(Decimal::from),
+        ]
+    }
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            amount().prop_map(Operation::Deposit),
+            amount().prop_map(Operation::Withdraw),
+            (0u64..16).prop_map(Operation::Dispute),
+            (0u64..16).prop_map(Operation::Resolve),
+            (0u64..16).prop_map(Operation::Chargeback),
+        ]
+    }
+
+    fn execute(account: &mut Account, tx_id: u64, operation: &Operation) -> TxResult<()> {
+        match *operation {
+            Operation::Deposit(amount) => account.deposit(tx_id.into(), amount),
+            Operation::Withdraw(amount) => account.withdraw(tx_id.into(), amount),
+            Operation::Dispute(tx_id) => account.dispute(tx_id.into()).map(|_| ()),
+            Operation::Resolve(tx_id) => account.resolve(tx_id.into()).map(|_| ()),
+            Operation::Chargeback(tx_id) => account.chargeback(tx_id.into()).map(|_| ()),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_near_max_amounts_never_overflow(
+            operations in prop::collection::vec(operation(), 1..32),
+            credit_limit in amount(),
+        ) {
+            let mut account = Account::new(1);
+            account.set_credit_limit(credit_limit).unwrap();
+
+            for (tx_id, operation) in operations.iter().enumerate() {
+                let before = account.summary();
+                let result = execute(&mut account, tx_id as u64, operation);
+
+                if let Err(TxError::Overflow(_)) = result {
+                    prop_assert_eq!(account.summary(), before);
+                }
+                let total = account
+                    .available()
+                    .checked_add(account.held())
+                    .and_then(|total| total.checked_add(account.authorized()));
+                prop_assert_eq!(total, Some(account.total()));
+            }
+        }
+    }
 }
@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::tx::engine::account::AccountSummary;
+
+/// A condition on the state of an account, see [`AccountQuery`]. Parsed from `locked`,
+/// `negative-available`, `held` and `total-above=<amount>`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AccountFilter {
+    Locked,
+    NegativeAvailable,
+    NonZeroHeld,
+    /// The total is strictly above the threshold.
+    TotalAbove(Decimal),
+}
+
+impl AccountFilter {
+    pub fn matches(&self, account: &AccountSummary) -> bool {
+        match self {
+            AccountFilter::Locked => account.is_locked,
+            AccountFilter::NegativeAvailable => account.available < Decimal::ZERO,
+            AccountFilter::NonZeroHeld => !account.held.is_zero(),
+            AccountFilter::TotalAbove(threshold) => account.total > *threshold,
+        }
+    }
+}
+
+impl FromStr for AccountFilter {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().split_once('=') {
+            None if value.trim() == "locked" => Ok(AccountFilter::Locked),
+            None if value.trim() == "negative-available" => Ok(AccountFilter::NegativeAvailable),
+            None if value.trim() == "held" => Ok(AccountFilter::NonZeroHeld),
+            Some(("total-above", threshold)) => threshold
+                .trim()
+                .parse::<Decimal>()
+                .map(AccountFilter::TotalAbove)
+                .map_err(|_| format!("Expected an amount as threshold, got [{}]", threshold)),
+            _ => Err(format!(
+                "Expected locked, negative-available, held or total-above=<amount>, got [{}]",
+                value
+            )),
+        }
+    }
+}
+
+impl Display for AccountFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountFilter::Locked => write!(f, "locked"),
+            AccountFilter::NegativeAvailable => write!(f, "negative-available"),
+            AccountFilter::NonZeroHeld => write!(f, "held"),
+            AccountFilter::TotalAbove(threshold) => write!(f, "total-above={}", threshold),
+        }
+    }
+}
+
+/// Selects the accounts matching all of its filters, ordered by client, and optionally a page of
+/// them. See [`TransactionEngine::query`].
+///
+/// [`TransactionEngine::query`]: crate::tx::engine::engine::TransactionEngine::query
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AccountQuery {
+    pub filters: Vec<AccountFilter>,
+    /// Number of matching accounts to skip.
+    pub offset: usize,
+    /// Maximum number of accounts to return, all if `None`.
+    pub limit: Option<usize>,
+}
+
+impl AccountQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_filter(mut self, filter: AccountFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn with_page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether the account matches all filters, any account matches a query without filters.
+    pub fn matches(&self, account: &AccountSummary) -> bool {
+        self.filters.iter().all(|filter| filter.matches(account))
+    }
+
+    /// Applies the offset and limit to ordered matches.
+    pub fn paginate<T>(&self, matches: impl Iterator<Item = T>) -> impl Iterator<Item = T> {
+        matches
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::account::{AccountStatus, AccountSummary};
+    use crate::tx::engine::account_query::{AccountFilter, AccountQuery};
+
+    fn account(available: Decimal, held: Decimal) -> AccountSummary {
+        AccountSummary {
+            id: 1.into(),
+            available,
+            held,
+            authorized: dec!(0),
+            total: available + held,
+            is_locked: false,
+            status: AccountStatus::Open,
+            credit_limit: dec!(0),
+            fees: dec!(0),
+        }
+    }
+
+    #[rstest]
+    #[case("locked", AccountFilter::Locked)]
+    #[case(" negative-available ", AccountFilter::NegativeAvailable)]
+    #[case("held", AccountFilter::NonZeroHeld)]
+    #[case("total-above=100.5", AccountFilter::TotalAbove(dec!(100.5)))]
+    #[case("total-above= -1", AccountFilter::TotalAbove(dec!(-1)))]
+    fn test_parse_filter(#[case] value: &str, #[case] expected: AccountFilter) {
+        assert_eq!(value.parse::<AccountFilter>().unwrap(), expected);
+        assert_eq!(
+            expected.to_string().parse::<AccountFilter>().unwrap(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "unlocked",
+        "Expected locked, negative-available, held or total-above=<amount>, got [unlocked]"
+    )]
+    #[case(
+        "locked=true",
+        "Expected locked, negative-available, held or total-above=<amount>, got [locked=true]"
+    )]
+    #[case("total-above=abc", "Expected an amount as threshold, got [abc]")]
+    fn test_parse_invalid_filter(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(value.parse::<AccountFilter>().unwrap_err(), expected);
+    }
+
+    #[test]
+    fn test_matches_all_filters() {
+        let query = AccountQuery::new()
+            .with_filter(AccountFilter::NegativeAvailable)
+            .with_filter(AccountFilter::NonZeroHeld);
+
+        assert!(query.matches(&account(dec!(-1), dec!(2))));
+        assert!(!query.matches(&account(dec!(-1), dec!(0))));
+        assert!(!query.matches(&account(dec!(1), dec!(2))));
+        assert!(AccountQuery::new().matches(&account(dec!(0), dec!(0))));
+        assert!(AccountFilter::TotalAbove(dec!(1)).matches(&account(dec!(1), dec!(0.1))));
+        assert!(!AccountFilter::TotalAbove(dec!(1)).matches(&account(dec!(1), dec!(0))));
+    }
+
+    #[test]
+    fn test_paginate() {
+        let query = AccountQuery::new().with_page(1, 2);
+
+        assert_eq!(query.paginate(1..5).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(
+            AccountQuery::new().paginate(1..5).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+    }
+}
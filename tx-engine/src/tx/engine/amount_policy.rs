@@ -0,0 +1,147 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+/// Defines how amounts with more decimal places than allowed are handled.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ExcessPrecision {
+    #[default]
+    Reject,
+    Round(RoundingStrategy),
+}
+
+/// Constraints amounts of the input have to satisfy, so balances never contain more precision
+/// than the reports show. By default all non-negative amounts are accepted as they are. The
+/// bounds only apply to the full amounts of transactions, partial amounts of disputes,
+/// chargebacks and captures are only checked for their precision.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct AmountPolicy {
+    /// The maximum number of decimal places, trailing zeros don't count. If unset, amounts can
+    /// have any precision.
+    pub max_decimal_places: Option<u32>,
+    pub excess_precision: ExcessPrecision,
+    /// Amounts below are rejected, checked after rounding.
+    pub min_amount: Option<Decimal>,
+    /// Amounts above are rejected, checked after rounding.
+    pub max_amount: Option<Decimal>,
+    /// Whether amounts that are zero, or are rounded to zero, are rejected.
+    pub reject_zero: bool,
+}
+
+impl AmountPolicy {
+    /// Returns the amount to execute for an amount of the input, or the reason it is rejected.
+    pub fn apply(&self, amount: Decimal) -> Result<Decimal, String> {
+        let amount = self.apply_precision(amount)?;
+
+        if self.reject_zero && amount == dec!(0) {
+            Err("Zero amounts are not allowed".to_string())
+        } else if let Some(min) = self.min_amount.filter(|min| amount < *min) {
+            Err(format!("Expected an amount of at least [{}]", min))
+        } else if let Some(max) = self.max_amount.filter(|max| amount > *max) {
+            Err(format!("Expected an amount of at most [{}]", max))
+        } else {
+            Ok(amount)
+        }
+    }
+
+    /// Like [`AmountPolicy::apply`], but without checking the bounds. Used for partial amounts
+    /// of a transaction.
+    pub fn apply_precision(&self, amount: Decimal) -> Result<Decimal, String> {
+        match self.max_decimal_places {
+            Some(places) if amount.normalize().scale() > places => match self.excess_precision {
+                ExcessPrecision::Reject => {
+                    Err(format!("Expected at most [{}] decimal places", places))
+                }
+                ExcessPrecision::Round(strategy) => {
+                    Ok(amount.round_dp_with_strategy(places, strategy))
+                }
+            },
+            _ => Ok(amount),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rust_decimal::{Decimal, RoundingStrategy};
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::amount_policy::{AmountPolicy, ExcessPrecision};
+
+    #[rstest]
+    #[case(dec!(1.2345), Ok(dec!(1.2345)))]
+    #[case(dec!(1.23450000), Ok(dec!(1.23450000)))]
+    #[case(dec!(1.23456), Err("Expected at most [4] decimal places".to_string()))]
+    fn test_excess_precision_is_rejected(
+        #[case] amount: Decimal,
+        #[case] expected: Result<Decimal, String>,
+    ) {
+        let policy = AmountPolicy {
+            max_decimal_places: Some(4),
+            ..AmountPolicy::default()
+        };
+
+        assert_eq!(policy.apply(amount), expected);
+    }
+
+    #[rstest]
+    #[case(RoundingStrategy::MidpointNearestEven, dec!(0.125), dec!(0.12))]
+    #[case(RoundingStrategy::MidpointAwayFromZero, dec!(0.125), dec!(0.13))]
+    #[case(RoundingStrategy::ToZero, dec!(0.129), dec!(0.12))]
+    #[case(RoundingStrategy::AwayFromZero, dec!(0.121), dec!(0.13))]
+    fn test_excess_precision_is_rounded(
+        #[case] strategy: RoundingStrategy,
+        #[case] amount: Decimal,
+        #[case] expected: Decimal,
+    ) {
+        let policy = AmountPolicy {
+            max_decimal_places: Some(2),
+            excess_precision: ExcessPrecision::Round(strategy),
+            ..AmountPolicy::default()
+        };
+
+        assert_eq!(policy.apply(amount), Ok(expected));
+    }
+
+    #[rstest]
+    #[case(dec!(0.004), Err("Zero amounts are not allowed".to_string()))]
+    #[case(dec!(0.5), Err("Expected an amount of at least [1]".to_string()))]
+    #[case(dec!(1), Ok(dec!(1)))]
+    #[case(dec!(1000), Ok(dec!(1000)))]
+    #[case(dec!(1000.001), Ok(dec!(1000.00)))]
+    #[case(dec!(1000.01), Err("Expected an amount of at most [1000]".to_string()))]
+    fn test_bounds_are_checked_after_rounding(
+        #[case] amount: Decimal,
+        #[case] expected: Result<Decimal, String>,
+    ) {
+        let policy = AmountPolicy {
+            max_decimal_places: Some(2),
+            excess_precision: ExcessPrecision::Round(RoundingStrategy::MidpointNearestEven),
+            min_amount: Some(dec!(1)),
+            max_amount: Some(dec!(1000)),
+            reject_zero: true,
+        };
+
+        assert_eq!(policy.apply(amount), expected);
+    }
+
+    #[rstest]
+    #[case(dec!(0), Ok(dec!(0)))]
+    #[case(dec!(0.5), Ok(dec!(0.5)))]
+    #[case(dec!(1000.01), Ok(dec!(1000.01)))]
+    #[case(dec!(0.125), Err("Expected at most [2] decimal places".to_string()))]
+    fn test_partial_amounts_are_only_checked_for_precision(
+        #[case] amount: Decimal,
+        #[case] expected: Result<Decimal, String>,
+    ) {
+        let policy = AmountPolicy {
+            max_decimal_places: Some(2),
+            min_amount: Some(dec!(1)),
+            max_amount: Some(dec!(1000)),
+            reject_zero: true,
+            ..AmountPolicy::default()
+        };
+
+        assert_eq!(policy.apply_precision(amount), expected);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::tx::engine::id::TxId;
+use crate::tx::engine::result::TxError;
+
+/// Why a batch was rolled back, see [`TransactionEngine::execute_batch`].
+///
+/// [`TransactionEngine::execute_batch`]: crate::tx::engine::engine::TransactionEngine::execute_batch
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    /// Position of the failed transaction within the batch, starting at zero.
+    pub index: usize,
+    pub tx_id: TxId,
+    pub error: TxError,
+}
+
+impl From<BatchFailure> for TxError {
+    fn from(failure: BatchFailure) -> Self {
+        TxError::BatchRolledBack(format!(
+            "Batch was rolled back as transaction [{}] at index [{}] failed: {:?}",
+            failure.tx_id, failure.index, failure.error
+        ))
+    }
+}
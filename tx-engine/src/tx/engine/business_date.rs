@@ -0,0 +1,134 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::result::TxError;
+
+/// A calendar day the engine processes transactions for, formatted as `YYYY-MM-DD`. Transactions
+/// with a later value date are kept pending until the business date reaches it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BusinessDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl BusinessDate {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        if month == 0 || month > 12 || day == 0 || day > Self::days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Self { year, month, day })
+    }
+
+    fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    fn is_leap_year(year: u16) -> bool {
+        year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+    }
+}
+
+impl FromStr for BusinessDate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected a date formatted as YYYY-MM-DD, got [{}]", value);
+        let parts = value.trim().split('-').collect::<Vec<_>>();
+
+        let [year, month, day] = parts.as_slice() else {
+            return Err(error());
+        };
+        if year.len() != 4
+            || month.len() != 2
+            || day.len() != 2
+            || !parts
+                .iter()
+                .all(|part| part.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(error());
+        }
+
+        let year = year.parse::<u16>().map_err(|_| error())?;
+        let month = month.parse::<u8>().map_err(|_| error())?;
+        let day = day.parse::<u8>().map_err(|_| error())?;
+
+        Self::new(year, month, day).ok_or_else(error)
+    }
+}
+
+impl Display for BusinessDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A pending transaction that failed once the business date reached its value date. It is
+/// dropped like any other failed transaction, the remaining pending transactions are settled
+/// nonetheless.
+#[derive(Debug, Clone)]
+pub struct SettlementFailure {
+    /// Number of transactions executed by the engine before this one.
+    pub sequence: u64,
+    /// The business date the transaction was settled with.
+    pub date: BusinessDate,
+    pub tx_id: TxId,
+    pub client_id: ClientId,
+    pub error: TxError,
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::tx::engine::business_date::BusinessDate;
+
+    #[rstest]
+    #[case("2024-01-31")]
+    #[case(" 2024-02-29 ")]
+    #[case("2000-02-29")]
+    #[case("0001-12-01")]
+    fn test_parse_success(#[case] given_value: &str) {
+        assert_eq!(
+            given_value.parse::<BusinessDate>().unwrap().to_string(),
+            given_value.trim()
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("2024-1-31")]
+    #[case("2024-13-01")]
+    #[case("2024-04-31")]
+    #[case("2023-02-29")]
+    #[case("1900-02-29")]
+    #[case("2024-00-10")]
+    #[case("2024/01/01")]
+    #[case("+024-01-01")]
+    fn test_parse_failures(#[case] given_value: &str) {
+        assert_eq!(
+            given_value.parse::<BusinessDate>().unwrap_err(),
+            format!(
+                "Expected a date formatted as YYYY-MM-DD, got [{}]",
+                given_value
+            )
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let dates = ["2024-02-01", "2023-12-31", "2024-01-15"]
+            .map(|value| value.parse::<BusinessDate>().unwrap());
+        let mut sorted = dates;
+        sorted.sort();
+
+        assert_eq!(sorted, [dates[1], dates[2], dates[0]]);
+    }
+}
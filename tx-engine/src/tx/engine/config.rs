@@ -0,0 +1,103 @@
+use crate::tx::engine::business_date::BusinessDate;
+use crate::tx::engine::dispute_window::DisputeWindow;
+use crate::tx::engine::fee_schedule::FeeSchedule;
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::interest::InterestConfig;
+use crate::tx::engine::transition::TransitionStrictness;
+use crate::tx::ledger::disk_ledger_store::DiskLedger;
+use crate::tx::ledger::in_memory_ledger_store::InMemoryLedgerStore;
+use crate::tx::ledger::ledger_store::LedgerStore;
+use crate::tx::metrics::metrics::Metrics;
+
+/// Defines within which boundaries the id of a deposit or withdrawal has to be unique.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum TxIdScope {
+    /// Transaction ids only need to be unique within the ledger of a single client.
+    #[default]
+    PerClient,
+    /// Transaction ids need to be unique across all clients. A repeated id is treated as a
+    /// replayed or corrupt feed and rejected with [`TxError::DuplicateTransaction`].
+    ///
+    /// Every id ever used is kept for the lifetime of the engine, even once its entry was
+    /// compacted. With [`LedgerBackend::InMemory`] this memory grows with each deposit,
+    /// withdrawal, transfer and authorization, use [`LedgerBackend::Disk`] for long running
+    /// feeds.
+    ///
+    /// [`TxError::DuplicateTransaction`]: crate::tx::engine::result::TxError::DuplicateTransaction
+    Global,
+}
+
+/// Defines which transactions open the account of a client that has none yet. Transactions of
+/// other clients are rejected.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum AccountOpening {
+    /// Deposits and incoming transfers open the account, as does [`TransactionKind::Open`].
+    ///
+    /// [`TransactionKind::Open`]: crate::tx::engine::transaction::TransactionKind::Open
+    #[default]
+    OnFirstDeposit,
+    /// Only [`TransactionKind::Open`] opens an account.
+    ///
+    /// [`TransactionKind::Open`]: crate::tx::engine::transaction::TransactionKind::Open
+    Explicit,
+}
+
+/// Defines where the ledger entries of the accounts are kept.
+#[derive(Debug, Clone, Default)]
+pub enum LedgerBackend {
+    #[default]
+    InMemory,
+    Disk(DiskLedger),
+}
+
+impl LedgerBackend {
+    pub fn create_store(&self, client_id: ClientId) -> Box<dyn LedgerStore> {
+        match self {
+            LedgerBackend::InMemory => Box::new(InMemoryLedgerStore::new()),
+            LedgerBackend::Disk(ledger) => Box::new(ledger.store(client_id)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EngineConfig {
+    pub tx_id_scope: TxIdScope,
+    pub ledger: LedgerBackend,
+    /// Disputes of transactions older than this window are rejected. If unset, transactions can
+    /// be disputed forever.
+    pub dispute_window: Option<DisputeWindow>,
+    /// Number of executed transactions after which ledger entries outside the dispute window are
+    /// removed. If unset, compaction only happens through [`TransactionEngine::compact`].
+    ///
+    /// [`TransactionEngine::compact`]: crate::tx::engine::engine::TransactionEngine::compact
+    pub compaction_interval: Option<u64>,
+    /// Fees charged for withdrawals, transfers and chargebacks. If unset, no fees are charged.
+    pub fees: Option<FeeSchedule>,
+    /// Interest paid on each [`TransactionKind::AccrueInterest`]. If unset, no interest is paid.
+    ///
+    /// [`TransactionKind::AccrueInterest`]: crate::tx::engine::transaction::TransactionKind::AccrueInterest
+    pub interest: Option<InterestConfig>,
+    /// Whether all changes of the client accounts are booked to a double-entry general ledger,
+    /// see [`TransactionEngine::general_ledger`].
+    ///
+    /// [`TransactionEngine::general_ledger`]: crate::tx::engine::engine::TransactionEngine::general_ledger
+    pub general_ledger: bool,
+    /// The business date the engine starts with. Transactions with a later value date are kept
+    /// pending, if unset all of them are kept pending until the business date is advanced.
+    pub business_date: Option<BusinessDate>,
+    pub account_opening: AccountOpening,
+    /// Open accounts without deposits, withdrawals, transfers or authorizations for this long
+    /// become dormant. If unset, accounts never become dormant.
+    pub dormancy: Option<DisputeWindow>,
+    /// Whether disputes, resolves and chargebacks that don't apply to their transaction are
+    /// ignored or rejected.
+    pub transition_strictness: TransitionStrictness,
+    /// How often a transaction can be disputed, further disputes are rejected. If unset, a
+    /// transaction can be disputed again after each resolve without limit.
+    pub max_dispute_cycles: Option<u32>,
+    /// Whether a representment unlocks the account once none of its transactions is charged
+    /// back anymore. Accounts locked by a rule stay locked.
+    pub unlock_on_representment: bool,
+    /// Where executed transactions and their errors are counted. If unset, nothing is counted.
+    pub metrics: Option<Metrics>,
+}
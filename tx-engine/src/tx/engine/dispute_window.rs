@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The position of a transaction within the processed input, used to measure the age of ledger
+/// entries.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LedgerPosition {
+    /// Number of transactions executed by the engine before this one.
+    pub sequence: u64,
+    /// Seconds since the unix epoch, only known if the input has a timestamp column.
+    pub timestamp: Option<u64>,
+}
+
+/// Defines for how long a deposit or withdrawal can be disputed after it was executed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DisputeWindow {
+    /// Number of subsequently executed transactions (of any client).
+    Transactions(u64),
+    /// Elapsed time according to the timestamps of the transactions. Entries without a
+    /// timestamp never expire.
+    Elapsed(Duration),
+}
+
+impl DisputeWindow {
+    pub fn is_expired(&self, recorded_at: LedgerPosition, now: LedgerPosition) -> bool {
+        match self {
+            DisputeWindow::Transactions(count) => {
+                now.sequence.saturating_sub(recorded_at.sequence) > *count
+            }
+            DisputeWindow::Elapsed(duration) => match (recorded_at.timestamp, now.timestamp) {
+                (Some(recorded_at), Some(now)) => {
+                    now.saturating_sub(recorded_at) > duration.as_secs()
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use crate::tx::engine::dispute_window::{DisputeWindow, LedgerPosition};
+
+    fn position(sequence: u64, timestamp: Option<u64>) -> LedgerPosition {
+        LedgerPosition {
+            sequence,
+            timestamp,
+        }
+    }
+
+    #[rstest]
+    #[case(
+        DisputeWindow::Transactions(2),
+        position(5, None),
+        position(7, None),
+        false
+    )]
+    #[case(
+        DisputeWindow::Transactions(2),
+        position(5, None),
+        position(8, None),
+        true
+    )]
+    #[case(
+        DisputeWindow::Transactions(0),
+        position(5, None),
+        position(5, None),
+        false
+    )]
+    #[case(
+        DisputeWindow::Elapsed(Duration::from_secs(60)),
+        position(1, Some(100)),
+        position(9, Some(160)),
+        false
+    )]
+    #[case(
+        DisputeWindow::Elapsed(Duration::from_secs(60)),
+        position(1, Some(100)),
+        position(2, Some(161)),
+        true
+    )]
+    #[case(
+        DisputeWindow::Elapsed(Duration::from_secs(60)),
+        position(1, None),
+        position(2, Some(1000)),
+        false
+    )]
+    #[case(
+        DisputeWindow::Elapsed(Duration::from_secs(60)),
+        position(1, Some(100)),
+        position(2, None),
+        false
+    )]
+    fn test_is_expired(
+        #[case] window: DisputeWindow,
+        #[case] recorded_at: LedgerPosition,
+        #[case] now: LedgerPosition,
+        #[case] expected_result: bool,
+    ) {
+        assert_eq!(window.is_expired(recorded_at, now), expected_result);
+    }
+}
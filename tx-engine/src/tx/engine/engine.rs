@@ -1,92 +1,2918 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use crate::tx::engine::account::{Account, AccountSummary};
-use crate::tx::engine::result::TxResult;
+use tokio::sync::broadcast;
+use tracing::{debug, debug_span, warn};
+
+use rust_decimal::Decimal;
+
+use crate::tx::accounting::general_ledger::GeneralLedger;
+use crate::tx::engine::account::{Account, AccountStatus, AccountSummary};
+use crate::tx::engine::account_query::AccountQuery;
+use crate::tx::engine::batch::BatchFailure;
+use crate::tx::engine::business_date::{BusinessDate, SettlementFailure};
+use crate::tx::engine::config::{AccountOpening, EngineConfig, LedgerBackend, TxIdScope};
+use crate::tx::engine::dispute_window::LedgerPosition;
+use crate::tx::engine::event::{AccountEvent, EventSubscriber};
+use crate::tx::engine::fee_schedule::{Fee, FeeKind};
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::interest::InterestAccrual;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::simulation::{AccountChange, Rejection, Simulation};
 use crate::tx::engine::transaction::{Transaction, TransactionKind};
+use crate::tx::engine::transition::{
+    DisputeEvent, IgnoredTransition, TransitionOutcome, TransitionStats, TransitionStrictness,
+};
+use crate::tx::metrics::metrics::EngineGauges;
+use crate::tx::rules::transaction_rule::{RuleAction, RuleContext, RuleDecision, TransactionRule};
+
+pub struct TransactionEngine {
+    config: EngineConfig,
+    // ordered by client, so accounts can be queried without sorting them
+    accounts: BTreeMap<ClientId, Account>,
+    // only populated for `TxIdScope::Global` with an in-memory ledger, per client uniqueness is
+    // enforced by the accounts
+    tx_ids: HashSet<TxId>,
+    // credit limits of clients, also for those without an account yet
+    credit_limits: HashMap<ClientId, Decimal>,
+    // number of executed transactions and the latest timestamp seen so far
+    sequence: u64,
+    timestamp: Option<u64>,
+    // interest posted by all accruals so far
+    accruals: Vec<InterestAccrual>,
+    // rules evaluated before each transaction and the violations found so far
+    rules: Vec<(Box<dyn TransactionRule>, RuleAction)>,
+    rule_decisions: Vec<RuleDecision>,
+    // outcomes of all dispute steps and the steps that were ignored so far
+    transition_stats: TransitionStats,
+    ignored_transitions: Vec<IgnoredTransition>,
+    // receivers of account events and the state of the accounts touched by the current
+    // transaction, only tracked if there are any subscribers or a general ledger
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+    snapshots: Vec<AccountSummary>,
+    general_ledger: Option<GeneralLedger>,
+    // the current business date and the transactions waiting for their value date, ordered by
+    // value date and arrival, as well as those that failed once their value date was reached
+    business_date: Option<BusinessDate>,
+    pending: Vec<Transaction>,
+    settlement_failures: Vec<SettlementFailure>,
+    // the batch currently executed, if any
+    batch: Option<Batch>,
+}
+
+/// What is needed to roll back the batch currently executed, see
+/// [`TransactionEngine::execute_batch`].
+struct Batch {
+    // position of the first transaction of the batch
+    position: LedgerPosition,
+    // accounts created and global transaction ids used by the batch
+    created_accounts: Vec<ClientId>,
+    tx_ids: Vec<TxId>,
+    // number of accruals and journal entries before the batch
+    accruals: usize,
+    journal_entries: usize,
+    // events are only published once the whole batch succeeded
+    events: Vec<AccountEvent>,
+    // scheduling state before the batch
+    business_date: Option<BusinessDate>,
+    pending: Vec<Transaction>,
+    settlement_failures: usize,
+    // dispute step outcomes before the batch
+    transition_stats: TransitionStats,
+    ignored_transitions: usize,
+}
+
+impl Default for TransactionEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionEngine {
+    pub fn new() -> Self {
+        Self::with_config(EngineConfig::default())
+    }
+
+    pub fn with_config(config: EngineConfig) -> Self {
+        Self {
+            general_ledger: config.general_ledger.then(GeneralLedger::new),
+            business_date: config.business_date,
+            config,
+            accounts: BTreeMap::new(),
+            tx_ids: HashSet::new(),
+            credit_limits: HashMap::new(),
+            sequence: 0,
+            timestamp: None,
+            accruals: Vec::new(),
+            rules: Vec::new(),
+            rule_decisions: Vec::new(),
+            transition_stats: TransitionStats::default(),
+            ignored_transitions: Vec::new(),
+            subscribers: Vec::new(),
+            snapshots: Vec::new(),
+            pending: Vec::new(),
+            settlement_failures: Vec::new(),
+            batch: None,
+        }
+    }
+
+    /// Executes a transaction and publishes the resulting account events. Events are also
+    /// published for failed transactions, e.g. if a rule locked the account.
+    pub fn execute(&mut self, transaction: Transaction) -> TxResult<()> {
+        let result = self.execute_and_publish(transaction);
+
+        if let Some(metrics) = &self.config.metrics {
+            metrics.record_transaction(&transaction.kind(), result.as_ref().err());
+        }
+
+        result
+    }
+
+    /// Executes a transaction and publishes the resulting account events, without recording
+    /// metrics. Used for transactions that were already counted, e.g. when they were scheduled.
+    fn execute_and_publish(&mut self, transaction: Transaction) -> TxResult<()> {
+        let span = debug_span!(
+            "execute",
+            client = transaction.client_id().as_field(),
+            tx = transaction.tx_id().as_field(),
+            kind = transaction.kind().name()
+        );
+        let _entered = span.enter();
+
+        let result = self.execute_transaction(transaction);
+        let published = self.publish_changes(&transaction);
+        let result = result.and(published);
+
+        match &result {
+            Ok(()) => debug!("transaction executed"),
+            Err(error) => warn!(error = ?error, "transaction rejected"),
+        }
+
+        result
+    }
+
+    /// Executes all transactions of a batch or none of them. If a transaction fails, the changes
+    /// of all transactions of the batch are rolled back and the failed transaction is reported.
+    /// Account events are only published once the whole batch succeeded, rule violations are
+    /// recorded either way.
+    ///
+    /// Transactions of a rolled back batch still count towards dispute windows measured in
+    /// transactions, just like any other failed transaction.
+    pub fn execute_batch(&mut self, transactions: Vec<Transaction>) -> Result<(), BatchFailure> {
+        self.begin_batch();
+
+        for (index, transaction) in transactions.into_iter().enumerate() {
+            if let Err(error) = self.execute(transaction) {
+                // if the rollback fails as well, the state of the engine can't be trusted anymore
+                let error = self.rollback_batch().err().unwrap_or(error);
+                return Err(BatchFailure {
+                    index,
+                    tx_id: transaction.tx_id(),
+                    error,
+                });
+            }
+        }
+
+        self.commit_batch();
+
+        Ok(())
+    }
+
+    /// Executes the transactions as a what-if, without changing the state of the engine. All
+    /// transactions are executed even if some of them fail, the failed ones are reported as
+    /// rejections, as are ignored dispute steps. No events are published and no rule violations,
+    /// transitions or metrics are recorded.
+    pub fn simulate(&mut self, transactions: Vec<Transaction>) -> TxResult<Simulation> {
+        let before = self.account_summary();
+        let rule_decisions = self.rule_decisions.len();
+        let metrics = self.config.metrics.take();
+        let mut rejections = Vec::new();
+
+        self.begin_batch();
+        for (index, transaction) in transactions.into_iter().enumerate() {
+            let ignored_transitions = self.ignored_transitions.len();
+            let settlement_failures = self.settlement_failures.len();
+            if let Err(error) = self.execute(transaction) {
+                rejections.push(Rejection {
+                    index,
+                    tx_id: transaction.tx_id(),
+                    client_id: transaction.client_id(),
+                    error,
+                });
+            }
+            for failure in &self.settlement_failures[settlement_failures..] {
+                rejections.push(Rejection {
+                    index,
+                    tx_id: failure.tx_id,
+                    client_id: failure.client_id,
+                    error: failure.error.clone(),
+                });
+            }
+            for ignored in &self.ignored_transitions[ignored_transitions..] {
+                rejections.push(Rejection {
+                    index,
+                    tx_id: ignored.tx_id,
+                    client_id: ignored.client_id,
+                    error: ignored.error(),
+                });
+            }
+        }
+        let after = self.account_summary();
+
+        let position = self.batch.as_ref().map(|batch| batch.position);
+        self.rollback_batch()?;
+        if let Some(position) = position {
+            self.sequence = position.sequence;
+            self.timestamp = position.timestamp;
+        }
+        self.rule_decisions.truncate(rule_decisions);
+        self.config.metrics = metrics;
+
+        let accounts = after
+            .into_iter()
+            .map(|after| AccountChange {
+                before: before
+                    .binary_search_by_key(&after.id, |before| before.id)
+                    .ok()
+                    .map(|index| before[index]),
+                after,
+            })
+            .collect();
+
+        Ok(Simulation {
+            accounts,
+            rejections,
+        })
+    }
+
+    fn begin_batch(&mut self) {
+        for account in self.accounts.values_mut() {
+            account.checkpoint();
+        }
+        for (rule, _) in self.rules.iter_mut() {
+            rule.checkpoint();
+        }
+
+        self.batch = Some(Batch {
+            position: self.position(),
+            created_accounts: Vec::new(),
+            tx_ids: Vec::new(),
+            accruals: self.accruals.len(),
+            journal_entries: self
+                .general_ledger
+                .as_ref()
+                .map_or(0, |general_ledger| general_ledger.journal().len()),
+            events: Vec::new(),
+            business_date: self.business_date,
+            pending: self.pending.clone(),
+            settlement_failures: self.settlement_failures.len(),
+            transition_stats: self.transition_stats.clone(),
+            ignored_transitions: self.ignored_transitions.len(),
+        });
+    }
+
+    fn commit_batch(&mut self) {
+        let Some(batch) = self.batch.take() else {
+            return;
+        };
+
+        for account in self.accounts.values_mut() {
+            account.commit();
+        }
+        for (rule, _) in self.rules.iter_mut() {
+            rule.commit();
+        }
+        for event in batch.events {
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.notify(&event);
+            }
+        }
+    }
+
+    fn rollback_batch(&mut self) -> TxResult<()> {
+        let Some(batch) = self.batch.take() else {
+            return Ok(());
+        };
+
+        for account in self.accounts.values_mut() {
+            account.rollback()?;
+        }
+        for client_id in batch.created_accounts {
+            self.accounts.remove(&client_id);
+        }
+        for tx_id in batch.tx_ids {
+            self.release_tx_id(tx_id)?;
+        }
+
+        self.business_date = batch.business_date;
+        self.pending = batch.pending;
+        self.settlement_failures.truncate(batch.settlement_failures);
+        self.transition_stats = batch.transition_stats;
+        self.ignored_transitions.truncate(batch.ignored_transitions);
+        self.accruals.truncate(batch.accruals);
+        if let Some(general_ledger) = self.general_ledger.as_mut() {
+            general_ledger.truncate(batch.journal_entries);
+        }
+        for (rule, _) in self.rules.iter_mut() {
+            rule.rollback();
+        }
+
+        Ok(())
+    }
+
+    /// The general ledger all changes of the accounts are booked to, if enabled in the config.
+    pub fn general_ledger(&self) -> Option<&GeneralLedger> {
+        self.general_ledger.as_ref()
+    }
+
+    /// Registers a subscriber that is notified of all subsequent account events.
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Creates a broadcast channel receiving all subsequent account events, receivers lagging
+    /// behind by more than `capacity` events miss the oldest ones.
+    pub fn event_channel(&mut self, capacity: usize) -> broadcast::Receiver<AccountEvent> {
+        let (sender, receiver) = broadcast::channel(capacity);
+        self.subscribe(Box::new(sender));
+        receiver
+    }
+
+    /// Publishes the events of the accounts touched by a transaction and books their changes
+    /// to the general ledger.
+    fn publish_changes(&mut self, transaction: &Transaction) -> TxResult<()> {
+        let changes = std::mem::take(&mut self.snapshots)
+            .into_iter()
+            .map(|before| (before, self.accounts[&before.id].summary()))
+            .collect::<Vec<_>>();
+
+        for (before, after) in changes.iter() {
+            for event in AccountEvent::between(transaction, *before, *after) {
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.events.push(event);
+                    continue;
+                }
+                for subscriber in self.subscribers.iter_mut() {
+                    subscriber.notify(&event);
+                }
+            }
+        }
+
+        match self.general_ledger.as_mut() {
+            Some(general_ledger) => general_ledger.record(transaction, &changes),
+            None => Ok(()),
+        }
+    }
+
+    /// Remembers the state of an account before the current transaction changes it.
+    fn snapshot(&mut self, client_id: ClientId) {
+        if (self.subscribers.is_empty() && self.general_ledger.is_none())
+            || self.snapshots.iter().any(|summary| summary.id == client_id)
+        {
+            return;
+        }
+
+        if let Some(account) = self.accounts.get(&client_id) {
+            self.snapshots.push(account.summary());
+        }
+    }
+
+    fn execute_transaction(&mut self, transaction: Transaction) -> TxResult<()> {
+        if let Some(value_date) = transaction.value_date() {
+            if self.business_date.is_none_or(|date| value_date > date) {
+                return self.schedule(transaction, value_date);
+            }
+        }
+
+        // entries removed by a compaction could not be restored if a batch is rolled back
+        if self.batch.is_none()
+            && self.config.compaction_interval.is_some_and(|interval| {
+                interval > 0 && self.sequence > 0 && self.sequence.is_multiple_of(interval)
+            })
+        {
+            self.compact()?;
+        }
+
+        let position = self.advance(&transaction);
+        let is_ledger_entry = matches!(
+            transaction.kind(),
+            TransactionKind::Withdrawal(_)
+                | TransactionKind::Deposit(_)
+                | TransactionKind::Transfer { .. }
+                | TransactionKind::Authorize(_)
+                | TransactionKind::Close
+                | TransactionKind::AccrueInterest
+        );
+        let track_tx_id = is_ledger_entry && self.config.tx_id_scope == TxIdScope::Global;
+
+        if track_tx_id && self.is_tx_id_used(transaction.tx_id())? {
+            return Err(TxError::DuplicateTransaction(format!(
+                "Attempt to execute a transaction [{}] for account [{}], but its id was already used.",
+                transaction.tx_id(),
+                transaction.client_id()
+            )));
+        }
+
+        // a dispute step of a client without an account can't refer to any of its transactions
+        if matches!(
+            transaction.kind(),
+            TransactionKind::Dispute(_)
+                | TransactionKind::Resolve
+                | TransactionKind::Chargeback(_)
+                | TransactionKind::Representment
+        ) && !self.accounts.contains_key(&transaction.client_id())
+        {
+            return self.record_transition(
+                &transaction,
+                position,
+                TransitionOutcome::IgnoredUnknownTx,
+            );
+        }
+
+        self.require_opened_accounts(&transaction)?;
+
+        if !matches!(
+            transaction.kind(),
+            TransactionKind::AccrueInterest | TransactionKind::AdvanceBusinessDate(_)
+        ) {
+            self.evaluate_rules(&transaction, position)?;
+        }
+
+        match transaction.kind() {
+            TransactionKind::Withdrawal(amount) => self.withdraw(
+                transaction.tx_id(),
+                transaction.client_id(),
+                amount,
+                position,
+            ),
+            TransactionKind::Deposit(amount) => self
+                .account_mut(transaction.client_id(), position)?
+                .deposit(transaction.tx_id(), amount),
+            TransactionKind::Transfer { to_client, amount } => self.transfer(
+                transaction.tx_id(),
+                transaction.client_id(),
+                to_client,
+                amount,
+                position,
+            ),
+            TransactionKind::Dispute(_)
+            | TransactionKind::Resolve
+            | TransactionKind::Chargeback(_)
+            | TransactionKind::Representment => self.execute_dispute_step(transaction, position),
+            TransactionKind::Authorize(amount) => self
+                .account_mut(transaction.client_id(), position)?
+                .authorize(transaction.tx_id(), amount),
+            TransactionKind::Capture(amount) => self.capture(
+                transaction.tx_id(),
+                transaction.client_id(),
+                amount,
+                position,
+            ),
+            TransactionKind::Release => self
+                .account_mut(transaction.client_id(), position)?
+                .release(transaction.tx_id()),
+            TransactionKind::Open => self.open(transaction.client_id(), position),
+            TransactionKind::Close => self
+                .account_mut(transaction.client_id(), position)?
+                .close(transaction.tx_id()),
+            TransactionKind::Cancel => self.cancel(transaction.tx_id(), transaction.client_id()),
+            TransactionKind::AdvanceBusinessDate(date) => self.settle(date),
+            TransactionKind::AccrueInterest => self.accrue_interest(transaction.tx_id(), position),
+        }?;
+
+        if track_tx_id {
+            self.mark_tx_id_used(transaction.tx_id())?;
+        }
+
+        let context = RuleContext {
+            position,
+            account: self.accounts.get(&transaction.client_id()),
+        };
+        for (rule, _) in self.rules.iter_mut() {
+            rule.executed(&transaction, &context);
+        }
+
+        Ok(())
+    }
+
+    /// Rejects transactions for clients without an account, unless the transaction opens it
+    /// according to [`EngineConfig::account_opening`].
+    fn require_opened_accounts(&self, transaction: &Transaction) -> TxResult<()> {
+        let opens_on_deposit = self.config.account_opening == AccountOpening::OnFirstDeposit;
+        let require_opened = |client_id: ClientId, opens: bool| {
+            if opens || self.accounts.contains_key(&client_id) {
+                Ok(())
+            } else {
+                Err(TxError::InvalidOperation(format!(
+                    "Attempt to execute a transaction [{}] for account [{}] which was not opened.",
+                    transaction.tx_id(),
+                    client_id
+                )))
+            }
+        };
+
+        match transaction.kind() {
+            TransactionKind::Open
+            | TransactionKind::Cancel
+            | TransactionKind::AdvanceBusinessDate(_)
+            | TransactionKind::AccrueInterest => Ok(()),
+            TransactionKind::Deposit(_) => {
+                require_opened(transaction.client_id(), opens_on_deposit)
+            }
+            TransactionKind::Transfer { to_client, .. } => {
+                require_opened(transaction.client_id(), false)?;
+                require_opened(to_client, opens_on_deposit)
+            }
+            _ => require_opened(transaction.client_id(), false),
+        }
+    }
+
+    /// Opens the account of a client, or reopens it if it is dormant.
+    fn open(&mut self, client_id: ClientId, position: LedgerPosition) -> TxResult<()> {
+        let exists = self.accounts.contains_key(&client_id);
+        let account = self.account_mut(client_id, position)?;
+
+        if exists {
+            account.reopen()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The business date of the engine, see [`Transaction::with_value_date`].
+    pub fn business_date(&self) -> Option<BusinessDate> {
+        self.business_date
+    }
+
+    /// Moves the business date forward, see [`TransactionKind::AdvanceBusinessDate`].
+    pub fn advance_business_date(&mut self, date: BusinessDate) -> TxResult<()> {
+        self.execute(Transaction::new_business_date(date))
+    }
+
+    /// Returns the transactions waiting for their value date, ordered by value date.
+    pub fn pending(&self) -> &[Transaction] {
+        &self.pending
+    }
+
+    /// Keeps a deposit, withdrawal or transfer pending until the business date reaches its value
+    /// date. Transactions are only checked once they are executed.
+    fn schedule(&mut self, transaction: Transaction, value_date: BusinessDate) -> TxResult<()> {
+        if !matches!(
+            transaction.kind(),
+            TransactionKind::Deposit(_)
+                | TransactionKind::Withdrawal(_)
+                | TransactionKind::Transfer { .. }
+        ) {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to schedule transaction [{}] for account [{}] for value date [{}], but only deposits, withdrawals and transfers can be scheduled.",
+                transaction.tx_id(),
+                transaction.client_id(),
+                value_date
+            )));
+        }
+
+        let index = self
+            .pending
+            .partition_point(|pending| pending.value_date() <= Some(value_date));
+        self.pending.insert(index, transaction);
+
+        Ok(())
+    }
+
+    fn cancel(&mut self, tx_id: TxId, client_id: ClientId) -> TxResult<()> {
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.tx_id() == tx_id && pending.client_id() == client_id)
+            .ok_or(TxError::InvalidOperation(format!(
+                "Attempt to cancel transaction [{}] for account [{}] which is not pending.",
+                tx_id, client_id
+            )))?;
+        self.pending.remove(index);
+
+        Ok(())
+    }
+
+    /// Moves the business date forward and executes all pending transactions whose value date
+    /// is reached, in order. Failing transactions are dropped and recorded, see
+    /// [`TransactionEngine::settlement_failures`].
+    fn settle(&mut self, date: BusinessDate) -> TxResult<()> {
+        if let Some(current) = self.business_date.filter(|current| date < *current) {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to move the business date back from [{}] to [{}].",
+                current, date
+            )));
+        }
+
+        self.business_date = Some(date);
+        while self
+            .pending
+            .first()
+            .is_some_and(|pending| pending.value_date() <= Some(date))
+        {
+            let transaction = self.pending.remove(0);
+            let sequence = self.sequence;
+            if let Err(error) = self.execute_and_publish(transaction) {
+                self.settlement_failures.push(SettlementFailure {
+                    sequence,
+                    date,
+                    tx_id: transaction.tx_id(),
+                    client_id: transaction.client_id(),
+                    error,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the pending transactions that failed once their value date was reached, in the
+    /// order they were settled.
+    pub fn settlement_failures(&self) -> &[SettlementFailure] {
+        &self.settlement_failures
+    }
+
+    /// Adds a rule that is evaluated before each transaction, violations are handled according
+    /// to the given action.
+    pub fn add_rule(&mut self, rule: Box<dyn TransactionRule>, action: RuleAction) {
+        self.rules.push((rule, action));
+    }
+
+    /// Returns the decisions of all rules evaluated so far, passed and violated ones, in the
+    /// order they were made.
+    pub fn rule_decisions(&self) -> &[RuleDecision] {
+        &self.rule_decisions
+    }
+
+    /// Evaluates all rules for a transaction, the decision of each rule is recorded. Fails if
+    /// any violated rule rejects the transaction, locking the account if requested.
+    fn evaluate_rules(
+        &mut self,
+        transaction: &Transaction,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        let context = RuleContext {
+            position,
+            account: self.accounts.get(&transaction.client_id()),
+        };
+        let mut rejection: Option<RuleDecision> = None;
+        let mut lock = false;
+
+        for (rule, action) in self.rules.iter_mut() {
+            let decision = RuleDecision {
+                tx_id: transaction.tx_id(),
+                client_id: transaction.client_id(),
+                rule: rule.name().to_string(),
+                action: *action,
+                reason: rule.evaluate(transaction, &context),
+            };
+
+            if decision.is_violation() {
+                lock |= *action == RuleAction::Lock;
+                if *action != RuleAction::Flag && rejection.is_none() {
+                    rejection = Some(decision.clone());
+                }
+            }
+            self.rule_decisions.push(decision);
+        }
+
+        if lock {
+            self.account_mut(transaction.client_id(), position)?.lock();
+        }
+
+        match rejection {
+            Some(decision) => Err(TxError::RuleViolation(format!(
+                "Transaction [{}] for account [{}] was rejected by rule [{}]: {}.",
+                decision.tx_id,
+                decision.client_id,
+                decision.rule,
+                decision.reason.unwrap_or_default()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Withdraws funds and charges the withdrawal fee, either both are applied or none of them.
+    fn withdraw(
+        &mut self,
+        tx_id: TxId,
+        client_id: ClientId,
+        amount: Decimal,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        let fee = self
+            .fee(client_id, FeeKind::Withdrawal)
+            .amount_for(amount)?;
+        self.require_fee_revenue(fee)?;
+        let account = self.account_mut(client_id, position)?;
+
+        account.require_withdrawable(tx_id, amount, fee)?;
+        account.withdraw(tx_id, amount)?;
+
+        self.charge_fee(tx_id, client_id, fee, position)
+    }
+
+    /// Captures an authorization and charges the withdrawal fee for the captured amount, the
+    /// capture is undone if the house account can not book the fee.
+    fn capture(
+        &mut self,
+        tx_id: TxId,
+        client_id: ClientId,
+        amount: Option<Decimal>,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        let fee = self.fee(client_id, FeeKind::Withdrawal);
+        let account = self.account_mut(client_id, position)?;
+        account.checkpoint();
+        let captured = account
+            .capture_with_fee(tx_id, amount, fee)
+            .and_then(|revenue| self.require_fee_revenue(revenue).map(|()| revenue));
+        let revenue = self.settle_checkpoints(&[client_id], captured)?;
+
+        self.book_fee_revenue(revenue, position)
+    }
+
+    /// Moves funds between two accounts, either both legs are applied or none of them. The
+    /// transfer fee is charged to the sending account.
+    fn transfer(
+        &mut self,
+        tx_id: TxId,
+        from_client: ClientId,
+        to_client: ClientId,
+        amount: Decimal,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        if from_client == to_client {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to transfer an amount [{}] in transaction [{}] from account [{}] to itself.",
+                amount, tx_id, from_client
+            )));
+        }
+
+        let fee = self
+            .fee(from_client, FeeKind::Transfer)
+            .amount_for(amount)?;
+        self.require_fee_revenue(fee)?;
+        // the recipient is only created once the sender can pay
+        self.account_mut(from_client, position)?
+            .require_withdrawable(tx_id, amount, fee)?;
+        self.account_mut(to_client, position)?;
+        let (from, to) = self.account_pair_mut(from_client, to_client);
+
+        to.require_depositable(tx_id, amount)?;
+
+        from.transfer_out(tx_id, to_client, amount)?;
+        to.transfer_in(tx_id, from_client, amount)?;
+
+        self.charge_fee(tx_id, from_client, fee, position)
+    }
+
+    /// Posts a day of interest to all accounts that are neither locked nor closing, either to
+    /// all of them or to none.
+    fn accrue_interest(&mut self, tx_id: TxId, position: LedgerPosition) -> TxResult<()> {
+        let Some(interest) = &self.config.interest else {
+            return Ok(());
+        };
+        let client_ids = self.accounts.keys().copied().collect::<Vec<_>>();
+
+        // the ids of client transactions may be reused by accrual rows, so interest is posted
+        // with an id of its own
+        let entry_id = TxId::internal("interest", position.sequence);
+        let mut accruals = Vec::new();
+        for client_id in client_ids.iter().copied() {
+            let account = self.accounts.get_mut(&client_id).unwrap();
+            account.advance(position);
+            if account.is_locked()
+                || !matches!(
+                    account.status(),
+                    AccountStatus::Open | AccountStatus::Dormant
+                )
+            {
+                continue;
+            }
+
+            let balance = if interest.include_held {
+                account.total()
+            } else {
+                account.available()
+            };
+            if let Some(accrual) = interest.accrue(tx_id, client_id, balance)? {
+                account.require_depositable(entry_id, accrual.interest)?;
+                accruals.push(accrual);
+            }
+        }
+
+        for client_id in client_ids {
+            self.snapshot(client_id);
+        }
+        for accrual in &accruals {
+            self.accounts
+                .get_mut(&accrual.client_id)
+                .unwrap()
+                .post_interest(entry_id, accrual.interest)?;
+        }
+        self.accruals.extend(accruals);
+
+        Ok(())
+    }
+
+    /// Returns the interest posted by all accruals so far, in the order it was posted.
+    pub fn accruals(&self) -> &[InterestAccrual] {
+        &self.accruals
+    }
+
+    /// Charges a fee for an executed transaction and books it as revenue of the house account.
+    fn charge_fee(
+        &mut self,
+        tx_id: TxId,
+        client_id: ClientId,
+        fee: Decimal,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        if fee == Decimal::ZERO {
+            return Ok(());
+        }
+
+        self.account_mut(client_id, position)?
+            .charge_fee(tx_id, fee)?;
+        self.book_fee_revenue(fee, position)
+    }
+
+    fn book_fee_revenue(&mut self, amount: Decimal, position: LedgerPosition) -> TxResult<()> {
+        match self.config.fees.as_ref().map(|fees| fees.house_client_id()) {
+            Some(house_client_id) if amount != Decimal::ZERO => self
+                .account_mut(house_client_id, position)?
+                .book_fee_revenue(amount),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if the house account can not book the given fee revenue, checked before the
+    /// client is charged. A house account that does not exist yet can book any fee but no
+    /// refund.
+    fn require_fee_revenue(&self, amount: Decimal) -> TxResult<()> {
+        let Some(house_client_id) = self.config.fees.as_ref().map(|fees| fees.house_client_id())
+        else {
+            return Ok(());
+        };
+
+        match self.accounts.get(&house_client_id) {
+            Some(house) => house.require_fee_revenue(amount),
+            None => Account::new(house_client_id).require_fee_revenue(amount),
+        }
+    }
+
+    /// Commits the checkpoints of the given accounts if the changes since were applied, rolls
+    /// them back otherwise.
+    fn settle_checkpoints<T>(
+        &mut self,
+        client_ids: &[ClientId],
+        applied: TxResult<T>,
+    ) -> TxResult<T> {
+        for client_id in client_ids {
+            let account = self.accounts.get_mut(client_id).unwrap();
+            match applied {
+                Ok(_) => account.commit(),
+                Err(_) => account.rollback()?,
+            }
+        }
+
+        applied
+    }
+
+    fn fee(&self, client_id: ClientId, kind: FeeKind) -> Fee {
+        self.config
+            .fees
+            .as_ref()
+            .map(|fees| fees.fee(client_id, kind))
+            .unwrap_or_default()
+    }
+
+    /// Disputes, resolves, charges back or represents a transaction. Both legs of a transfer are always
+    /// handled together, the chargeback fee is only charged to the client named in the
+    /// transaction.
+    fn execute_dispute_step(
+        &mut self,
+        transaction: Transaction,
+        position: LedgerPosition,
+    ) -> TxResult<()> {
+        let tx_id = transaction.tx_id();
+        let client_id = transaction.client_id();
+        let fee = self.fee(client_id, FeeKind::Chargeback);
+        let counterparty = self.account_mut(client_id, position)?.counterparty(tx_id)?;
+
+        let Some(counterparty) = counterparty else {
+            let account = self.account_mut(client_id, position)?;
+            account.checkpoint();
+            let applied = Self::apply_dispute_step(account, transaction.kind(), tx_id, fee)
+                .and_then(|applied| self.require_fee_revenue(applied.1).map(|()| applied));
+            let (outcome, revenue) = self.settle_checkpoints(&[client_id], applied)?;
+
+            self.book_fee_revenue(revenue, position)?;
+            return self.record_transition(&transaction, position, outcome);
+        };
+
+        self.account_mut(counterparty, position)?;
+        let (account, other) = self.account_pair_mut(client_id, counterparty);
+
+        // a representment is what unlocks the accounts of a charged back transfer
+        if other.is_locked() && transaction.kind() != TransactionKind::Representment {
+            return Err(TxError::InvalidOperation(format!(
+                "Attempt to execute a transaction [{}] for account [{}] whose transfer counterparty [{}] is locked.",
+                tx_id, client_id, counterparty
+            )));
+        }
+
+        // either both legs are changed or none of them
+        account.checkpoint();
+        other.checkpoint();
+        let applied = Self::apply_dispute_step(account, transaction.kind(), tx_id, fee)
+            .and_then(|(outcome, revenue)| {
+                Self::apply_dispute_step(other, transaction.kind(), tx_id, Fee::default())
+                    .map(|(_, other_revenue)| (outcome, revenue + other_revenue))
+            })
+            .and_then(|applied| self.require_fee_revenue(applied.1).map(|()| applied));
+        let (outcome, revenue) = self.settle_checkpoints(&[client_id, counterparty], applied)?;
+
+        self.book_fee_revenue(revenue, position)?;
+        self.record_transition(&transaction, position, outcome)
+    }
+
+    /// Applies a dispute step to a single account and returns its outcome together with the
+    /// resulting fee revenue.
+    fn apply_dispute_step(
+        account: &mut Account,
+        kind: TransactionKind,
+        tx_id: TxId,
+        chargeback_fee: Fee,
+    ) -> TxResult<(TransitionOutcome, Decimal)> {
+        match kind {
+            TransactionKind::Dispute(None) => account.dispute(tx_id),
+            TransactionKind::Dispute(Some(amount)) => account.dispute_partially(tx_id, amount),
+            TransactionKind::Resolve => account.resolve(tx_id),
+            TransactionKind::Representment => return account.represent_with_fee(tx_id),
+            TransactionKind::Chargeback(amount) => {
+                return account.chargeback_with_fee(tx_id, amount, chargeback_fee);
+            }
+            _ => unreachable!("Not a dispute related transaction [{:?}].", kind),
+        }
+        .map(|outcome| (outcome, Decimal::ZERO))
+    }
+
+    /// Counts the outcome of a dispute step. Steps that were not applied are recorded, or fail
+    /// if transitions are strict.
+    fn record_transition(
+        &mut self,
+        transaction: &Transaction,
+        position: LedgerPosition,
+        outcome: TransitionOutcome,
+    ) -> TxResult<()> {
+        let event = match transaction.kind() {
+            TransactionKind::Dispute(_) => DisputeEvent::Dispute,
+            TransactionKind::Resolve => DisputeEvent::Resolve,
+            TransactionKind::Chargeback(_) => DisputeEvent::Chargeback,
+            TransactionKind::Representment => DisputeEvent::Representment,
+            kind => unreachable!("Not a dispute related transaction [{:?}].", kind),
+        };
+        self.transition_stats.record(event, outcome);
+
+        if outcome == TransitionOutcome::Applied {
+            return Ok(());
+        }
+
+        let ignored = IgnoredTransition {
+            sequence: position.sequence,
+            tx_id: transaction.tx_id(),
+            client_id: transaction.client_id(),
+            event,
+            outcome,
+        };
+        match self.config.transition_strictness {
+            TransitionStrictness::Lenient => {
+                self.ignored_transitions.push(ignored);
+                Ok(())
+            }
+            TransitionStrictness::Strict => Err(ignored.error()),
+        }
+    }
+
+    /// How often each dispute step was applied or ignored so far.
+    pub fn transition_stats(&self) -> &TransitionStats {
+        &self.transition_stats
+    }
+
+    /// Returns the dispute steps that were ignored so far, in the order they were executed.
+    /// Always empty if transitions are strict.
+    pub fn ignored_transitions(&self) -> &[IgnoredTransition] {
+        &self.ignored_transitions
+    }
+
+    /// The current state of all accounts for [`Metrics::set_gauges`], computed on each call.
+    ///
+    /// [`Metrics::set_gauges`]: crate::tx::metrics::metrics::Metrics::set_gauges
+    pub fn gauges(&self) -> EngineGauges {
+        self.accounts
+            .values()
+            .fold(EngineGauges::default(), |gauges, account| EngineGauges {
+                accounts: gauges.accounts + 1,
+                locked_accounts: gauges.locked_accounts + usize::from(account.is_locked()),
+                open_disputes: gauges.open_disputes + account.open_disputes(),
+                ledger_entries: gauges.ledger_entries + account.ledger_entries(),
+            })
+    }
+
+    /// Number of transactions executed so far, including failed ones. See
+    /// [`IgnoredTransition::sequence`].
+    pub fn executed(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the accounts of both sides of a transfer, both of which have to exist already.
+    fn account_pair_mut(
+        &mut self,
+        client_id: ClientId,
+        other_client_id: ClientId,
+    ) -> (&mut Account, &mut Account) {
+        let mut accounts = self
+            .accounts
+            .range_mut(client_id.min(other_client_id)..=client_id.max(other_client_id));
+        let (Some((_, first)), Some((_, last))) = (accounts.next(), accounts.next_back()) else {
+            unreachable!("Both accounts of a transfer are created beforehand.")
+        };
+
+        if client_id < other_client_id {
+            (first, last)
+        } else {
+            (last, first)
+        }
+    }
+
+    fn account_mut(
+        &mut self,
+        client_id: ClientId,
+        position: LedgerPosition,
+    ) -> TxResult<&mut Account> {
+        if !self.accounts.contains_key(&client_id) {
+            let mut account =
+                Account::with_ledger(client_id, self.config.ledger.create_store(client_id));
+            account.set_dispute_window(self.config.dispute_window);
+            account.set_dormancy(self.config.dormancy);
+            account.set_max_dispute_cycles(self.config.max_dispute_cycles);
+            account.set_unlock_on_representment(self.config.unlock_on_representment);
+            if let Some(limit) = self.credit_limits.get(&client_id) {
+                account.set_credit_limit(*limit)?;
+            }
+            if let Some(batch) = self.batch.as_mut() {
+                account.checkpoint();
+                batch.created_accounts.push(client_id);
+            }
+            self.accounts.insert(client_id, account);
+        }
+
+        self.snapshot(client_id);
+
+        let account = self.accounts.get_mut(&client_id).unwrap();
+        account.advance(position);
+        Ok(account)
+    }
+
+    /// Sets the credit limit of a client, see [`Account::set_credit_limit`]. The limit also
+    /// applies if the client has no account yet.
+    pub fn set_credit_limit(
+        &mut self,
+        client_id: impl Into<ClientId>,
+        limit: Decimal,
+    ) -> TxResult<()> {
+        let client_id = client_id.into();
+        if let Some(account) = self.accounts.get_mut(&client_id) {
+            account.set_credit_limit(limit)?;
+        } else if limit < Decimal::ZERO {
+            return Err(TxError::InvalidArgument(format!(
+                "Attempt to set a negative credit limit [{}] for account [{}].",
+                limit, client_id
+            )));
+        }
+
+        self.credit_limits.insert(client_id, limit);
+
+        Ok(())
+    }
+
+    /// Removes ledger entries that can no longer be disputed from all accounts and returns the
+    /// number of removed entries. See [`Account::compact`].
+    pub fn compact(&mut self) -> TxResult<usize> {
+        let position = self.position();
+        let mut removed = 0;
+
+        for account in self.accounts.values_mut() {
+            account.advance(position);
+            removed += account.compact()?;
+        }
+
+        Ok(removed)
+    }
+
+    fn advance(&mut self, transaction: &Transaction) -> LedgerPosition {
+        self.timestamp = self.timestamp.max(transaction.timestamp());
+        let position = self.position();
+        self.sequence += 1;
+        position
+    }
+
+    fn position(&self) -> LedgerPosition {
+        LedgerPosition {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+        }
+    }
+
+    fn is_tx_id_used(&self, tx_id: TxId) -> TxResult<bool> {
+        match &self.config.ledger {
+            LedgerBackend::InMemory => Ok(self.tx_ids.contains(&tx_id)),
+            LedgerBackend::Disk(ledger) => ledger.is_tx_id_used(tx_id),
+        }
+    }
+
+    fn mark_tx_id_used(&mut self, tx_id: TxId) -> TxResult<()> {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.tx_ids.push(tx_id);
+        }
+
+        match &self.config.ledger {
+            LedgerBackend::InMemory => {
+                self.tx_ids.insert(tx_id);
+                Ok(())
+            }
+            LedgerBackend::Disk(ledger) => ledger.mark_tx_id_used(tx_id),
+        }
+    }
+
+    fn release_tx_id(&mut self, tx_id: TxId) -> TxResult<()> {
+        match &self.config.ledger {
+            LedgerBackend::InMemory => {
+                self.tx_ids.remove(&tx_id);
+                Ok(())
+            }
+            LedgerBackend::Disk(ledger) => ledger.release_tx_id(tx_id),
+        }
+    }
+
+    /// Summarizes all accounts, accounts are reported dormant as of the current position even if
+    /// they were not touched for a while.
+    pub fn account_summary(&self) -> Vec<AccountSummary> {
+        self.query(&AccountQuery::new()).collect()
+    }
+
+    /// Summarizes the account of a client, `None` if the client has no account.
+    pub fn account(&self, client_id: impl Into<ClientId>) -> Option<AccountSummary> {
+        self.accounts
+            .get(&client_id.into())
+            .map(|account| self.summarize(account))
+    }
+
+    /// Summarizes all accounts ordered by client, without collecting them.
+    pub fn accounts(&self) -> impl Iterator<Item = AccountSummary> + '_ {
+        self.accounts
+            .values()
+            .map(|account| self.summarize(account))
+    }
+
+    /// Summarizes the accounts matching the query, ordered by client. The accounts are
+    /// summarized one at a time while iterating, none of them are collected.
+    pub fn query<'a>(
+        &'a self,
+        query: &'a AccountQuery,
+    ) -> impl Iterator<Item = AccountSummary> + 'a {
+        query.paginate(self.accounts().filter(|account| query.matches(account)))
+    }
+
+    fn summarize(&self, account: &Account) -> AccountSummary {
+        AccountSummary {
+            status: account.status_at(self.position()),
+            ..account.summary()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+    use tracing::Level;
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use crate::tx::accounting::gl_account::GlAccount;
+    use crate::tx::engine::account::{AccountStatus, AccountSummary};
+    use crate::tx::engine::account_query::{AccountFilter, AccountQuery};
+    use crate::tx::engine::business_date::BusinessDate;
+    use crate::tx::engine::config::{AccountOpening, EngineConfig, LedgerBackend, TxIdScope};
+    use crate::tx::engine::dispute_window::DisputeWindow;
+    use crate::tx::engine::engine::TransactionEngine;
+    use crate::tx::engine::event::AccountEventKind;
+    use crate::tx::engine::fee_schedule::{Fee, FeeKind, FeeSchedule};
+    use crate::tx::engine::interest::{InterestConfig, InterestRate};
+    use crate::tx::engine::ledger_entry::LedgerEntryState;
+    use crate::tx::engine::result::TxError;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::engine::transition::{DisputeEvent, TransitionOutcome, TransitionStrictness};
+    use crate::tx::ledger::disk_ledger_store::DiskLedger;
+    use crate::tx::metrics::metrics::{EngineGauges, Metrics};
+    use crate::tx::rules::blocked_client_rule::BlockedClientRule;
+    use crate::tx::rules::max_deposit_rule::MaxDepositRule;
+    use crate::tx::rules::open_disputes_rule::OpenDisputesRule;
+    use crate::tx::rules::transaction_rule::RuleAction;
+
+    #[test]
+    fn test_basic_happy_case() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 2, dec!(12)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 3, dec!(32)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(3, 2, dec!(1)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(3, 2)).unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(
+            accounts[0],
+            AccountSummary {
+                id: 2.into(),
+                available: dec!(12),
+                held: dec!(-1),
+                authorized: dec!(0),
+                total: dec!(11),
+                is_locked: false,
+                status: AccountStatus::Open,
+                credit_limit: dec!(0),
+                fees: dec!(0),
+            }
+        );
+        assert_eq!(
+            accounts[1],
+            AccountSummary {
+                id: 3.into(),
+                available: dec!(32),
+                held: dec!(0),
+                authorized: dec!(0),
+                total: dec!(32),
+                is_locked: false,
+                status: AccountStatus::Open,
+                credit_limit: dec!(0),
+                fees: dec!(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tx_ids_are_unique_per_client_by_default() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(12)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(1, 2, dec!(32)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_deposit(1, 2, dec!(32)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction [1] twice for account [2].\")"
+        );
+    }
+
+    #[test]
+    fn test_tx_ids_are_unique_across_clients_in_global_scope() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            tx_id_scope: TxIdScope::Global,
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(12)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_withdrawal(1, 2, dec!(32)))
+                    .unwrap_err()
+            ),
+            "DuplicateTransaction(\"Attempt to execute a transaction [1] for account [2], but its id was already used.\")"
+        );
+
+        // rejected transactions do not consume their id
+        engine
+            .execute(Transaction::new_withdrawal(2, 1, dec!(100)))
+            .unwrap_err();
+        engine
+            .execute(Transaction::new_withdrawal(2, 1, dec!(10)))
+            .unwrap();
+
+        // disputes refer to existing ids and are not subject to the uniqueness check
+        engine.execute(Transaction::new_dispute(1, 1)).unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].available, dec!(-10));
+        assert_eq!(accounts[0].held, dec!(12));
+    }
+
+    #[test]
+    fn test_disk_ledger_supports_disputes_of_evicted_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            tx_id_scope: TxIdScope::Global,
+            ledger: LedgerBackend::Disk(DiskLedger::create(directory.path(), 4).unwrap()),
+            ..EngineConfig::default()
+        });
+
+        for tx_id in 0..100u64 {
+            engine
+                .execute(Transaction::new_deposit(tx_id, tx_id % 3, dec!(1)))
+                .unwrap();
+        }
+
+        engine.execute(Transaction::new_dispute(0, 0)).unwrap();
+        engine.execute(Transaction::new_dispute(1, 1)).unwrap();
+        engine.execute(Transaction::new_charge_back(1, 1)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_deposit(2, 0, dec!(1)))
+                    .unwrap_err()
+            ),
+            "DuplicateTransaction(\"Attempt to execute a transaction [2] for account [0], but its id was already used.\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0].available, dec!(33));
+        assert_eq!(accounts[0].held, dec!(1));
+        assert_eq!(accounts[1].total, dec!(32));
+        assert!(accounts[1].is_locked);
+        assert_eq!(accounts[2].total, dec!(33));
+    }
+
+    #[test]
+    fn test_dispute_window_in_transactions() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            dispute_window: Some(DisputeWindow::Transactions(2)),
+            compaction_interval: Some(5),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, dec!(20)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(3, 2, dec!(30)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_dispute(1, 1)).unwrap_err()
+            ),
+            "DisputeWindowExpired(\"Attempt to dispute transaction [1] for account [1] after its dispute window has passed.\")"
+        );
+        engine.execute(Transaction::new_dispute(3, 2)).unwrap();
+
+        // compaction kicks in after the 5th transaction and removes tx 1 and 2
+        engine
+            .execute(Transaction::new_deposit(4, 1, dec!(40)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(5, 1, dec!(50)))
+            .unwrap();
+        assert_eq!(engine.compact().unwrap(), 0);
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(100));
+        assert_eq!(accounts[1].available, dec!(20));
+        assert_eq!(accounts[1].held, dec!(30));
+    }
+
+    #[test]
+    fn test_dispute_window_in_elapsed_time() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            dispute_window: Some(DisputeWindow::Elapsed(Duration::from_secs(60))),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)).with_timestamp(1000))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 1, dec!(20)).with_timestamp(1030))
+            .unwrap();
+        engine
+            .execute(Transaction::new_dispute(2, 1).with_timestamp(1061))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_dispute(1, 1).with_timestamp(1061))
+                    .unwrap_err()
+            ),
+            "DisputeWindowExpired(\"Attempt to dispute transaction [1] for account [1] after its dispute window has passed.\")"
+        );
+        assert_eq!(engine.compact().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_partial_dispute_and_chargeback() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_partial_dispute(1, 1, dec!(4)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_partial_charge_back(1, 1, dec!(3)))
+            .unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(
+            accounts[0],
+            AccountSummary {
+                id: 1.into(),
+                available: dec!(7),
+                held: dec!(0),
+                authorized: dec!(0),
+                total: dec!(7),
+                is_locked: true,
+                status: AccountStatus::Open,
+                credit_limit: dec!(0),
+                fees: dec!(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_transfer_moves_funds_between_accounts() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(2, 1, 2, dec!(4)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(3, 1, 1, dec!(1)))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to transfer an amount [1] in transaction [3] from account [1] to itself.\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(6));
+        assert_eq!(accounts[1].available, dec!(4));
+    }
+
+    #[test]
+    fn test_transfer_is_not_applied_if_one_leg_is_rejected() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(1, 2, dec!(10)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(1, 2)).unwrap();
+        engine.execute(Transaction::new_charge_back(1, 2)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(2, 1, 2, dec!(4)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction on locked account [2].\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(2, 1, 3, dec!(11)))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to withdraw an amount [11] greater than balance [10] in transaction [2] for account [1].\")"
+        );
+
+        // the recipient of a rejected transfer is not created
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].available, dec!(10));
+        assert_eq!(accounts[1].available, dec!(0));
+
+        // the id of a rejected transfer was not consumed by either account
+        engine
+            .execute(Transaction::new_transfer(2, 1, 3, dec!(10)))
+            .unwrap();
+        assert_eq!(engine.account_summary()[2].available, dec!(10));
+    }
+
+    #[test]
+    fn test_transfer_is_disputed_as_unit() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(2, 1, 2, dec!(4)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_partial_dispute(2, 2, dec!(3)))
+            .unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(9));
+        assert_eq!(accounts[0].held, dec!(-3));
+        assert_eq!(accounts[1].available, dec!(1));
+        assert_eq!(accounts[1].held, dec!(3));
+
+        engine.execute(Transaction::new_resolve(2, 1)).unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(6));
+        assert_eq!(accounts[0].held, dec!(0));
+        assert_eq!(accounts[1].available, dec!(4));
+        assert_eq!(accounts[1].held, dec!(0));
+
+        engine.execute(Transaction::new_dispute(2, 1)).unwrap();
+        engine.execute(Transaction::new_charge_back(2, 1)).unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].total, dec!(10));
+        assert_eq!(accounts[1].total, dec!(0));
+        assert!(accounts[0].is_locked);
+        assert!(accounts[1].is_locked);
+    }
+
+    #[test]
+    fn test_failed_dispute_of_transfer_changes_neither_leg() {
+        let mut engine = TransactionEngine::new();
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(100)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(2, 1, 2, dec!(40)))
+            .unwrap();
+        engine.execute(Transaction::new_closing(3, 2)).unwrap();
+        let before = engine.account_summary();
+
+        assert!(engine.execute(Transaction::new_dispute(2, 1)).is_err());
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts, before);
+        assert_eq!(accounts[0].available, dec!(60));
+        assert_eq!(accounts[0].held, dec!(0));
+    }
+
+    #[test]
+    fn test_credit_limits_apply_to_withdrawals_and_transfers() {
+        let mut engine = TransactionEngine::new();
+
+        engine.set_credit_limit(1, dec!(100)).unwrap();
+        engine.execute(Transaction::new_opening(1)).unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(1, 1, dec!(30)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(2, 1, 2, dec!(60)))
+            .unwrap();
+        engine.set_credit_limit(1, dec!(80)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(3, 1, 2, dec!(1)))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to withdraw an amount [1] greater than balance [-90] plus credit limit [80] in transaction [3] for account [1].\")"
+        );
+        assert_eq!(
+            format!("{:?}", engine.set_credit_limit(3, dec!(-1)).unwrap_err()),
+            "InvalidArgument(\"Attempt to set a negative credit limit [-1] for account [3].\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].available, dec!(-90));
+        assert_eq!(accounts[0].credit_limit, dec!(80));
+        assert_eq!(accounts[1].available, dec!(60));
+        assert_eq!(accounts[1].credit_limit, dec!(0));
+    }
+
+    #[test]
+    fn test_fees_are_charged_and_refunded_on_chargeback() {
+        let mut fees = FeeSchedule::new(0);
+        fees.set_fee(
+            FeeKind::Withdrawal,
+            None,
+            Fee {
+                flat: dec!(1),
+                percentage: dec!(1),
+            },
+        );
+        fees.set_fee(
+            FeeKind::Transfer,
+            Some("gold"),
+            Fee {
+                flat: dec!(0.5),
+                percentage: dec!(0),
+            },
+        );
+        fees.set_fee(
+            FeeKind::Chargeback,
+            None,
+            Fee {
+                flat: dec!(15),
+                percentage: dec!(0),
+            },
+        );
+        fees.set_client_tier(2, "gold");
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(100)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, dec!(100)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(3, 1, dec!(50)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(4, 2, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_transfer(5, 1, 2, dec!(10)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_withdrawal(6, 2, dec!(100)))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to withdraw an amount [100] plus fee [2] greater than balance [99.5] in transaction [6] for account [2].\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(2));
+        assert_eq!(accounts[1].available, dec!(48.5));
+        assert_eq!(accounts[1].fees, dec!(1.5));
+        assert_eq!(accounts[2].available, dec!(99.5));
+        assert_eq!(accounts[2].fees, dec!(0.5));
+
+        engine.execute(Transaction::new_dispute(3, 1)).unwrap();
+        engine.execute(Transaction::new_charge_back(3, 1)).unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(15.5));
+        assert_eq!(accounts[0].total, dec!(15.5));
+        assert_eq!(accounts[1].available, dec!(85));
+        assert_eq!(accounts[1].fees, dec!(15));
+        assert!(accounts[1].is_locked);
+    }
+
+    #[test]
+    fn test_interest_is_accrued_for_unlocked_accounts() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            interest: Some(InterestConfig {
+                rates: vec![InterestRate {
+                    minimum_balance: dec!(0),
+                    annual_percentage: dec!(3.65),
+                }],
+                ..InterestConfig::default()
+            }),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(1000)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, dec!(500)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(3, 2, dec!(500)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(4, 3, dec!(10)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(2, 2)).unwrap();
+        engine.execute(Transaction::new_dispute(4, 3)).unwrap();
+        engine.execute(Transaction::new_charge_back(4, 3)).unwrap();
+        engine
+            .execute(Transaction::new_interest_accrual(100))
+            .unwrap();
+
+        let accruals = engine.accruals();
+        assert_eq!(accruals.len(), 2);
+        assert_eq!(accruals[0].client_id, 1.into());
+        assert_eq!(accruals[0].interest, dec!(0.1));
+        assert_eq!(accruals[1].client_id, 2.into());
+        assert_eq!(accruals[1].balance, dec!(500));
+        assert_eq!(accruals[1].interest, dec!(0.05));
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(1000.1));
+        assert_eq!(accounts[1].available, dec!(500.05));
+        assert_eq!(accounts[1].held, dec!(500));
+
+        // interest is posted with an id of its own, which can neither be disputed nor collide
+        // with the ids of client transactions
+        engine.execute(Transaction::new_dispute(100, 1)).unwrap();
+        assert_eq!(
+            engine.ignored_transitions()[0].outcome,
+            TransitionOutcome::IgnoredUnknownTx
+        );
+        engine
+            .execute(Transaction::new_interest_accrual(3))
+            .unwrap();
+        assert_eq!(engine.accruals().len(), 4);
+        assert_eq!(engine.account_summary()[0].available, dec!(1000.2));
+    }
+
+    #[test]
+    fn test_rules_reject_flag_and_lock() {
+        let mut engine = TransactionEngine::new();
+
+        engine.add_rule(Box::new(MaxDepositRule::new(dec!(100))), RuleAction::Flag);
+        engine.add_rule(Box::new(OpenDisputesRule::new(1)), RuleAction::Reject);
+        engine.add_rule(Box::new(BlockedClientRule::new(2)), RuleAction::Lock);
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(150)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 1, dec!(50)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(1, 1)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_dispute(2, 1)).unwrap_err()
+            ),
+            "RuleViolation(\"Transaction [2] for account [1] was rejected by rule [max_open_disputes]: Account already has [1] open disputes, the maximum is [1].\")"
+        );
+
+        engine.execute(Transaction::new_resolve(1, 1)).unwrap();
+        engine.execute(Transaction::new_dispute(2, 1)).unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_deposit(3, 2, dec!(1)))
+                    .unwrap_err()
+            ),
+            "RuleViolation(\"Transaction [3] for account [2] was rejected by rule [blocked_client]: Client [2] is blocked.\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(150));
+        assert_eq!(accounts[0].held, dec!(50));
+        assert_eq!(accounts[1].total, dec!(0));
+        assert!(accounts[1].is_locked);
+
+        // every rule is recorded for each of the 7 transactions, passed or not
+        assert_eq!(engine.rule_decisions().len(), 21);
+        assert_eq!(engine.rule_decisions()[1].rule, "max_open_disputes");
+        assert_eq!(engine.rule_decisions()[1].reason, None);
+
+        let decisions = engine
+            .rule_decisions()
+            .iter()
+            .filter(|decision| decision.is_violation())
+            .collect::<Vec<_>>();
+        assert_eq!(decisions.len(), 3);
+        assert_eq!(decisions[0].rule, "max_deposit");
+        assert_eq!(decisions[0].action, RuleAction::Flag);
+        assert_eq!(decisions[1].tx_id, 2.into());
+        assert_eq!(decisions[1].action, RuleAction::Reject);
+        assert_eq!(decisions[2].client_id, 2.into());
+        assert_eq!(decisions[2].action, RuleAction::Lock);
+    }
+
+    #[test]
+    fn test_fee_revenue_overflow_leaves_client_unchanged() {
+        let mut fees = FeeSchedule::new(9);
+        for kind in [FeeKind::Withdrawal, FeeKind::Transfer] {
+            fees.set_fee(
+                kind,
+                None,
+                Fee {
+                    flat: dec!(1),
+                    percentage: dec!(0),
+                },
+            );
+        }
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 9, Decimal::MAX))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 1, dec!(10)))
+            .unwrap();
+        let before = engine.account_summary();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_withdrawal(3, 1, dec!(4)))
+                    .unwrap_err()
+            ),
+            "Overflow(\"Attempt to change the balance of account [9] beyond the supported range.\")"
+        );
+        assert!(engine
+            .execute(Transaction::new_transfer(4, 1, 2, dec!(4)))
+            .is_err());
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0], before[0]);
+        assert_eq!(accounts[0].available, dec!(10));
+        assert_eq!(accounts[1].total, Decimal::MAX);
+    }
+
+    #[test]
+    fn test_fee_revenue_of_captures_and_chargebacks_is_checked_first() {
+        let mut fees = FeeSchedule::new(9);
+        for kind in [FeeKind::Withdrawal, FeeKind::Chargeback] {
+            fees.set_fee(
+                kind,
+                None,
+                Fee {
+                    flat: dec!(1),
+                    percentage: dec!(0),
+                },
+            );
+        }
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 9, Decimal::MAX),
+            Transaction::new_deposit(2, 1, dec!(10)),
+            Transaction::new_authorization(3, 1, dec!(4)),
+            Transaction::new_deposit(4, 2, dec!(10)),
+            Transaction::new_dispute(4, 2),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+        let before = engine.account_summary();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_capture(3, 1)).unwrap_err()
+            ),
+            "Overflow(\"Attempt to change the balance of account [9] beyond the supported range.\")"
+        );
+        assert!(engine.execute(Transaction::new_charge_back(4, 2)).is_err());
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts, before);
+        assert_eq!(accounts[0].authorized, dec!(4));
+        assert_eq!(accounts[1].held, dec!(10));
+        assert!(!accounts[1].is_locked);
+    }
+
+    #[test]
+    fn test_fee_refund_exceeding_house_account_leaves_client_unchanged() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Withdrawal,
+            None,
+            Fee {
+                flat: dec!(1),
+                percentage: dec!(0),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(100)),
+            Transaction::new_withdrawal(2, 1, dec!(10)),
+            Transaction::new_transfer(3, 9, 5, dec!(1)),
+            Transaction::new_dispute(2, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+        let before = engine.account_summary();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_charge_back(2, 1))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to refund fees [1] greater than the available balance [0] of account [9].\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts, before);
+        assert_eq!(accounts[0].held, dec!(-10));
+        assert!(!accounts[0].is_locked);
+    }
+
+    #[tokio::test]
+    async fn test_events_are_published() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Withdrawal,
+            None,
+            Fee {
+                flat: dec!(1),
+                percentage: dec!(0),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+        let mut receiver = engine.event_channel(16);
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(2, 1, dec!(4)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(3, 1)).unwrap();
+        engine.execute(Transaction::new_dispute(1, 1)).unwrap();
+        engine.execute(Transaction::new_charge_back(1, 1)).unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            events.push((event.client_id, event.tx_id, event.kind));
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                (
+                    1.into(),
+                    1.into(),
+                    AccountEventKind::Credited { amount: dec!(10) }
+                ),
+                (
+                    1.into(),
+                    2.into(),
+                    AccountEventKind::Debited { amount: dec!(4) }
+                ),
+                (
+                    1.into(),
+                    2.into(),
+                    AccountEventKind::FeeCharged { amount: dec!(1) }
+                ),
+                (
+                    9.into(),
+                    2.into(),
+                    AccountEventKind::Credited { amount: dec!(1) }
+                ),
+                (1.into(), 1.into(), AccountEventKind::Disputed),
+                (1.into(), 1.into(), AccountEventKind::ChargedBack),
+                (1.into(), 1.into(), AccountEventKind::Locked),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_general_ledger_matches_accounts() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Transfer,
+            None,
+            Fee {
+                flat: dec!(0.5),
+                percentage: dec!(0),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            interest: Some(InterestConfig {
+                rates: vec![InterestRate {
+                    minimum_balance: dec!(0),
+                    annual_percentage: dec!(36.5),
+                }],
+                ..InterestConfig::default()
+            }),
+            general_ledger: true,
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(100)),
+            Transaction::new_deposit(2, 2, dec!(50)),
+            Transaction::new_transfer(3, 1, 2, dec!(20)),
+            Transaction::new_withdrawal(4, 2, dec!(30)),
+            Transaction::new_dispute(2, 2),
+            Transaction::new_interest_accrual(5),
+            Transaction::new_charge_back(2, 2),
+            Transaction::new_dispute(4, 2),
+        ] {
+            engine.execute(transaction).unwrap_or_default();
+        }
+
+        let general_ledger = engine.general_ledger().unwrap();
+        let accounts = engine.account_summary();
+        general_ledger.reconcile(&accounts).unwrap();
+
+        assert_eq!(
+            general_ledger.balance(GlAccount::Settlement),
+            accounts.iter().map(|account| account.total).sum()
+        );
+        assert_eq!(
+            general_ledger.balance(GlAccount::InterestExpense),
+            dec!(0.08)
+        );
+        assert_eq!(general_ledger.balance(GlAccount::ChargebackLoss), dec!(0));
+        assert_eq!(general_ledger.journal().len(), 7);
+    }
+
+    #[test]
+    fn test_failed_batch_is_rolled_back() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            tx_id_scope: TxIdScope::Global,
+            ledger: LedgerBackend::Disk(DiskLedger::create(directory.path(), 16).unwrap()),
+            general_ledger: true,
+            ..EngineConfig::default()
+        });
+        let mut receiver = engine.event_channel(16);
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        let accounts = engine.account_summary();
+
+        let failure = engine
+            .execute_batch(vec![
+                Transaction::new_deposit(2, 2, dec!(5)),
+                Transaction::new_dispute(1, 1),
+                Transaction::new_withdrawal(3, 1, dec!(100)),
+            ])
+            .unwrap_err();
+        assert_eq!((failure.index, failure.tx_id), (2, 3.into()));
+        assert_eq!(
+            format!("{:?}", TxError::from(failure)),
+            "BatchRolledBack(\"Batch was rolled back as transaction [3] at index [2] failed: InvalidArgument(\\\"Attempt to withdraw an amount [100] greater than balance [0] in transaction [3] for account [1].\\\")\")"
+        );
+        assert_eq!(engine.account_summary(), accounts);
+        assert_eq!(engine.general_ledger().unwrap().journal().len(), 1);
+        assert_eq!(receiver.try_recv().unwrap().tx_id, 1.into());
+        assert!(receiver.try_recv().is_err());
+
+        // the ids and ledger entries of the rolled back transactions are gone
+        engine
+            .execute_batch(vec![
+                Transaction::new_deposit(2, 2, dec!(5)),
+                Transaction::new_dispute(1, 1),
+                Transaction::new_resolve(1, 1),
+            ])
+            .unwrap();
+        assert_eq!(engine.account_summary()[0].available, dec!(10));
+        assert_eq!(engine.account_summary()[1].available, dec!(5));
+        assert_eq!(receiver.try_recv().unwrap().tx_id, 2.into());
+        engine
+            .general_ledger()
+            .unwrap()
+            .reconcile(&engine.account_summary())
+            .unwrap();
+    }
 
-pub struct TransactionEngine {
-    accounts: HashMap<u16, Account>,
-}
+    #[test]
+    fn test_simulation_does_not_change_state() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            general_ledger: true,
+            ..EngineConfig::default()
+        });
+        engine.add_rule(Box::new(MaxDepositRule::new(dec!(3))), RuleAction::Flag);
+        let mut receiver = engine.event_channel(16);
 
-impl TransactionEngine {
-    pub fn new() -> Self {
-        Self {
-            accounts: HashMap::new(),
-        }
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(2)))
+            .unwrap();
+        let accounts = engine.account_summary();
+        let rule_decisions = engine.rule_decisions().to_vec();
+
+        let simulation = engine
+            .simulate(vec![
+                Transaction::new_deposit(2, 2, dec!(5)),
+                Transaction::new_withdrawal(3, 1, dec!(1.5)),
+                Transaction::new_withdrawal(4, 1, dec!(100)),
+            ])
+            .unwrap();
+
+        assert_eq!(simulation.accounts.len(), 2);
+        assert_eq!(simulation.accounts[0].before, Some(accounts[0]));
+        assert_eq!(simulation.accounts[0].available_delta(), dec!(-1.5));
+        assert_eq!(simulation.accounts[1].before, None);
+        assert_eq!(simulation.accounts[1].after.total, dec!(5));
+        assert_eq!(
+            simulation
+                .rejections
+                .iter()
+                .map(|rejection| (rejection.index, rejection.tx_id))
+                .collect::<Vec<_>>(),
+            vec![(2, 4.into())]
+        );
+
+        assert_eq!(engine.account_summary(), accounts);
+        assert_eq!(engine.rule_decisions(), rule_decisions);
+        assert_eq!(engine.general_ledger().unwrap().journal().len(), 1);
+        assert_eq!(receiver.try_recv().unwrap().tx_id, 1.into());
+        assert!(receiver.try_recv().is_err());
+        engine
+            .execute(Transaction::new_withdrawal(3, 1, dec!(1.5)))
+            .unwrap();
     }
 
-    pub fn execute(&mut self, transaction: Transaction) -> TxResult<()> {
-        let account = self
-            .accounts
-            .entry(transaction.client_id())
-            .or_insert_with(|| Account::new(transaction.client_id()));
+    #[test]
+    fn test_authorizations_are_booked_to_the_general_ledger() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Withdrawal,
+            None,
+            Fee {
+                flat: dec!(0.5),
+                percentage: dec!(0),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            tx_id_scope: TxIdScope::Global,
+            general_ledger: true,
+            ..EngineConfig::default()
+        });
 
-        match transaction.kind() {
-            TransactionKind::Withdrawal(amount) => account.withdraw(transaction.tx_id(), amount),
-            TransactionKind::Deposit(amount) => account.deposit(transaction.tx_id(), amount),
-            TransactionKind::Dispute => account.dispute(transaction.tx_id()),
-            TransactionKind::Resolve => account.resolve(transaction.tx_id()),
-            TransactionKind::Chargeback => account.chargeback(transaction.tx_id()),
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(100)),
+            Transaction::new_authorization(2, 1, dec!(40)),
+            Transaction::new_authorization(3, 1, dec!(20)),
+            Transaction::new_partial_capture(2, 1, dec!(30)),
+            Transaction::new_release(3, 1),
+            Transaction::new_authorization(4, 1, dec!(10)),
+        ] {
+            engine.execute(transaction).unwrap();
         }
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_authorization(4, 2, dec!(1)))
+                    .unwrap_err()
+            ),
+            "DuplicateTransaction(\"Attempt to execute a transaction [4] for account [2], but its id was already used.\")"
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(59.5));
+        assert_eq!(accounts[0].authorized, dec!(10));
+        assert_eq!(accounts[0].total, dec!(69.5));
+        assert_eq!(accounts[0].fees, dec!(0.5));
+        assert_eq!(accounts[1].available, dec!(0.5));
+
+        let general_ledger = engine.general_ledger().unwrap();
+        general_ledger.reconcile(&accounts).unwrap();
+        assert_eq!(
+            general_ledger.balance(GlAccount::AuthorizationsHeld),
+            dec!(-10)
+        );
     }
 
-    pub fn account_summary(&self) -> Vec<AccountSummary> {
-        let mut accounts = self
-            .accounts
-            .iter()
-            .map(|(_, account)| account.summary())
-            .collect::<Vec<_>>();
+    #[test]
+    fn test_scheduled_transactions_settle_on_their_value_date() {
+        let date = |value: &str| value.parse::<BusinessDate>().unwrap();
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            business_date: Some(date("2024-03-01")),
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_deposit(2, 1, dec!(5)).with_value_date(date("2024-03-03")),
+            Transaction::new_withdrawal(3, 1, dec!(12)).with_value_date(date("2024-03-02")),
+            Transaction::new_withdrawal(4, 1, dec!(1)).with_value_date(date("2024-03-01")),
+            Transaction::new_deposit(5, 1, dec!(1)).with_value_date(date("2024-03-03")),
+            Transaction::new_cancellation(5, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        assert_eq!(engine.account_summary()[0].available, dec!(9));
+        assert_eq!(
+            engine
+                .pending()
+                .iter()
+                .map(|transaction| transaction.tx_id())
+                .collect::<Vec<_>>(),
+            vec![3.into(), 2.into()]
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_cancellation(5, 1))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to cancel transaction [5] for account [1] which is not pending.\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_dispute(1, 1).with_value_date(date("2024-03-02")))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to schedule transaction [1] for account [1] for value date [2024-03-02], but only deposits, withdrawals and transfers can be scheduled.\")"
+        );
+
+        // the failing withdrawal is dropped and recorded, the deposit is not due yet
+        engine.advance_business_date(date("2024-03-02")).unwrap();
+        let failures = engine.settlement_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].date, date("2024-03-02"));
+        assert_eq!(
+            (failures[0].tx_id, failures[0].client_id),
+            (3.into(), 1.into())
+        );
+        assert_eq!(
+            format!("{:?}", failures[0].error),
+            "InvalidArgument(\"Attempt to withdraw an amount [12] greater than balance [9] in transaction [3] for account [1].\")"
+        );
+        assert_eq!(engine.business_date(), Some(date("2024-03-02")));
+        assert_eq!(engine.pending().len(), 1);
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .advance_business_date(date("2024-03-01"))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to move the business date back from [2024-03-02] to [2024-03-01].\")"
+        );
 
-        accounts.sort_by(|a, b| a.id.cmp(&b.id));
+        let simulation = engine
+            .simulate(vec![Transaction::new_business_date(date("2024-03-03"))])
+            .unwrap();
+        assert_eq!(simulation.accounts[0].after.available, dec!(14));
+        assert_eq!(engine.pending().len(), 1);
 
-        accounts
+        engine
+            .execute(Transaction::new_business_date(date("2024-03-04")))
+            .unwrap();
+        assert_eq!(engine.account_summary()[0].available, dec!(14));
+        assert!(engine.pending().is_empty());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use rust_decimal_macros::dec;
+    #[test]
+    fn test_settlement_continues_after_a_failure() {
+        let date = |value: &str| value.parse::<BusinessDate>().unwrap();
+        let metrics = Metrics::new();
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            business_date: Some(date("2024-03-01")),
+            metrics: Some(metrics.clone()),
+            ..EngineConfig::default()
+        });
 
-    use crate::tx::engine::account::AccountSummary;
-    use crate::tx::engine::engine::TransactionEngine;
-    use crate::tx::engine::transaction::Transaction;
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_withdrawal(2, 1, dec!(12)).with_value_date(date("2024-03-02")),
+            Transaction::new_withdrawal(3, 1, dec!(4)).with_value_date(date("2024-03-02")),
+            Transaction::new_business_date(date("2024-03-02")),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        assert_eq!(engine.account_summary()[0].available, dec!(6));
+        assert!(engine.pending().is_empty());
+        assert_eq!(
+            engine
+                .settlement_failures()
+                .iter()
+                .map(|failure| (failure.sequence, failure.tx_id))
+                .collect::<Vec<_>>(),
+            vec![(2, 2.into())]
+        );
+        // scheduled transactions are only counted once
+        assert_eq!(metrics.transactions("withdrawal"), 2);
+        assert_eq!(metrics.rejections("invalid_argument"), 0);
+    }
 
     #[test]
-    fn test_basic_happy_case() {
+    fn test_accounts_are_opened_according_to_policy() {
         let mut engine = TransactionEngine::new();
 
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_withdrawal(1, 1, dec!(1)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction [1] for account [1] which was not opened.\")"
+        );
         engine
-            .execute(Transaction::new_deposit(1, 2, dec!(12)))
+            .execute(Transaction::new_deposit(2, 1, dec!(10)))
             .unwrap();
         engine
-            .execute(Transaction::new_deposit(2, 3, dec!(32)))
+            .execute(Transaction::new_transfer(3, 1, 2, dec!(4)))
             .unwrap();
+        engine.execute(Transaction::new_dispute(3, 3)).unwrap();
+        engine.execute(Transaction::new_charge_back(3, 3)).unwrap();
+        assert_eq!(engine.account_summary().len(), 2);
+        assert_eq!(
+            engine
+                .ignored_transitions()
+                .iter()
+                .map(|ignored| (ignored.client_id, ignored.outcome))
+                .collect::<Vec<_>>(),
+            vec![
+                (3.into(), TransitionOutcome::IgnoredUnknownTx),
+                (3.into(), TransitionOutcome::IgnoredUnknownTx),
+            ]
+        );
+
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            account_opening: AccountOpening::Explicit,
+            ..EngineConfig::default()
+        });
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_deposit(1, 1, dec!(10)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction [1] for account [1] which was not opened.\")"
+        );
+        engine.execute(Transaction::new_opening(1)).unwrap();
         engine
-            .execute(Transaction::new_withdrawal(3, 2, dec!(1)))
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
             .unwrap();
-        engine.execute(Transaction::new_dispute(3, 2)).unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(2, 1, 2, dec!(4)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction [2] for account [2] which was not opened.\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_opening(1)).unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to open account [1] which is open.\")"
+        );
+        assert_eq!(engine.account_summary().len(), 1);
+    }
+
+    #[test]
+    fn test_closed_account_is_paid_out() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            general_ledger: true,
+            ..EngineConfig::default()
+        });
+        let (sender, receiver) = std::sync::mpsc::channel();
+        engine.subscribe(Box::new(sender));
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_deposit(2, 1, dec!(5)),
+            Transaction::new_dispute(2, 1),
+            Transaction::new_closing(3, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+        assert_eq!(engine.account_summary()[0].status, AccountStatus::Closing);
+        assert_eq!(engine.account_summary()[0].total, dec!(15));
+
+        engine.execute(Transaction::new_resolve(2, 1)).unwrap();
 
         let accounts = engine.account_summary();
-        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].status, AccountStatus::Closed);
+        assert_eq!(accounts[0].total, dec!(0));
         assert_eq!(
-            accounts[0],
-            AccountSummary {
-                id: 2,
-                available: dec!(12),
-                held: dec!(-1),
-                total: dec!(11),
-                is_locked: false,
+            receiver
+                .try_iter()
+                .map(|event| event.kind)
+                .filter(|kind| *kind == AccountEventKind::Closed)
+                .count(),
+            1
+        );
+        engine
+            .general_ledger()
+            .unwrap()
+            .reconcile(&accounts)
+            .unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_deposit(4, 1, dec!(1)))
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to execute a transaction [4] on closed account [1].\")"
+        );
+    }
+
+    #[test]
+    fn test_dormant_accounts_are_reported_without_activity() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            dormancy: Some(DisputeWindow::Transactions(1)),
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_deposit(2, 2, dec!(10)),
+            Transaction::new_deposit(3, 2, dec!(10)),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].status, AccountStatus::Dormant);
+        assert_eq!(accounts[1].status, AccountStatus::Open);
+
+        engine.execute(Transaction::new_opening(1)).unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(4, 1, dec!(1)))
+            .unwrap();
+        assert_eq!(engine.account_summary()[0].available, dec!(9));
+    }
+
+    #[test]
+    fn test_ignored_transitions_are_recorded_or_rejected() {
+        let mut engine = TransactionEngine::new();
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_resolve(1, 1),
+            Transaction::new_dispute(1, 1),
+            Transaction::new_charge_back(2, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        let stats = engine.transition_stats();
+        assert_eq!(
+            stats.count(DisputeEvent::Dispute, TransitionOutcome::Applied),
+            1
+        );
+        assert_eq!(
+            stats.count(
+                DisputeEvent::Chargeback,
+                TransitionOutcome::IgnoredUnknownTx
+            ),
+            1
+        );
+        assert_eq!(
+            engine
+                .ignored_transitions()
+                .iter()
+                .map(|ignored| (ignored.sequence, ignored.outcome))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    1,
+                    TransitionOutcome::IgnoredInvalidState {
+                        from: LedgerEntryState::Normal,
+                        event: DisputeEvent::Resolve,
+                    }
+                ),
+                (3, TransitionOutcome::IgnoredUnknownTx),
+            ]
+        );
+
+        let simulation = engine
+            .simulate(vec![
+                Transaction::new_resolve(1, 1),
+                Transaction::new_resolve(1, 1),
+            ])
+            .unwrap();
+        assert_eq!(simulation.rejections.len(), 1);
+        assert_eq!(simulation.rejections[0].index, 1);
+        assert_eq!(
+            format!("{:?}", simulation.rejections[0].error),
+            "InvalidTransition(\"Attempt to resolve transaction [1] for account [1] in state [normal].\")"
+        );
+        assert_eq!(engine.ignored_transitions().len(), 2);
+
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            transition_strictness: TransitionStrictness::Strict,
+            ..EngineConfig::default()
+        });
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_resolve(1, 1)).unwrap_err()
+            ),
+            "InvalidTransition(\"Attempt to resolve transaction [1] for account [1] in state [normal].\")"
+        );
+        assert!(engine.ignored_transitions().is_empty());
+    }
+
+    #[test]
+    fn test_representment_of_zero_amount_chargeback() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            unlock_on_representment: true,
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(0)),
+            Transaction::new_dispute(1, 1),
+            Transaction::new_charge_back(1, 1),
+            Transaction::new_representment(1, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].total, dec!(0));
+        assert!(!accounts[0].is_locked);
+    }
+
+    #[test]
+    fn test_representment_charges_the_refunded_fee_again() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Withdrawal,
+            None,
+            Fee {
+                flat: dec!(1),
+                percentage: dec!(0),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(100)),
+            Transaction::new_withdrawal(2, 1, dec!(10)),
+            Transaction::new_dispute(2, 1),
+            Transaction::new_charge_back(2, 1),
+            Transaction::new_representment(2, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, dec!(89));
+        assert_eq!(accounts[0].fees, dec!(1));
+        assert_eq!(accounts[1].total, dec!(1));
+    }
+
+    #[test]
+    fn test_representment_of_transfer_unlocks_both_accounts() {
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            general_ledger: true,
+            max_dispute_cycles: Some(2),
+            unlock_on_representment: true,
+            ..EngineConfig::default()
+        });
+        let mut receiver = engine.event_channel(64);
+
+        for transaction in [
+            Transaction::new_deposit(1, 1, dec!(10)),
+            Transaction::new_transfer(2, 1, 2, dec!(4)),
+            Transaction::new_dispute(2, 1),
+            Transaction::new_resolve(2, 1),
+            Transaction::new_dispute(2, 1),
+            Transaction::new_charge_back(2, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+        let accounts = engine.account_summary();
+        assert!(accounts[0].is_locked && accounts[1].is_locked);
+
+        engine
+            .execute(Transaction::new_representment(2, 1))
+            .unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].total, dec!(6));
+        assert_eq!(accounts[1].total, dec!(4));
+        assert!(!accounts[0].is_locked && !accounts[1].is_locked);
+        assert_eq!(
+            engine
+                .transition_stats()
+                .count(DisputeEvent::Representment, TransitionOutcome::Applied),
+            1
+        );
+        engine
+            .general_ledger()
+            .unwrap()
+            .reconcile(&accounts)
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if event.tx_id == 2.into() && event.kind == AccountEventKind::Unlocked {
+                events.push(event.client_id);
             }
+        }
+        assert_eq!(events, vec![1.into(), 2.into()]);
+
+        for transaction in [
+            Transaction::new_deposit(3, 1, dec!(1)),
+            Transaction::new_dispute(3, 1),
+            Transaction::new_resolve(3, 1),
+            Transaction::new_dispute(3, 1),
+            Transaction::new_resolve(3, 1),
+        ] {
+            engine.execute(transaction).unwrap();
+        }
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine.execute(Transaction::new_dispute(3, 1)).unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to dispute transaction [3] for account [1] more than [2] times.\")"
         );
+    }
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_execution_is_traced() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(Level::TRACE)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(logs.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut engine = TransactionEngine::new();
+            engine
+                .execute(Transaction::new_deposit(1, 7, dec!(10)))
+                .unwrap();
+            engine
+                .execute(Transaction::new_withdrawal(2, 7, dec!(20)))
+                .unwrap_err();
+        });
+
+        // the message and the span of each event, timings vary
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let events = logs
+            .lines()
+            .map(|line| {
+                let event = serde_json::from_str::<serde_json::Value>(line).unwrap();
+                (event["fields"]["message"].clone(), event["span"].clone())
+            })
+            .collect::<Vec<_>>();
+
+        let deposit = json!({"name": "execute", "client": 7, "tx": 1, "kind": "deposit"});
+        let withdrawal = json!({"name": "execute", "client": 7, "tx": 2, "kind": "withdrawal"});
         assert_eq!(
-            accounts[1],
-            AccountSummary {
-                id: 3,
-                available: dec!(32),
-                held: dec!(0),
-                total: dec!(32),
-                is_locked: false,
+            events,
+            vec![
+                (
+                    json!("close"),
+                    json!({"name": "deposit", "client": 7, "tx": 1, "amount": "10"})
+                ),
+                (json!("transaction executed"), deposit.clone()),
+                (json!("close"), deposit),
+                (json!("transaction rejected"), withdrawal.clone()),
+                (json!("close"), withdrawal),
+            ]
+        );
+        assert!(logs.contains("\"error\":\"InvalidArgument(\\\"Attempt to withdraw an amount [20]"));
+    }
+
+    #[test]
+    fn test_metrics() {
+        let metrics = Metrics::new();
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            metrics: Some(metrics.clone()),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, dec!(5)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(3, 2, dec!(6)))
+            .unwrap_err();
+        engine.execute(Transaction::new_dispute(1, 1)).unwrap();
+        engine.execute(Transaction::new_dispute(2, 2)).unwrap();
+        engine.execute(Transaction::new_charge_back(2, 2)).unwrap();
+        engine
+            .simulate(vec![Transaction::new_deposit(4, 3, dec!(1))])
+            .unwrap();
+
+        assert_eq!(metrics.transactions("deposit"), 2);
+        assert_eq!(metrics.transactions("withdrawal"), 1);
+        assert_eq!(metrics.transactions("dispute"), 2);
+        assert_eq!(metrics.transactions("chargeback"), 1);
+        assert_eq!(metrics.rejections("invalid_argument"), 1);
+        assert_eq!(
+            engine.gauges(),
+            EngineGauges {
+                accounts: 2,
+                locked_accounts: 1,
+                open_disputes: 1,
+                ledger_entries: 2,
             }
         );
     }
+
+    #[test]
+    fn test_overflows_are_rejected() {
+        let mut fees = FeeSchedule::new(9);
+        fees.set_fee(
+            FeeKind::Transfer,
+            None,
+            Fee {
+                flat: dec!(0),
+                percentage: dec!(200),
+            },
+        );
+        let mut engine = TransactionEngine::with_config(EngineConfig {
+            fees: Some(fees),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(Transaction::new_deposit(1, 1, Decimal::MAX))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, Decimal::MAX))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(3, 1, 2, dec!(1)))
+                    .unwrap_err()
+            ),
+            "Overflow(\"Attempt to change the balance of account [2] beyond the supported range.\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(Transaction::new_transfer(4, 1, 2, Decimal::MAX))
+                    .unwrap_err()
+            ),
+            format!(
+                "Overflow(\"Attempt to calculate the fee for an amount [{}] beyond the supported range.\")",
+                Decimal::MAX
+            )
+        );
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].available, Decimal::MAX);
+        assert_eq!(accounts[1].available, Decimal::MAX);
+    }
+
+    #[test]
+    fn test_query_accounts() {
+        let mut engine = TransactionEngine::new();
+        engine
+            .execute(Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_deposit(2, 2, dec!(5)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(2, 2)).unwrap();
+        engine
+            .execute(Transaction::new_deposit(3, 3, dec!(3)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(3, 3)).unwrap();
+        engine.execute(Transaction::new_charge_back(3, 3)).unwrap();
+        engine
+            .execute(Transaction::new_deposit(4, 4, dec!(5)))
+            .unwrap();
+        engine
+            .execute(Transaction::new_withdrawal(5, 4, dec!(4)))
+            .unwrap();
+        engine.execute(Transaction::new_dispute(4, 4)).unwrap();
+
+        fn ids(accounts: impl Iterator<Item = AccountSummary>) -> Vec<String> {
+            accounts.map(|account| account.id.to_string()).collect()
+        }
+
+        assert_eq!(engine.account(4).unwrap().available, dec!(-4));
+        assert_eq!(engine.account(5), None);
+        assert_eq!(engine.accounts().count(), 4);
+        assert_eq!(
+            ids(engine.query(&AccountQuery::new().with_filter(AccountFilter::Locked))),
+            vec!["3"]
+        );
+        assert_eq!(
+            ids(engine.query(&AccountQuery::new().with_filter(AccountFilter::NonZeroHeld))),
+            vec!["2", "4"]
+        );
+        assert_eq!(
+            ids(engine.query(
+                &AccountQuery::new()
+                    .with_filter(AccountFilter::NonZeroHeld)
+                    .with_filter(AccountFilter::NegativeAvailable)
+            )),
+            vec!["4"]
+        );
+        assert_eq!(
+            ids(engine.query(&AccountQuery::new().with_filter(AccountFilter::TotalAbove(dec!(4))))),
+            vec!["1", "2"]
+        );
+        assert_eq!(
+            ids(engine.query(&AccountQuery::new().with_page(1, 2))),
+            vec!["2", "3"]
+        );
+        assert_eq!(
+            ids(engine.query(&AccountQuery::new().with_page(4, 2))),
+            Vec::<String>::new()
+        );
+    }
 }
@@ -0,0 +1,245 @@
+use std::sync::mpsc;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::tx::engine::account::{AccountStatus, AccountSummary};
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccountEventKind {
+    /// The total balance of the account increased by the amount.
+    Credited {
+        amount: Decimal,
+    },
+    /// The total balance of the account decreased by the amount.
+    Debited {
+        amount: Decimal,
+    },
+    /// A fee was charged to the account, reported apart from the transaction it was charged
+    /// for.
+    FeeCharged {
+        amount: Decimal,
+    },
+    /// Fees were refunded to the account, e.g. on a chargeback.
+    FeeRefunded {
+        amount: Decimal,
+    },
+    Disputed,
+    Resolved,
+    ChargedBack,
+    Authorized,
+    Captured,
+    Released,
+    Locked,
+    /// A representment lifted the lock of a chargeback, see
+    /// [`EngineConfig::unlock_on_representment`].
+    ///
+    /// [`EngineConfig::unlock_on_representment`]: crate::tx::engine::config::EngineConfig::unlock_on_representment
+    Unlocked,
+    /// The remaining funds were paid out and the account was closed, see
+    /// [`AccountStatus::Closed`].
+    Closed,
+}
+
+/// A change of an account caused by a transaction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct AccountEvent {
+    #[serde(flatten)]
+    pub kind: AccountEventKind,
+    #[serde(rename = "tx")]
+    pub tx_id: TxId,
+    #[serde(rename = "client")]
+    pub client_id: ClientId,
+    pub before: AccountSummary,
+    pub after: AccountSummary,
+}
+
+impl AccountEvent {
+    /// Derives the events of an account from its state before and after a transaction. Held
+    /// and authorized funds only change for the accounts a dispute step or an authorization
+    /// applies to, all other changes are reported as credits or debits (e.g. interest). Fees
+    /// are reported on their own, so credits and debits don't include them.
+    pub fn between(
+        transaction: &Transaction,
+        before: AccountSummary,
+        after: AccountSummary,
+    ) -> Vec<AccountEvent> {
+        let mut kinds = Vec::new();
+
+        if before.held != after.held {
+            match transaction.kind() {
+                TransactionKind::Dispute(_) => kinds.push(AccountEventKind::Disputed),
+                TransactionKind::Resolve => kinds.push(AccountEventKind::Resolved),
+                TransactionKind::Chargeback(_) => kinds.push(AccountEventKind::ChargedBack),
+                _ => {}
+            }
+        }
+
+        if before.authorized != after.authorized {
+            match transaction.kind() {
+                TransactionKind::Authorize(_) => kinds.push(AccountEventKind::Authorized),
+                TransactionKind::Capture(_) => kinds.push(AccountEventKind::Captured),
+                TransactionKind::Release => kinds.push(AccountEventKind::Released),
+                _ => {}
+            }
+        }
+
+        let fees = after.fees - before.fees;
+        let total = after.total - before.total + fees;
+        if kinds.is_empty() && total > Decimal::ZERO {
+            kinds.push(AccountEventKind::Credited { amount: total });
+        } else if kinds.is_empty() && total < Decimal::ZERO {
+            kinds.push(AccountEventKind::Debited { amount: -total });
+        }
+
+        if fees > Decimal::ZERO {
+            kinds.push(AccountEventKind::FeeCharged { amount: fees });
+        } else if fees < Decimal::ZERO {
+            kinds.push(AccountEventKind::FeeRefunded { amount: -fees });
+        }
+
+        if !before.is_locked && after.is_locked {
+            kinds.push(AccountEventKind::Locked);
+        } else if before.is_locked && !after.is_locked {
+            kinds.push(AccountEventKind::Unlocked);
+        }
+        if before.status != AccountStatus::Closed && after.status == AccountStatus::Closed {
+            kinds.push(AccountEventKind::Closed);
+        }
+
+        kinds
+            .into_iter()
+            .map(|kind| AccountEvent {
+                kind,
+                tx_id: transaction.tx_id(),
+                client_id: after.id,
+                before,
+                after,
+            })
+            .collect()
+    }
+}
+
+/// Receives the events published by the engine, in the order they happened.
+pub trait EventSubscriber: Send {
+    fn notify(&mut self, event: &AccountEvent);
+}
+
+impl EventSubscriber for broadcast::Sender<AccountEvent> {
+    fn notify(&mut self, event: &AccountEvent) {
+        // having no receivers is not an error, the events are just dropped
+        let _ = self.send(*event);
+    }
+}
+
+impl EventSubscriber for mpsc::Sender<AccountEvent> {
+    fn notify(&mut self, event: &AccountEvent) {
+        let _ = self.send(*event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::account::Account;
+    use crate::tx::engine::event::{AccountEvent, AccountEventKind};
+    use crate::tx::engine::transaction::Transaction;
+
+    #[test]
+    fn test_events_between() {
+        let mut account = Account::new(1);
+        let before = account.summary();
+        account.deposit(1.into(), dec!(10)).unwrap();
+        let deposited = account.summary();
+        account.dispute(1.into()).unwrap();
+        let disputed = account.summary();
+        account.chargeback(1.into()).unwrap();
+        let charged_back = account.summary();
+
+        let kinds = |transaction, before, after| {
+            AccountEvent::between(&transaction, before, after)
+                .into_iter()
+                .map(|event| event.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds(Transaction::new_deposit(1, 1, dec!(10)), before, deposited),
+            vec![AccountEventKind::Credited { amount: dec!(10) }]
+        );
+        assert_eq!(
+            kinds(Transaction::new_dispute(1, 1), deposited, disputed),
+            vec![AccountEventKind::Disputed]
+        );
+        assert_eq!(
+            kinds(Transaction::new_charge_back(1, 1), disputed, charged_back),
+            vec![AccountEventKind::ChargedBack, AccountEventKind::Locked]
+        );
+        assert_eq!(
+            kinds(Transaction::new_dispute(2, 1), deposited, deposited),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_authorization_events() {
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        let deposited = account.summary();
+        account.authorize(2.into(), dec!(4)).unwrap();
+        let authorized = account.summary();
+        account.capture_partially(2.into(), dec!(3)).unwrap();
+        let captured = account.summary();
+
+        let kinds = |transaction, before, after| {
+            AccountEvent::between(&transaction, before, after)
+                .into_iter()
+                .map(|event| event.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds(
+                Transaction::new_authorization(2, 1, dec!(4)),
+                deposited,
+                authorized
+            ),
+            vec![AccountEventKind::Authorized]
+        );
+        assert_eq!(
+            kinds(
+                Transaction::new_partial_capture(2, 1, dec!(3)),
+                authorized,
+                captured
+            ),
+            vec![AccountEventKind::Captured]
+        );
+        assert_eq!(
+            kinds(Transaction::new_release(2, 1), authorized, deposited),
+            vec![AccountEventKind::Released]
+        );
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let mut account = Account::new(1);
+        let before = account.summary();
+        account.deposit(8.into(), dec!(1.5)).unwrap();
+
+        let event = AccountEvent::between(
+            &Transaction::new_deposit(8, 1, dec!(1.5)),
+            before,
+            account.summary(),
+        )[0];
+
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            "{\"type\":\"credited\",\"amount\":\"1.5\",\"tx\":8,\"client\":1,\"before\":{\"client\":1,\"available\":\"0\",\"held\":\"0\",\"authorized\":\"0\",\"total\":\"0\",\"locked\":false,\"status\":\"open\",\"credit_limit\":\"0\",\"fees\":\"0\"},\"after\":{\"client\":1,\"available\":\"1.5\",\"held\":\"0\",\"authorized\":\"0\",\"total\":\"1.5\",\"locked\":false,\"status\":\"open\",\"credit_limit\":\"0\",\"fees\":\"0\"}}"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// The kinds of transactions a fee can be charged for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FeeKind {
+    Withdrawal,
+    /// Charged to the sending client.
+    Transfer,
+    /// Charged to the client whose transaction was charged back.
+    Chargeback,
+}
+
+/// A fee made of a flat part and a percentage of the transaction amount.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Fee {
+    pub flat: Decimal,
+    /// Percentage of the transaction amount, i.e. `1.5` charges 1.5%.
+    pub percentage: Decimal,
+}
+
+impl Fee {
+    /// Calculates the fee for the given transaction amount, rounded to four decimal places
+    /// using banker's rounding.
+    pub fn amount_for(&self, amount: Decimal) -> TxResult<Decimal> {
+        amount
+            .checked_mul(self.percentage)
+            .and_then(|fee| fee.checked_div(dec!(100)))
+            .and_then(|fee| fee.checked_add(self.flat))
+            .map(|fee| fee.round_dp(4))
+            .ok_or_else(|| {
+                TxError::Overflow(format!(
+                    "Attempt to calculate the fee for an amount [{}] beyond the supported range.",
+                    amount
+                ))
+            })
+    }
+}
+
+/// Defines the fees charged per kind of transaction. Fees can be specific to a tier of clients,
+/// clients without tier or without a tier specific fee are charged the default fee of the kind.
+/// Collected fees are credited to the house account, which itself is never charged any fees.
+#[derive(Debug, Clone, Default)]
+pub struct FeeSchedule {
+    house_client_id: ClientId,
+    fees: HashMap<(FeeKind, Option<String>), Fee>,
+    client_tiers: HashMap<ClientId, String>,
+}
+
+impl FeeSchedule {
+    pub fn new(house_client_id: impl Into<ClientId>) -> Self {
+        Self {
+            house_client_id: house_client_id.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn house_client_id(&self) -> ClientId {
+        self.house_client_id
+    }
+
+    /// Sets the fee of a kind for the given tier, or the default fee if `tier` is `None`.
+    pub fn set_fee(&mut self, kind: FeeKind, tier: Option<&str>, fee: Fee) {
+        self.fees.insert((kind, tier.map(str::to_string)), fee);
+    }
+
+    pub fn set_client_tier(&mut self, client_id: impl Into<ClientId>, tier: &str) {
+        self.client_tiers.insert(client_id.into(), tier.to_string());
+    }
+
+    /// Returns the fee a client is charged for a kind of transaction.
+    pub fn fee(&self, client_id: ClientId, kind: FeeKind) -> Fee {
+        if client_id == self.house_client_id {
+            return Fee::default();
+        }
+
+        self.client_tiers
+            .get(&client_id)
+            .and_then(|tier| self.fees.get(&(kind, Some(tier.clone()))))
+            .or_else(|| self.fees.get(&(kind, None)))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::fee_schedule::{Fee, FeeKind, FeeSchedule};
+
+    #[rstest]
+    #[case(dec!(0), dec!(0), dec!(100), dec!(0))]
+    #[case(dec!(1.5), dec!(0), dec!(100), dec!(1.5))]
+    #[case(dec!(0), dec!(2), dec!(100), dec!(2))]
+    #[case(dec!(0.25), dec!(1), dec!(12.345), dec!(0.3734))]
+    #[case(dec!(0), dec!(0.1), dec!(0.00015), dec!(0.0000))]
+    fn test_fee_amount(
+        #[case] flat: Decimal,
+        #[case] percentage: Decimal,
+        #[case] amount: Decimal,
+        #[case] expected_result: Decimal,
+    ) {
+        assert_eq!(
+            Fee { flat, percentage }.amount_for(amount).unwrap(),
+            expected_result
+        );
+    }
+
+    #[test]
+    fn test_fee_amount_overflow() {
+        let fee = Fee {
+            flat: dec!(0),
+            percentage: dec!(200),
+        };
+
+        assert_eq!(
+            format!("{:?}", fee.amount_for(Decimal::MAX).unwrap_err()),
+            format!(
+                "Overflow(\"Attempt to calculate the fee for an amount [{}] beyond the supported range.\")",
+                Decimal::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_fee_lookup() {
+        let default_fee = Fee {
+            flat: dec!(1),
+            percentage: dec!(0),
+        };
+        let gold_fee = Fee {
+            flat: dec!(0.5),
+            percentage: dec!(0),
+        };
+        let mut schedule = FeeSchedule::new(0);
+
+        schedule.set_fee(FeeKind::Withdrawal, None, default_fee);
+        schedule.set_fee(FeeKind::Withdrawal, Some("gold"), gold_fee);
+        schedule.set_client_tier(1, "gold");
+        schedule.set_client_tier(2, "silver");
+
+        assert_eq!(schedule.fee(1.into(), FeeKind::Withdrawal), gold_fee);
+        assert_eq!(schedule.fee(2.into(), FeeKind::Withdrawal), default_fee);
+        assert_eq!(schedule.fee(3.into(), FeeKind::Withdrawal), default_fee);
+        assert_eq!(schedule.fee(0.into(), FeeKind::Withdrawal), Fee::default());
+        assert_eq!(schedule.fee(1.into(), FeeKind::Chargeback), Fee::default());
+    }
+}
@@ -0,0 +1,382 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::field::display;
+use tracing::Value;
+
+/// The id of a client, see [`Id`].
+pub type ClientId = Id;
+
+/// The id of a transaction, see [`Id`].
+pub type TxId = Id;
+
+/// The id of a tenant, e.g. a partner whose feed is processed separately, see [`Id`].
+pub type TenantId = Id;
+
+/// Maximum number of characters of an alphanumeric id.
+pub const MAX_TEXT_ID_LEN: usize = 32;
+
+/// Length of the binary encoding of an id, see [`Id::to_bytes`].
+pub const ID_BYTES_LEN: usize = MAX_TEXT_ID_LEN + 1;
+
+/// Identifies a client or a transaction. Ids are either unsigned 64-bit integers, UUIDs or
+/// alphanumeric references of up to [`MAX_TEXT_ID_LEN`] characters (starting with a letter or
+/// digit), the latter two only if the input is parsed with [`IdKind::Mixed`]. All of them are
+/// stored inline, so ids are cheap to copy. Integers are ordered numerically and before all
+/// UUIDs and references.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Id {
+    Number(u64),
+    Uuid(u128),
+    Text(TextId),
+}
+
+/// The kinds of ids accepted when parsing input, see [`Id::parse_as`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum IdKind {
+    /// Only unsigned integers, leading zeros are ignored, i.e. `007` is `7`.
+    #[default]
+    Numeric,
+    /// Integers, UUIDs and alphanumeric references. Values consisting of digits only are always
+    /// integers, so they fail to parse if they are out of range.
+    Mixed,
+}
+
+/// An alphanumeric id, zero padded to its maximum length.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TextId([u8; MAX_TEXT_ID_LEN]);
+
+impl TextId {
+    pub fn as_str(&self) -> &str {
+        let len = self
+            .0
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(MAX_TEXT_ID_LEN);
+        // only ASCII characters are accepted when parsing
+        std::str::from_utf8(&self.0[..len]).unwrap()
+    }
+}
+
+impl Id {
+    /// Encodes the id as a fixed number of bytes, the encoding of integers preserves their
+    /// order. Used as key of on-disk stores.
+    pub fn to_bytes(&self) -> [u8; ID_BYTES_LEN] {
+        let mut bytes = [0u8; ID_BYTES_LEN];
+
+        match self {
+            Id::Number(number) => bytes[1..9].copy_from_slice(&number.to_be_bytes()),
+            Id::Uuid(uuid) => {
+                bytes[0] = 1;
+                bytes[1..17].copy_from_slice(&uuid.to_be_bytes());
+            }
+            Id::Text(text) => {
+                bytes[0] = 2;
+                bytes[1..].copy_from_slice(&text.0);
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes an id encoded by [`Id::to_bytes`], `None` is returned for invalid encodings.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != ID_BYTES_LEN {
+            return None;
+        }
+
+        match bytes[0] {
+            0 => Some(Id::Number(u64::from_be_bytes(bytes[1..9].try_into().ok()?))),
+            1 => Some(Id::Uuid(u128::from_be_bytes(bytes[1..17].try_into().ok()?))),
+            2 => Some(Id::Text(TextId(bytes[1..].try_into().ok()?))),
+            _ => None,
+        }
+    }
+
+    /// Parses an id of the given kind, surrounding whitespace is ignored.
+    pub fn parse_as(value: &str, kind: IdKind) -> Result<Self, String> {
+        let value = value.trim();
+        let digits = value.strip_prefix('+').unwrap_or(value);
+
+        if kind == IdKind::Numeric
+            || (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return value
+                .parse::<u64>()
+                .map(Id::Number)
+                .map_err(|e| e.to_string());
+        }
+
+        Self::parse_uuid(value)
+            .or_else(|| Self::parse_text(value))
+            .ok_or(format!(
+                "Expected an integer, a UUID or up to [{}] letters, digits, '-' or '_'",
+                MAX_TEXT_ID_LEN
+            ))
+    }
+
+    fn parse_uuid(value: &str) -> Option<Self> {
+        let bytes = value.as_bytes();
+        let is_uuid = bytes.len() == 36
+            && bytes.iter().enumerate().all(|(i, byte)| match i {
+                8 | 13 | 18 | 23 => *byte == b'-',
+                _ => byte.is_ascii_hexdigit(),
+            });
+
+        if !is_uuid {
+            return None;
+        }
+
+        u128::from_str_radix(&value.replace('-', ""), 16)
+            .ok()
+            .map(Id::Uuid)
+    }
+
+    fn parse_text(value: &str) -> Option<Self> {
+        let is_text = value.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && value.len() <= MAX_TEXT_ID_LEN
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+
+        if !is_text {
+            return None;
+        }
+
+        let mut text = [0u8; MAX_TEXT_ID_LEN];
+        text[..value.len()].copy_from_slice(value.as_bytes());
+        Some(Id::Text(TextId(text)))
+    }
+}
+
+impl Id {
+    /// An id reserved for ledger entries the engine records on its own, e.g. posted interest.
+    /// It contains a `:`, so it never collides with an id parsed from the input.
+    pub fn internal(namespace: &str, number: u64) -> Self {
+        let value = format!("{}:{}", namespace, number);
+        assert!(
+            value.len() <= MAX_TEXT_ID_LEN,
+            "internal id [{}] too long",
+            value
+        );
+
+        let mut text = [0u8; MAX_TEXT_ID_LEN];
+        text[..value.len()].copy_from_slice(value.as_bytes());
+        Id::Text(TextId(text))
+    }
+}
+
+impl Default for Id {
+    fn default() -> Self {
+        Id::Number(0)
+    }
+}
+
+impl From<u64> for Id {
+    fn from(number: u64) -> Self {
+        Id::Number(number)
+    }
+}
+
+impl FromStr for Id {
+    type Err = String;
+
+    /// Parses any kind of id, see [`IdKind::Mixed`]. Hyphenated UUIDs are accepted in any case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse_as(value, IdKind::Mixed)
+    }
+}
+
+impl Id {
+    /// The id as the value of a tracing field. Integers are recorded as numbers, so log filters
+    /// like `[execute{client=7}]` match them.
+    pub fn as_field(&self) -> Box<dyn Value> {
+        match self {
+            Id::Number(number) => Box::new(*number),
+            id => Box::new(display(*id)),
+        }
+    }
+}
+
+impl Display for Id {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Id::Number(number) => write!(f, "{}", number),
+            Id::Uuid(uuid) => {
+                let hex = format!("{:032x}", uuid);
+                write!(
+                    f,
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                )
+            }
+            Id::Text(text) => write!(f, "{}", text.as_str()),
+        }
+    }
+}
+
+impl Debug for Id {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Id({})", self)
+    }
+}
+
+impl Debug for TextId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+/// Integers are serialized as numbers by human readable formats, all other ids (and all ids in
+/// binary formats) as their string representation.
+impl Serialize for Id {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Id::Number(number) if serializer.is_human_readable() => {
+                serializer.serialize_u64(*number)
+            }
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdVisitor;
+
+        impl Visitor<'_> for IdVisitor {
+            type Value = Id;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "an integer or a string id")
+            }
+
+            fn visit_u64<E: Error>(self, number: u64) -> Result<Id, E> {
+                Ok(Id::Number(number))
+            }
+
+            fn visit_str<E: Error>(self, value: &str) -> Result<Id, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(IdVisitor)
+        } else {
+            deserializer.deserialize_str(IdVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::tx::engine::id::{Id, IdKind};
+
+    #[rstest]
+    #[case("0", "0")]
+    #[case(" +2 ", "2")]
+    #[case("18446744073709551615", "18446744073709551615")]
+    #[case("007", "7")]
+    #[case("A3F-ref_9", "A3F-ref_9")]
+    #[case(
+        "67E55044-10B1-426F-9247-BB680E5FE0C8",
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    )]
+    fn test_parse_and_display(#[case] given_value: &str, #[case] expected_value: &str) {
+        let id = given_value.parse::<Id>().unwrap();
+
+        assert_eq!(id.to_string(), expected_value);
+        assert_eq!(id.to_string().parse::<Id>().unwrap(), id);
+        assert_eq!(Id::from_bytes(&id.to_bytes()), Some(id));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("-1")]
+    #[case("_a")]
+    #[case("0.5")]
+    #[case("with space")]
+    #[case("a23456789012345678901234567890123")]
+    fn test_parse_failures(#[case] given_value: &str) {
+        assert_eq!(
+            given_value.parse::<Id>().unwrap_err(),
+            "Expected an integer, a UUID or up to [32] letters, digits, '-' or '_'"
+        );
+    }
+
+    #[rstest]
+    #[case(" 007 ", IdKind::Numeric, Ok(Id::Number(7)))]
+    #[case("+2", IdKind::Numeric, Ok(Id::Number(2)))]
+    #[case("1O", IdKind::Numeric, Err("invalid digit found in string"))]
+    #[case(
+        "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        IdKind::Numeric,
+        Err("invalid digit found in string")
+    )]
+    #[case("", IdKind::Numeric, Err("cannot parse integer from empty string"))]
+    #[case(
+        "18446744073709551616",
+        IdKind::Numeric,
+        Err("number too large to fit in target type")
+    )]
+    #[case("007", IdKind::Mixed, Ok(Id::Number(7)))]
+    #[case(
+        "18446744073709551616",
+        IdKind::Mixed,
+        Err("number too large to fit in target type")
+    )]
+    fn test_parse_as(
+        #[case] given_value: &str,
+        #[case] kind: IdKind,
+        #[case] expected: Result<Id, &str>,
+    ) {
+        assert_eq!(
+            Id::parse_as(given_value, kind),
+            expected.map_err(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn test_internal_ids_are_never_parsed() {
+        let id = Id::internal("interest", u64::MAX);
+
+        assert_eq!(id.to_string(), "interest:18446744073709551615");
+        assert!(id.to_string().parse::<Id>().is_err());
+        assert_eq!(Id::from_bytes(&id.to_bytes()), Some(id));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut ids = ["b", "10", "9", "a", "00000000-0000-0000-0000-000000000001"]
+            .map(|value| value.parse::<Id>().unwrap());
+        ids.sort();
+
+        assert_eq!(
+            ids.map(|id| id.to_string()),
+            ["9", "10", "00000000-0000-0000-0000-000000000001", "a", "b"]
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        let ids = ["12", "ref-1"].map(|value| value.parse::<Id>().unwrap());
+
+        assert_eq!(serde_json::to_string(&ids).unwrap(), "[12,\"ref-1\"]");
+        assert_eq!(
+            serde_json::from_str::<[Id; 2]>("[12,\"ref-1\"]").unwrap(),
+            ids
+        );
+        assert_eq!(
+            bincode::deserialize::<[Id; 2]>(&bincode::serialize(&ids).unwrap()).unwrap(),
+            ids
+        );
+    }
+}
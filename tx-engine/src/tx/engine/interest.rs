@@ -0,0 +1,168 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// An annual interest rate that applies to balances of at least `minimum_balance`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InterestRate {
+    pub minimum_balance: Decimal,
+    /// Annual rate in percent, i.e. `2.5` pays 2.5% per year.
+    pub annual_percentage: Decimal,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InterestConfig {
+    /// The rate table, the rate with the highest minimum balance not above the balance of an
+    /// account applies to its whole balance. Balances below all minimums earn no interest.
+    pub rates: Vec<InterestRate>,
+    /// Whether held funds earn interest as well, by default only available funds do.
+    pub include_held: bool,
+    /// Number of days per year used to derive the daily rate from the annual rate.
+    pub days_per_year: u32,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self {
+            rates: Vec::new(),
+            include_held: false,
+            days_per_year: 365,
+        }
+    }
+}
+
+impl InterestConfig {
+    /// Returns the annual rate in percent applying to the balance, if any.
+    pub fn rate_for(&self, balance: Decimal) -> Option<Decimal> {
+        self.rates
+            .iter()
+            .filter(|rate| rate.minimum_balance <= balance)
+            .max_by_key(|rate| rate.minimum_balance)
+            .map(|rate| rate.annual_percentage)
+    }
+
+    /// Calculates the interest of a single day for the balance of an account. Interest is
+    /// rounded to four decimal places using banker's rounding, `None` is returned if the
+    /// balance earns no interest. Fails if the interest exceeds the supported range.
+    pub fn accrue(
+        &self,
+        tx_id: TxId,
+        client_id: ClientId,
+        balance: Decimal,
+    ) -> TxResult<Option<InterestAccrual>> {
+        if balance <= dec!(0) || self.days_per_year == 0 {
+            return Ok(None);
+        }
+
+        let Some(annual_percentage) = self.rate_for(balance) else {
+            return Ok(None);
+        };
+        let interest = balance
+            .checked_mul(annual_percentage)
+            .ok_or_else(|| {
+                TxError::Overflow(format!(
+                    "Attempt to accrue interest in transaction [{}] for a balance [{}] of account [{}] beyond the supported range.",
+                    tx_id, balance, client_id
+                ))
+            })?
+            / dec!(100)
+            / Decimal::from(self.days_per_year);
+        let interest = interest
+            .round_dp_with_strategy(4, RoundingStrategy::MidpointNearestEven)
+            .normalize();
+
+        if interest <= dec!(0) {
+            return Ok(None);
+        }
+
+        Ok(Some(InterestAccrual {
+            tx_id,
+            client_id,
+            balance,
+            annual_percentage,
+            interest,
+        }))
+    }
+}
+
+/// The interest posted to an account at the end of a business day.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InterestAccrual {
+    /// Id of the accrual row, the ledger entry of the interest has an internal id.
+    pub tx_id: TxId,
+    pub client_id: ClientId,
+    /// The balance interest was calculated for.
+    pub balance: Decimal,
+    pub annual_percentage: Decimal,
+    pub interest: Decimal,
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::interest::{InterestConfig, InterestRate};
+
+    fn config() -> InterestConfig {
+        InterestConfig {
+            rates: vec![
+                InterestRate {
+                    minimum_balance: dec!(0),
+                    annual_percentage: dec!(1),
+                },
+                InterestRate {
+                    minimum_balance: dec!(10000),
+                    annual_percentage: dec!(3.65),
+                },
+            ],
+            ..InterestConfig::default()
+        }
+    }
+
+    #[rstest]
+    #[case(dec!(-100), None)]
+    #[case(dec!(0), None)]
+    #[case(dec!(0.01), None)]
+    #[case(dec!(365), Some(dec!(0.01)))]
+    #[case(dec!(1000), Some(dec!(0.0274)))]
+    #[case(dec!(10000), Some(dec!(1)))]
+    // daily interest of 0.00005 and 0.00015 is rounded to the nearest even digit
+    #[case(dec!(1.825), None)]
+    #[case(dec!(5.475), Some(dec!(0.0002)))]
+    fn test_accrue(#[case] balance: Decimal, #[case] expected_interest: Option<Decimal>) {
+        assert_eq!(
+            config()
+                .accrue(1.into(), 2.into(), balance)
+                .unwrap()
+                .map(|accrual| accrual.interest),
+            expected_interest
+        );
+    }
+
+    #[test]
+    fn test_accrue_overflow() {
+        assert_eq!(
+            format!(
+                "{:?}",
+                config()
+                    .accrue(1.into(), 2.into(), Decimal::MAX)
+                    .unwrap_err()
+            ),
+            format!(
+                "Overflow(\"Attempt to accrue interest in transaction [1] for a balance [{}] of account [2] beyond the supported range.\")",
+                Decimal::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_rate_lookup() {
+        assert_eq!(config().rate_for(dec!(-1)), None);
+        assert_eq!(config().rate_for(dec!(9999.99)), Some(dec!(1)));
+        assert_eq!(config().rate_for(dec!(20000)), Some(dec!(3.65)));
+    }
+}
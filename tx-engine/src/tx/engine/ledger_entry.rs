@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::tx::engine::dispute_window::LedgerPosition;
+use crate::tx::engine::id::ClientId;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum LedgerEntryState {
+    Normal,
+    Disputed,
+    ChargedBack,
+    /// A part of the amount was charged back, the rest can still be disputed.
+    PartiallyChargedBack,
+    /// The chargeback was reversed after the merchant contested it, the funds were restored.
+    Represented,
+}
+
+impl Display for LedgerEntryState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerEntryState::Normal => write!(f, "normal"),
+            LedgerEntryState::Disputed => write!(f, "disputed"),
+            LedgerEntryState::ChargedBack => write!(f, "charged_back"),
+            LedgerEntryState::PartiallyChargedBack => write!(f, "partially_charged_back"),
+            LedgerEntryState::Represented => write!(f, "represented"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LedgerEntryKind {
+    /// A deposit, withdrawal or a leg of a transfer.
+    #[default]
+    Transaction,
+    /// Interest posted by an accrual, can not be disputed.
+    Interest,
+    /// Funds held by a card authorization until it is captured, after which the entry is a
+    /// regular withdrawal. Can not be disputed.
+    Authorization,
+    /// An authorization whose funds were released without being captured, can not be disputed.
+    ReleasedAuthorization,
+    /// The remaining funds paid out when an account is closed, can not be disputed.
+    Payout,
+}
+
+/// A deposit or withdrawal as recorded in the ledger of an account, kept so it can be disputed
+/// later on. Withdrawals and authorizations are recorded with a negative amount.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub kind: LedgerEntryKind,
+    pub amount: Decimal,
+    pub state: LedgerEntryState,
+    pub recorded_at: LedgerPosition,
+    /// The part of the amount that is currently disputed, always positive.
+    pub disputed: Decimal,
+    /// The part of the amount that was charged back so far, always positive.
+    pub charged_back: Decimal,
+    /// The other account involved if this entry is one leg of a transfer.
+    pub counterparty: Option<ClientId>,
+    /// The fee charged for the transaction itself, e.g. a withdrawal fee. Refunded once the
+    /// entry is charged back completely.
+    pub fee: Decimal,
+    /// The fees charged for chargebacks of this entry so far, never refunded.
+    pub chargeback_fee: Decimal,
+    /// How often the entry was disputed so far.
+    pub dispute_cycles: u32,
+}
+
+impl LedgerEntry {
+    pub fn new(amount: Decimal, recorded_at: LedgerPosition) -> Self {
+        Self {
+            kind: LedgerEntryKind::Transaction,
+            amount,
+            state: LedgerEntryState::Normal,
+            recorded_at,
+            disputed: dec!(0),
+            charged_back: dec!(0),
+            counterparty: None,
+            fee: dec!(0),
+            chargeback_fee: dec!(0),
+            dispute_cycles: 0,
+        }
+    }
+
+    /// The part of the amount that was not charged back yet and can therefore be disputed.
+    pub fn disputable_amount(&self) -> Decimal {
+        self.amount.abs() - self.charged_back
+    }
+
+    /// The state the entry settles in once a dispute of it is over, depending on how much of it
+    /// was charged back.
+    pub fn settled_state(&self) -> LedgerEntryState {
+        if self.disputable_amount() == dec!(0) {
+            LedgerEntryState::ChargedBack
+        } else if self.charged_back > dec!(0) {
+            LedgerEntryState::PartiallyChargedBack
+        } else {
+            LedgerEntryState::Normal
+        }
+    }
+
+    /// Applies the direction of this entry to a positive amount, i.e. negates it for withdrawals.
+    pub fn directed(&self, amount: Decimal) -> Decimal {
+        if self.amount.is_sign_negative() {
+            -amount
+        } else {
+            amount
+        }
+    }
+}
@@ -1,4 +1,19 @@
 pub mod account;
+pub mod account_query;
+pub mod amount_policy;
+pub mod batch;
+pub mod business_date;
+pub mod config;
+pub mod dispute_window;
+#[allow(clippy::module_inception)]
 pub mod engine;
+pub mod event;
+pub mod fee_schedule;
+pub mod id;
+pub mod interest;
+pub mod ledger_entry;
+pub mod multi_tenant_engine;
 pub mod result;
+pub mod simulation;
 pub mod transaction;
+pub mod transition;
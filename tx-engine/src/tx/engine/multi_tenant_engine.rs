@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, HashMap};
+
+use tracing::debug_span;
+
+use crate::tx::engine::account::AccountSummary;
+use crate::tx::engine::account_query::AccountQuery;
+use crate::tx::engine::config::{EngineConfig, LedgerBackend};
+use crate::tx::engine::engine::TransactionEngine;
+use crate::tx::engine::id::TenantId;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::transaction::Transaction;
+use crate::tx::metrics::metrics::EngineGauges;
+
+/// An account of a tenant, see [`MultiTenantEngine::account_summary`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TenantAccountSummary {
+    pub tenant_id: TenantId,
+    pub account: AccountSummary,
+}
+
+/// Executes the transactions of several tenants whose client and transaction ids may overlap.
+/// Each tenant has its own [`TransactionEngine`], so accounts, ledgers, rules and policies are
+/// never shared. The engine of a tenant is created on first use, with the config set for the
+/// tenant or the default config otherwise.
+pub struct MultiTenantEngine {
+    default_config: EngineConfig,
+    configs: HashMap<TenantId, EngineConfig>,
+    engines: BTreeMap<TenantId, TransactionEngine>,
+}
+
+impl Default for MultiTenantEngine {
+    fn default() -> Self {
+        Self::new(EngineConfig::default())
+    }
+}
+
+impl MultiTenantEngine {
+    pub fn new(default_config: EngineConfig) -> Self {
+        Self {
+            default_config,
+            configs: HashMap::new(),
+            engines: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the config of a tenant, which has to happen before its engine is created.
+    pub fn set_config(&mut self, tenant_id: TenantId, config: EngineConfig) -> TxResult<()> {
+        if self.engines.contains_key(&tenant_id) {
+            return Err(TxError::InvalidOperation(format!(
+                "Attempt to configure tenant [{}] after its engine was created.",
+                tenant_id
+            )));
+        }
+
+        self.configs.insert(tenant_id, config);
+
+        Ok(())
+    }
+
+    /// Returns the engine of the tenant, it is created if the tenant has none yet. Use it to
+    /// set up rules, credit limits or subscribers of a tenant.
+    pub fn engine_mut(&mut self, tenant_id: TenantId) -> &mut TransactionEngine {
+        self.engines.entry(tenant_id).or_insert_with(|| {
+            let mut config = self
+                .configs
+                .get(&tenant_id)
+                .unwrap_or(&self.default_config)
+                .clone();
+            // tenants sharing an on-disk ledger get their own namespace within it
+            if let LedgerBackend::Disk(ledger) = &config.ledger {
+                config.ledger = LedgerBackend::Disk(ledger.for_tenant(tenant_id));
+            }
+            TransactionEngine::with_config(config)
+        })
+    }
+
+    /// Returns the engine of the tenant, `None` if it has none yet.
+    pub fn engine(&self, tenant_id: TenantId) -> Option<&TransactionEngine> {
+        self.engines.get(&tenant_id)
+    }
+
+    /// Executes a transaction with the engine of the tenant, see [`TransactionEngine::execute`].
+    pub fn execute(&mut self, tenant_id: TenantId, transaction: Transaction) -> TxResult<()> {
+        let span = debug_span!("tenant", tenant = tenant_id.as_field());
+        let _entered = span.enter();
+
+        self.engine_mut(tenant_id).execute(transaction)
+    }
+
+    /// All tenants that have an engine, ordered by id.
+    pub fn tenants(&self) -> impl Iterator<Item = TenantId> + '_ {
+        self.engines.keys().copied()
+    }
+
+    /// The accounts of all tenants, ordered by tenant and client.
+    pub fn account_summary(&self) -> Vec<TenantAccountSummary> {
+        self.query(&AccountQuery::new()).collect()
+    }
+
+    /// The accounts of all tenants matching the query, ordered by tenant and client. The page
+    /// spans all tenants, accounts are summarized one at a time while iterating.
+    pub fn query<'a>(
+        &'a self,
+        query: &'a AccountQuery,
+    ) -> impl Iterator<Item = TenantAccountSummary> + 'a {
+        let matches = self
+            .engines
+            .iter()
+            .flat_map(|(tenant_id, engine)| {
+                engine.accounts().map(|account| TenantAccountSummary {
+                    tenant_id: *tenant_id,
+                    account,
+                })
+            })
+            .filter(|summary| query.matches(&summary.account));
+
+        query.paginate(matches)
+    }
+
+    /// The gauges of all tenants added up, see [`TransactionEngine::gauges`].
+    pub fn gauges(&self) -> EngineGauges {
+        self.engines.values().map(TransactionEngine::gauges).fold(
+            EngineGauges::default(),
+            |total, gauges| EngineGauges {
+                accounts: total.accounts + gauges.accounts,
+                locked_accounts: total.locked_accounts + gauges.locked_accounts,
+                open_disputes: total.open_disputes + gauges.open_disputes,
+                ledger_entries: total.ledger_entries + gauges.ledger_entries,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::account_query::{AccountFilter, AccountQuery};
+    use crate::tx::engine::config::{EngineConfig, LedgerBackend, TxIdScope};
+    use crate::tx::engine::id::TenantId;
+    use crate::tx::engine::multi_tenant_engine::MultiTenantEngine;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::ledger::disk_ledger_store::DiskLedger;
+
+    fn tenant(id: &str) -> TenantId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_tenants_are_isolated() {
+        let mut engine = MultiTenantEngine::default();
+
+        engine
+            .execute(tenant("acme"), Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_deposit(1, 1, dec!(3)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_dispute(1, 1))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_charge_back(1, 1))
+            .unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].tenant_id, tenant("acme"));
+        assert_eq!(accounts[0].account.available, dec!(10));
+        assert!(!accounts[0].account.is_locked);
+        assert_eq!(accounts[1].tenant_id, tenant("globex"));
+        assert_eq!(accounts[1].account.total, dec!(0));
+        assert!(accounts[1].account.is_locked);
+        assert_eq!(engine.gauges().accounts, 2);
+        assert_eq!(engine.gauges().locked_accounts, 1);
+
+        let locked = engine
+            .query(&AccountQuery::new().with_filter(AccountFilter::Locked))
+            .collect::<Vec<_>>();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].tenant_id, tenant("globex"));
+        let page = engine
+            .query(&AccountQuery::new().with_page(1, 1))
+            .collect::<Vec<_>>();
+        assert_eq!(page, vec![accounts[1]]);
+    }
+
+    #[test]
+    fn test_tenants_have_their_own_config() {
+        let mut engine = MultiTenantEngine::default();
+        engine
+            .set_config(
+                tenant("acme"),
+                EngineConfig {
+                    tx_id_scope: TxIdScope::Global,
+                    ..EngineConfig::default()
+                },
+            )
+            .unwrap();
+
+        engine
+            .execute(tenant("acme"), Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_deposit(1, 2, dec!(10)))
+            .unwrap();
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .execute(tenant("acme"), Transaction::new_deposit(1, 2, dec!(10)))
+                    .unwrap_err()
+            ),
+            "DuplicateTransaction(\"Attempt to execute a transaction [1] for account [2], but its id was already used.\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                engine
+                    .set_config(tenant("acme"), EngineConfig::default())
+                    .unwrap_err()
+            ),
+            "InvalidOperation(\"Attempt to configure tenant [acme] after its engine was created.\")"
+        );
+    }
+
+    #[test]
+    fn test_tenants_share_disk_ledger_without_collisions() {
+        let directory = tempfile::tempdir().unwrap();
+        let mut engine = MultiTenantEngine::new(EngineConfig {
+            tx_id_scope: TxIdScope::Global,
+            ledger: LedgerBackend::Disk(DiskLedger::create(directory.path(), 16).unwrap()),
+            ..EngineConfig::default()
+        });
+
+        engine
+            .execute(tenant("acme"), Transaction::new_deposit(1, 1, dec!(10)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_deposit(1, 1, dec!(4)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_withdrawal(2, 1, dec!(1)))
+            .unwrap();
+        engine
+            .execute(tenant("globex"), Transaction::new_dispute(1, 1))
+            .unwrap();
+
+        let accounts = engine.account_summary();
+        assert_eq!(accounts[0].account.available, dec!(10));
+        assert_eq!(accounts[1].account.available, dec!(-1));
+        assert_eq!(accounts[1].account.held, dec!(4));
+    }
+}
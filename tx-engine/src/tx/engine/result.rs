@@ -4,5 +4,29 @@ pub type TxResult<T> = Result<T, TxError>;
 pub enum TxError {
     InvalidArgument(String),
     InvalidOperation(String),
+    DuplicateTransaction(String),
+    DisputeWindowExpired(String),
+    InvalidTransition(String),
+    /// A balance or an amount derived from it exceeded the range of supported values.
+    Overflow(String),
+    RuleViolation(String),
+    BatchRolledBack(String),
     IoError(String),
 }
+
+impl TxError {
+    /// A short name of the kind of error, e.g. to label metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TxError::InvalidArgument(_) => "invalid_argument",
+            TxError::InvalidOperation(_) => "invalid_operation",
+            TxError::DuplicateTransaction(_) => "duplicate_transaction",
+            TxError::DisputeWindowExpired(_) => "dispute_window_expired",
+            TxError::InvalidTransition(_) => "invalid_transition",
+            TxError::Overflow(_) => "overflow",
+            TxError::RuleViolation(_) => "rule_violation",
+            TxError::BatchRolledBack(_) => "batch_rolled_back",
+            TxError::IoError(_) => "io_error",
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use rust_decimal::Decimal;
+
+use crate::tx::engine::account::AccountSummary;
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::result::TxError;
+
+/// The effect transactions would have on the accounts, see [`TransactionEngine::simulate`].
+///
+/// [`TransactionEngine::simulate`]: crate::tx::engine::engine::TransactionEngine::simulate
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// All accounts after the simulated transactions, ordered by client id.
+    pub accounts: Vec<AccountChange>,
+    /// The simulated transactions that failed, in the order they were executed.
+    pub rejections: Vec<Rejection>,
+}
+
+/// The state of an account before and after the simulated transactions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AccountChange {
+    /// `None` if the account would be created by the simulated transactions.
+    pub before: Option<AccountSummary>,
+    pub after: AccountSummary,
+}
+
+impl AccountChange {
+    pub fn available_delta(&self) -> Decimal {
+        self.after.available - self.before.map_or(Decimal::ZERO, |before| before.available)
+    }
+
+    pub fn held_delta(&self) -> Decimal {
+        self.after.held - self.before.map_or(Decimal::ZERO, |before| before.held)
+    }
+
+    pub fn total_delta(&self) -> Decimal {
+        self.after.total - self.before.map_or(Decimal::ZERO, |before| before.total)
+    }
+}
+
+/// A simulated transaction that failed.
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// Position of the transaction within the simulated transactions, starting at zero.
+    pub index: usize,
+    pub tx_id: TxId,
+    pub client_id: ClientId,
+    pub error: TxError,
+}
@@ -1,70 +1,323 @@
 use rust_decimal::Decimal;
 
+use crate::tx::engine::business_date::BusinessDate;
+use crate::tx::engine::id::{ClientId, TxId};
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum TransactionKind {
     Withdrawal(Decimal),
     Deposit(Decimal),
-    Dispute,
+    /// Moves the amount from the account of the transaction's client to another account.
+    Transfer {
+        to_client: ClientId,
+        amount: Decimal,
+    },
+    /// Disputes the given amount of a deposit or withdrawal, or all of it if `None`.
+    Dispute(Option<Decimal>),
     Resolve,
-    Chargeback,
+    /// Charges back the given amount of a dispute, or all of it if `None`.
+    Chargeback(Option<Decimal>),
+    /// Reverses the chargeback of a deposit or withdrawal after the merchant contested it, the
+    /// charged back funds are restored.
+    Representment,
+    /// Holds the amount of the available funds for a card payment until it is captured or
+    /// released.
+    Authorize(Decimal),
+    /// Withdraws the given amount of an authorization, or all of it if `None`. The rest of the
+    /// authorization is released.
+    Capture(Option<Decimal>),
+    /// Returns the funds of an authorization to the available funds.
+    Release,
+    /// Opens the account of the client, or reopens it if it is dormant.
+    Open,
+    /// Closes the account of the client once all disputes and authorizations are settled, the
+    /// remaining funds are paid out with the id of the transaction.
+    Close,
+    /// Cancels a pending transaction of the client with the same id before its value date is
+    /// reached.
+    Cancel,
+    /// Moves the business date of the engine forward, pending transactions whose value date is
+    /// reached are executed. Not tied to a single client.
+    AdvanceBusinessDate(BusinessDate),
+    /// Marks the end of a business day, interest is accrued for all accounts and posted with the
+    /// id of the transaction. Not tied to a single client.
+    AccrueInterest,
+}
+
+impl TransactionKind {
+    /// The name of the kind, as used for the type column of CSV input.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransactionKind::Withdrawal(_) => "withdrawal",
+            TransactionKind::Deposit(_) => "deposit",
+            TransactionKind::Transfer { .. } => "transfer",
+            TransactionKind::Dispute(_) => "dispute",
+            TransactionKind::Resolve => "resolve",
+            TransactionKind::Chargeback(_) => "chargeback",
+            TransactionKind::Representment => "representment",
+            TransactionKind::Authorize(_) => "authorize",
+            TransactionKind::Capture(_) => "capture",
+            TransactionKind::Release => "release",
+            TransactionKind::Open => "open",
+            TransactionKind::Close => "close",
+            TransactionKind::Cancel => "cancel",
+            TransactionKind::AdvanceBusinessDate(_) => "business_date",
+            TransactionKind::AccrueInterest => "accrual",
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Transaction {
     kind: TransactionKind,
-    client_id: u16,
-    tx_id: u32,
+    client_id: ClientId,
+    tx_id: TxId,
+    timestamp: Option<u64>,
+    value_date: Option<BusinessDate>,
 }
 
 impl Transaction {
-    pub fn new_charge_back(tx_id: u32, client_id: u16) -> Self {
+    pub fn new_charge_back(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Chargeback(None),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_partial_charge_back(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
+        Transaction {
+            kind: TransactionKind::Chargeback(Some(amount)),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_dispute(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Dispute(None),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_partial_dispute(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
         Transaction {
-            kind: TransactionKind::Chargeback,
-            client_id,
-            tx_id,
+            kind: TransactionKind::Dispute(Some(amount)),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
         }
     }
 
-    pub fn new_dispute(tx_id: u32, client_id: u16) -> Self {
+    pub fn new_representment(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
         Transaction {
-            kind: TransactionKind::Dispute,
-            client_id,
-            tx_id,
+            kind: TransactionKind::Representment,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
         }
     }
 
-    pub fn new_resolve(tx_id: u32, client_id: u16) -> Self {
+    pub fn new_resolve(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
         Transaction {
             kind: TransactionKind::Resolve,
-            client_id,
-            tx_id,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
         }
     }
 
-    pub fn new_deposit(tx_id: u32, client_id: u16, amount: Decimal) -> Self {
+    pub fn new_deposit(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
         Transaction {
             kind: TransactionKind::Deposit(amount),
-            client_id,
-            tx_id,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
         }
     }
 
-    pub fn new_withdrawal(tx_id: u32, client_id: u16, amount: Decimal) -> Self {
+    pub fn new_withdrawal(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
         Transaction {
             kind: TransactionKind::Withdrawal(amount),
-            client_id,
-            tx_id,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_transfer(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        to_client: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
+        Transaction {
+            kind: TransactionKind::Transfer {
+                to_client: to_client.into(),
+                amount,
+            },
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_authorization(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
+        Transaction {
+            kind: TransactionKind::Authorize(amount),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_capture(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Capture(None),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_partial_capture(
+        tx_id: impl Into<TxId>,
+        client_id: impl Into<ClientId>,
+        amount: Decimal,
+    ) -> Self {
+        Transaction {
+            kind: TransactionKind::Capture(Some(amount)),
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
         }
     }
 
+    pub fn new_release(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Release,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_opening(client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Open,
+            client_id: client_id.into(),
+            tx_id: TxId::default(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_closing(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Close,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_cancellation(tx_id: impl Into<TxId>, client_id: impl Into<ClientId>) -> Self {
+        Transaction {
+            kind: TransactionKind::Cancel,
+            client_id: client_id.into(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_business_date(date: BusinessDate) -> Self {
+        Transaction {
+            kind: TransactionKind::AdvanceBusinessDate(date),
+            client_id: ClientId::default(),
+            tx_id: TxId::default(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    pub fn new_interest_accrual(tx_id: impl Into<TxId>) -> Self {
+        Transaction {
+            kind: TransactionKind::AccrueInterest,
+            client_id: ClientId::default(),
+            tx_id: tx_id.into(),
+            timestamp: None,
+            value_date: None,
+        }
+    }
+
+    /// Attaches the time the transaction happened at, in seconds since the unix epoch.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Attaches the business date the transaction takes effect on, it is kept pending until the
+    /// business date of the engine reaches it.
+    pub fn with_value_date(mut self, value_date: BusinessDate) -> Self {
+        self.value_date = Some(value_date);
+        self
+    }
+
     pub fn kind(&self) -> TransactionKind {
         self.kind
     }
 
-    pub fn client_id(&self) -> u16 {
+    pub fn client_id(&self) -> ClientId {
         self.client_id
     }
-    pub fn tx_id(&self) -> u32 {
+    pub fn tx_id(&self) -> TxId {
         self.tx_id
     }
+
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    pub fn value_date(&self) -> Option<BusinessDate> {
+        self.value_date
+    }
 }
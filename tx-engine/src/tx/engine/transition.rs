@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::ledger_entry::LedgerEntryState;
+use crate::tx::engine::result::TxError;
+
+/// A step of the dispute lifecycle of a ledger entry.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum DisputeEvent {
+    Dispute,
+    Resolve,
+    Chargeback,
+    Representment,
+}
+
+impl DisputeEvent {
+    /// The transition table of ledger entries, returns the state an entry in the given state
+    /// moves to, or `None` if the step does not apply to it. An entry that was charged back
+    /// only partially moves to [`LedgerEntryState::PartiallyChargedBack`] instead, see
+    /// [`LedgerEntry::settled_state`].
+    ///
+    /// [`LedgerEntry::settled_state`]: crate::tx::engine::ledger_entry::LedgerEntry::settled_state
+    pub fn target(self, from: LedgerEntryState) -> Option<LedgerEntryState> {
+        match (from, self) {
+            (
+                LedgerEntryState::Normal | LedgerEntryState::PartiallyChargedBack,
+                DisputeEvent::Dispute,
+            ) => Some(LedgerEntryState::Disputed),
+            (LedgerEntryState::Disputed, DisputeEvent::Resolve) => Some(LedgerEntryState::Normal),
+            (LedgerEntryState::Disputed, DisputeEvent::Chargeback) => {
+                Some(LedgerEntryState::ChargedBack)
+            }
+            (
+                LedgerEntryState::ChargedBack | LedgerEntryState::PartiallyChargedBack,
+                DisputeEvent::Representment,
+            ) => Some(LedgerEntryState::Represented),
+            _ => None,
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            DisputeEvent::Dispute => "dispute",
+            DisputeEvent::Resolve => "resolve",
+            DisputeEvent::Chargeback => "charge back",
+            DisputeEvent::Representment => "represent",
+        }
+    }
+}
+
+impl Display for DisputeEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeEvent::Dispute => write!(f, "dispute"),
+            DisputeEvent::Resolve => write!(f, "resolve"),
+            DisputeEvent::Chargeback => write!(f, "chargeback"),
+            DisputeEvent::Representment => write!(f, "representment"),
+        }
+    }
+}
+
+/// What a dispute step did to the ledger entry it refers to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum TransitionOutcome {
+    Applied,
+    /// The account has no ledger entry with the id of the step.
+    IgnoredUnknownTx,
+    /// The transition table does not allow the step for the state of the entry.
+    IgnoredInvalidState {
+        from: LedgerEntryState,
+        event: DisputeEvent,
+    },
+}
+
+impl TransitionOutcome {
+    /// Looks up the outcome of a dispute step for an entry in the given state, `None` if the
+    /// entry is unknown.
+    pub fn of(state: Option<LedgerEntryState>, event: DisputeEvent) -> Self {
+        match state {
+            None => TransitionOutcome::IgnoredUnknownTx,
+            Some(from) if event.target(from).is_none() => {
+                TransitionOutcome::IgnoredInvalidState { from, event }
+            }
+            Some(_) => TransitionOutcome::Applied,
+        }
+    }
+
+    /// The state the entry was in if the step was ignored because of it.
+    pub fn from_state(&self) -> Option<LedgerEntryState> {
+        match self {
+            TransitionOutcome::IgnoredInvalidState { from, .. } => Some(*from),
+            _ => None,
+        }
+    }
+}
+
+impl Display for TransitionOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransitionOutcome::Applied => write!(f, "applied"),
+            TransitionOutcome::IgnoredUnknownTx => write!(f, "ignored_unknown_tx"),
+            TransitionOutcome::IgnoredInvalidState { .. } => write!(f, "ignored_invalid_state"),
+        }
+    }
+}
+
+/// Defines how dispute steps that don't apply to their ledger entry are handled.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum TransitionStrictness {
+    /// The step is ignored and recorded, see [`TransactionEngine::ignored_transitions`].
+    ///
+    /// [`TransactionEngine::ignored_transitions`]: crate::tx::engine::engine::TransactionEngine::ignored_transitions
+    #[default]
+    Lenient,
+    /// The step fails with [`TxError::InvalidTransition`].
+    Strict,
+}
+
+/// A dispute step that was not applied to its ledger entry.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IgnoredTransition {
+    /// Number of transactions executed by the engine before this one.
+    pub sequence: u64,
+    pub tx_id: TxId,
+    pub client_id: ClientId,
+    pub event: DisputeEvent,
+    pub outcome: TransitionOutcome,
+}
+
+impl IgnoredTransition {
+    /// The error the step fails with if transitions are strict.
+    pub fn error(&self) -> TxError {
+        match self.outcome.from_state() {
+            Some(from) => TxError::InvalidTransition(format!(
+                "Attempt to {} transaction [{}] for account [{}] in state [{}].",
+                self.event.verb(),
+                self.tx_id,
+                self.client_id,
+                from
+            )),
+            None => TxError::InvalidTransition(format!(
+                "Attempt to {} unknown transaction [{}] for account [{}].",
+                self.event.verb(),
+                self.tx_id,
+                self.client_id
+            )),
+        }
+    }
+}
+
+/// How often each dispute step had each outcome.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TransitionStats {
+    counts: BTreeMap<(DisputeEvent, TransitionOutcome), u64>,
+}
+
+/// A row of the transition stats, see [`TransitionStats::counts`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TransitionCount {
+    pub event: DisputeEvent,
+    pub outcome: TransitionOutcome,
+    pub count: u64,
+}
+
+impl TransitionStats {
+    pub fn record(&mut self, event: DisputeEvent, outcome: TransitionOutcome) {
+        *self.counts.entry((event, outcome)).or_default() += 1;
+    }
+
+    pub fn count(&self, event: DisputeEvent, outcome: TransitionOutcome) -> u64 {
+        self.counts
+            .get(&(event, outcome))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// All outcomes that happened at least once, ordered by event and outcome.
+    pub fn counts(&self) -> Vec<TransitionCount> {
+        self.counts
+            .iter()
+            .map(|((event, outcome), count)| TransitionCount {
+                event: *event,
+                outcome: *outcome,
+                count: *count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::tx::engine::ledger_entry::LedgerEntryState;
+    use crate::tx::engine::transition::{DisputeEvent, IgnoredTransition, TransitionOutcome};
+
+    #[rstest]
+    #[case(
+        LedgerEntryState::Normal,
+        DisputeEvent::Dispute,
+        Some(LedgerEntryState::Disputed)
+    )]
+    #[case(LedgerEntryState::Normal, DisputeEvent::Resolve, None)]
+    #[case(LedgerEntryState::Normal, DisputeEvent::Chargeback, None)]
+    #[case(LedgerEntryState::Disputed, DisputeEvent::Dispute, None)]
+    #[case(
+        LedgerEntryState::Disputed,
+        DisputeEvent::Resolve,
+        Some(LedgerEntryState::Normal)
+    )]
+    #[case(
+        LedgerEntryState::Disputed,
+        DisputeEvent::Chargeback,
+        Some(LedgerEntryState::ChargedBack)
+    )]
+    #[case(LedgerEntryState::ChargedBack, DisputeEvent::Dispute, None)]
+    #[case(LedgerEntryState::ChargedBack, DisputeEvent::Resolve, None)]
+    #[case(LedgerEntryState::ChargedBack, DisputeEvent::Chargeback, None)]
+    #[case(
+        LedgerEntryState::ChargedBack,
+        DisputeEvent::Representment,
+        Some(LedgerEntryState::Represented)
+    )]
+    #[case(
+        LedgerEntryState::PartiallyChargedBack,
+        DisputeEvent::Dispute,
+        Some(LedgerEntryState::Disputed)
+    )]
+    #[case(LedgerEntryState::PartiallyChargedBack, DisputeEvent::Resolve, None)]
+    #[case(LedgerEntryState::PartiallyChargedBack, DisputeEvent::Chargeback, None)]
+    #[case(
+        LedgerEntryState::PartiallyChargedBack,
+        DisputeEvent::Representment,
+        Some(LedgerEntryState::Represented)
+    )]
+    #[case(LedgerEntryState::Normal, DisputeEvent::Representment, None)]
+    #[case(LedgerEntryState::Disputed, DisputeEvent::Representment, None)]
+    #[case(LedgerEntryState::Represented, DisputeEvent::Dispute, None)]
+    #[case(LedgerEntryState::Represented, DisputeEvent::Representment, None)]
+    fn test_transition_table(
+        #[case] from: LedgerEntryState,
+        #[case] event: DisputeEvent,
+        #[case] expected: Option<LedgerEntryState>,
+    ) {
+        assert_eq!(event.target(from), expected);
+    }
+
+    #[test]
+    fn test_ignored_transition_errors() {
+        let ignored = |outcome| IgnoredTransition {
+            sequence: 0,
+            tx_id: 4.into(),
+            client_id: 2.into(),
+            event: DisputeEvent::Chargeback,
+            outcome,
+        };
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                ignored(TransitionOutcome::of(None, DisputeEvent::Chargeback)).error()
+            ),
+            "InvalidTransition(\"Attempt to charge back unknown transaction [4] for account [2].\")"
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                ignored(TransitionOutcome::of(
+                    Some(LedgerEntryState::Normal),
+                    DisputeEvent::Chargeback
+                ))
+                .error()
+            ),
+            "InvalidTransition(\"Attempt to charge back transaction [4] for account [2] in state [normal].\")"
+        );
+    }
+}
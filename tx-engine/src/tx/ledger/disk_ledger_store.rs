@@ -0,0 +1,443 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
+use crate::tx::engine::id::{ClientId, TenantId, TxId, ID_BYTES_LEN};
+use crate::tx::engine::ledger_entry::LedgerEntry;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::ledger::ledger_store::LedgerStore;
+
+/// An embedded on-disk key-value store holding the ledger entries of all accounts. Only the
+/// most recently used entries are kept in memory, the cache is shared between all accounts so
+/// memory consumption is bounded by its capacity regardless of the number of clients.
+#[derive(Clone)]
+pub struct DiskLedger {
+    inner: Arc<DiskLedgerInner>,
+    // the tenant whose entries are accessed, see `DiskLedger::for_tenant`, and the prefix of
+    // all keys of its entries and transaction ids
+    tenant_id: Option<TenantId>,
+    prefix: Vec<u8>,
+}
+
+// cached entries of all tenants, keyed by tenant, client and transaction
+type CacheKey = (Option<TenantId>, ClientId, TxId);
+
+struct DiskLedgerInner {
+    entries: sled::Tree,
+    // keys of the entries removed by a compaction, see `LedgerStore::is_expired`
+    expired: sled::Tree,
+    tx_ids: sled::Tree,
+    cache: Mutex<LruCache<CacheKey, LedgerEntry>>,
+}
+
+/// The view of a single account onto a [`DiskLedger`].
+#[derive(Debug)]
+pub struct DiskLedgerStore {
+    ledger: DiskLedger,
+    client_id: ClientId,
+    // counted by the store itself, as sled can only count the entries of all clients at once
+    len: usize,
+}
+
+impl DiskLedger {
+    /// Creates a new ledger in the given directory. Any data found in the directory from
+    /// previous runs is discarded.
+    pub fn create<P: AsRef<Path>>(path: P, cache_capacity: usize) -> TxResult<Self> {
+        let cache_capacity = NonZeroUsize::new(cache_capacity).ok_or(TxError::InvalidArgument(
+            "The ledger cache capacity must be greater than zero.".to_string(),
+        ))?;
+        // the data is discarded on the next run anyway, so there's no need for periodic flushes
+        // by a background thread that would also keep the directory locked after a drop
+        let db = sled::Config::new()
+            .path(path.as_ref())
+            .flush_every_ms(None)
+            .open()
+            .map_err(Self::io_error)?;
+        let entries = db.open_tree("entries").map_err(Self::io_error)?;
+        let expired = db.open_tree("expired").map_err(Self::io_error)?;
+        let tx_ids = db.open_tree("tx_ids").map_err(Self::io_error)?;
+
+        entries.clear().map_err(Self::io_error)?;
+        expired.clear().map_err(Self::io_error)?;
+        tx_ids.clear().map_err(Self::io_error)?;
+
+        Ok(Self {
+            inner: Arc::new(DiskLedgerInner {
+                entries,
+                expired,
+                tx_ids,
+                cache: Mutex::new(LruCache::new(cache_capacity)),
+            }),
+            tenant_id: None,
+            prefix: vec![0],
+        })
+    }
+
+    /// Returns the view of a tenant onto the same ledger, entries and transaction ids of
+    /// different tenants never collide. The cache is shared by all tenants.
+    pub fn for_tenant(&self, tenant_id: TenantId) -> Self {
+        let mut prefix = vec![1];
+        prefix.extend_from_slice(&tenant_id.to_bytes());
+
+        Self {
+            inner: self.inner.clone(),
+            tenant_id: Some(tenant_id),
+            prefix,
+        }
+    }
+
+    pub fn store(&self, client_id: ClientId) -> DiskLedgerStore {
+        DiskLedgerStore {
+            ledger: self.clone(),
+            client_id,
+            len: 0,
+        }
+    }
+
+    /// Checks whether the transaction id was marked as used by any client, see
+    /// [`DiskLedger::mark_tx_id_used`].
+    pub fn is_tx_id_used(&self, tx_id: TxId) -> TxResult<bool> {
+        self.inner
+            .tx_ids
+            .contains_key(self.tx_id_key(tx_id))
+            .map_err(Self::io_error)
+    }
+
+    pub fn mark_tx_id_used(&self, tx_id: TxId) -> TxResult<()> {
+        self.inner
+            .tx_ids
+            .insert(self.tx_id_key(tx_id), &[])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    /// Releases a transaction id marked as used, e.g. if its transaction was rolled back.
+    pub fn release_tx_id(&self, tx_id: TxId) -> TxResult<()> {
+        self.inner
+            .tx_ids
+            .remove(self.tx_id_key(tx_id))
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    fn get(&self, client_id: ClientId, tx_id: TxId) -> TxResult<Option<LedgerEntry>> {
+        let mut cache = self.inner.cache.lock().unwrap();
+        if let Some(entry) = cache.get(&(self.tenant_id, client_id, tx_id)) {
+            return Ok(Some(entry.clone()));
+        }
+
+        let entry = match self
+            .inner
+            .entries
+            .get(self.key(client_id, tx_id))
+            .map_err(Self::io_error)?
+        {
+            Some(bytes) => bincode::deserialize::<LedgerEntry>(&bytes).map_err(Self::io_error)?,
+            None => return Ok(None),
+        };
+
+        cache.put((self.tenant_id, client_id, tx_id), entry.clone());
+
+        Ok(Some(entry))
+    }
+
+    /// Records the entry and returns whether it replaced an entry recorded before.
+    fn put(&self, client_id: ClientId, tx_id: TxId, entry: LedgerEntry) -> TxResult<bool> {
+        let bytes = bincode::serialize(&entry).map_err(Self::io_error)?;
+
+        let previous = self
+            .inner
+            .entries
+            .insert(self.key(client_id, tx_id), bytes)
+            .map_err(Self::io_error)?;
+        self.inner
+            .cache
+            .lock()
+            .unwrap()
+            .put((self.tenant_id, client_id, tx_id), entry);
+
+        Ok(previous.is_some())
+    }
+
+    /// Removes the entry and returns whether there was one.
+    fn remove(&self, client_id: ClientId, tx_id: TxId) -> TxResult<bool> {
+        let previous = self
+            .inner
+            .entries
+            .remove(self.key(client_id, tx_id))
+            .map_err(Self::io_error)?;
+        self.inner
+            .cache
+            .lock()
+            .unwrap()
+            .pop(&(self.tenant_id, client_id, tx_id));
+
+        Ok(previous.is_some())
+    }
+
+    fn retain(
+        &self,
+        client_id: ClientId,
+        keep: &mut dyn FnMut(TxId, &LedgerEntry) -> bool,
+    ) -> TxResult<usize> {
+        let mut removed = 0;
+
+        let mut client_prefix = self.prefix.clone();
+        client_prefix.extend_from_slice(&client_id.to_bytes());
+
+        for item in self.inner.entries.scan_prefix(&client_prefix) {
+            let (key, bytes) = item.map_err(Self::io_error)?;
+            let tx_id = TxId::from_bytes(&key[client_prefix.len()..]).ok_or(Self::io_error(
+                "Invalid transaction id in key of ledger entry",
+            ))?;
+            let entry = bincode::deserialize::<LedgerEntry>(&bytes).map_err(Self::io_error)?;
+
+            if !keep(tx_id, &entry) {
+                self.inner.entries.remove(&key).map_err(Self::io_error)?;
+                self.inner
+                    .expired
+                    .insert(key, &[])
+                    .map_err(Self::io_error)?;
+                self.inner
+                    .cache
+                    .lock()
+                    .unwrap()
+                    .pop(&(self.tenant_id, client_id, tx_id));
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn is_expired(&self, client_id: ClientId, tx_id: TxId) -> TxResult<bool> {
+        self.inner
+            .expired
+            .contains_key(self.key(client_id, tx_id))
+            .map_err(Self::io_error)
+    }
+
+    fn key(&self, client_id: ClientId, tx_id: TxId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.prefix.len() + 2 * ID_BYTES_LEN);
+        key.extend_from_slice(&self.prefix);
+        key.extend_from_slice(&client_id.to_bytes());
+        key.extend_from_slice(&tx_id.to_bytes());
+        key
+    }
+
+    fn tx_id_key(&self, tx_id: TxId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.prefix.len() + ID_BYTES_LEN);
+        key.extend_from_slice(&self.prefix);
+        key.extend_from_slice(&tx_id.to_bytes());
+        key
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while accessing the ledger: {}",
+            error
+        ))
+    }
+}
+
+impl Debug for DiskLedger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskLedger")
+            .field("entries", &self.inner.entries.len())
+            .field("cached", &self.inner.cache.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl LedgerStore for DiskLedgerStore {
+    fn get(&mut self, tx_id: TxId) -> TxResult<Option<LedgerEntry>> {
+        self.ledger.get(self.client_id, tx_id)
+    }
+
+    fn put(&mut self, tx_id: TxId, entry: LedgerEntry) -> TxResult<()> {
+        if !self.ledger.put(self.client_id, tx_id, entry)? {
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, tx_id: TxId) -> TxResult<()> {
+        if self.ledger.remove(self.client_id, tx_id)? {
+            self.len -= 1;
+        }
+        Ok(())
+    }
+
+    fn retain(&mut self, keep: &mut dyn FnMut(TxId, &LedgerEntry) -> bool) -> TxResult<usize> {
+        let removed = self.ledger.retain(self.client_id, keep)?;
+        self.len -= removed;
+        Ok(removed)
+    }
+
+    fn is_expired(&mut self, tx_id: TxId) -> TxResult<bool> {
+        self.ledger.is_expired(self.client_id, tx_id)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::dispute_window::LedgerPosition;
+    use crate::tx::engine::ledger_entry::LedgerEntry;
+    use crate::tx::ledger::disk_ledger_store::DiskLedger;
+    use crate::tx::ledger::ledger_store::LedgerStore;
+
+    #[test]
+    fn test_entries_survive_cache_eviction() {
+        let directory = tempfile::tempdir().unwrap();
+        let ledger = DiskLedger::create(directory.path(), 2).unwrap();
+        let mut store = ledger.store(1.into());
+
+        for tx_id in 0..10 {
+            store
+                .put(
+                    tx_id.into(),
+                    LedgerEntry::new(tx_id.into(), LedgerPosition::default()),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(ledger.inner.cache.lock().unwrap().len(), 2);
+        assert_eq!(
+            store.get(3.into()).unwrap(),
+            Some(LedgerEntry::new(dec!(3), LedgerPosition::default()))
+        );
+        assert_eq!(store.get(10.into()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_tenants_do_not_share_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+        let tenant_ledger = ledger.for_tenant("acme".parse().unwrap());
+        let mut store = ledger.store(1.into());
+        let mut tenant_store = tenant_ledger.store(1.into());
+
+        store
+            .put(
+                7.into(),
+                LedgerEntry::new(dec!(-12.5), LedgerPosition::default()),
+            )
+            .unwrap();
+        ledger.mark_tx_id_used(7.into()).unwrap();
+
+        assert!(store.contains(7.into()).unwrap());
+        assert!(!tenant_store.contains(7.into()).unwrap());
+        assert!(!tenant_ledger.is_tx_id_used(7.into()).unwrap());
+    }
+
+    #[test]
+    fn test_accounts_do_not_share_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+        let mut store_a = ledger.store(1.into());
+        let mut store_b = ledger.store(2.into());
+
+        store_a
+            .put(
+                7.into(),
+                LedgerEntry::new(dec!(-12.5), LedgerPosition::default()),
+            )
+            .unwrap();
+
+        assert!(store_a.contains(7.into()).unwrap());
+        assert!(!store_b.contains(7.into()).unwrap());
+    }
+
+    #[test]
+    fn test_retain_removes_entries_of_one_account_only() {
+        let directory = tempfile::tempdir().unwrap();
+        let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+        let mut store_a = ledger.store(1.into());
+        let mut store_b = ledger.store(2.into());
+
+        for tx_id in 0..4u64 {
+            let entry = LedgerEntry::new(dec!(1), LedgerPosition::default());
+            store_a.put(tx_id.into(), entry.clone()).unwrap();
+            store_b.put(tx_id.into(), entry).unwrap();
+        }
+
+        assert_eq!(
+            store_a
+                .retain(&mut |tx_id, _| tx_id == 0.into() || tx_id == 2.into())
+                .unwrap(),
+            2
+        );
+        assert_eq!(store_a.len(), 2);
+        assert_eq!(store_b.len(), 4);
+        assert!(store_a.contains(0.into()).unwrap());
+        assert!(!store_a.contains(1.into()).unwrap());
+        assert!(store_a.contains(2.into()).unwrap());
+        assert!(!store_a.contains(3.into()).unwrap());
+        assert!(store_b.contains(3.into()).unwrap());
+        assert!(store_a.is_expired(1.into()).unwrap());
+        assert!(!store_a.is_expired(2.into()).unwrap());
+        assert!(!store_b.is_expired(1.into()).unwrap());
+    }
+
+    #[test]
+    fn test_create_discards_previous_data() {
+        let directory = tempfile::tempdir().unwrap();
+
+        {
+            let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+            ledger.mark_tx_id_used(1.into()).unwrap();
+            ledger
+                .store(1.into())
+                .put(
+                    1.into(),
+                    LedgerEntry::new(dec!(1), LedgerPosition::default()),
+                )
+                .unwrap();
+        }
+
+        let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+        assert!(!ledger.is_tx_id_used(1.into()).unwrap());
+        assert!(!ledger.store(1.into()).contains(1.into()).unwrap());
+    }
+
+    #[test]
+    fn test_remove_evicts_cached_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let ledger = DiskLedger::create(directory.path(), 16).unwrap();
+        let mut store = ledger.store(1.into());
+
+        store
+            .put(
+                1.into(),
+                LedgerEntry::new(dec!(1), LedgerPosition::default()),
+            )
+            .unwrap();
+        ledger.mark_tx_id_used(1.into()).unwrap();
+        store.remove(1.into()).unwrap();
+        ledger.release_tx_id(1.into()).unwrap();
+
+        assert!(!store.contains(1.into()).unwrap());
+        assert!(!ledger.is_tx_id_used(1.into()).unwrap());
+    }
+
+    #[test]
+    fn test_zero_cache_capacity_is_rejected() {
+        let directory = tempfile::tempdir().unwrap();
+
+        assert_eq!(
+            format!("{:?}", DiskLedger::create(directory.path(), 0).unwrap_err()),
+            "InvalidArgument(\"The ledger cache capacity must be greater than zero.\")"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::tx::engine::id::TxId;
+use crate::tx::engine::ledger_entry::LedgerEntry;
+use crate::tx::engine::result::TxResult;
+use crate::tx::ledger::ledger_store::LedgerStore;
+
+/// Keeps all ledger entries in memory for the whole lifetime of the account. Removed entries
+/// are dropped, but their ids are kept as well, so memory grows with every transaction.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryLedgerStore {
+    entries: HashMap<TxId, LedgerEntry>,
+    expired: HashSet<TxId>,
+}
+
+impl InMemoryLedgerStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LedgerStore for InMemoryLedgerStore {
+    fn get(&mut self, tx_id: TxId) -> TxResult<Option<LedgerEntry>> {
+        Ok(self.entries.get(&tx_id).cloned())
+    }
+
+    fn put(&mut self, tx_id: TxId, entry: LedgerEntry) -> TxResult<()> {
+        self.entries.insert(tx_id, entry);
+        Ok(())
+    }
+
+    fn remove(&mut self, tx_id: TxId) -> TxResult<()> {
+        self.entries.remove(&tx_id);
+        Ok(())
+    }
+
+    fn retain(&mut self, keep: &mut dyn FnMut(TxId, &LedgerEntry) -> bool) -> TxResult<usize> {
+        let mut removed = Vec::new();
+        self.entries.retain(|tx_id, entry| {
+            let kept = keep(*tx_id, entry);
+            if !kept {
+                removed.push(*tx_id);
+            }
+            kept
+        });
+        let count = removed.len();
+        self.expired.extend(removed);
+        Ok(count)
+    }
+
+    fn is_expired(&mut self, tx_id: TxId) -> TxResult<bool> {
+        Ok(self.expired.contains(&tx_id))
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn contains(&mut self, tx_id: TxId) -> TxResult<bool> {
+        Ok(self.entries.contains_key(&tx_id))
+    }
+}
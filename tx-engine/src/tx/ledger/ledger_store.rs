@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+
+use crate::tx::engine::id::TxId;
+use crate::tx::engine::ledger_entry::LedgerEntry;
+use crate::tx::engine::result::TxResult;
+
+/// Storage for the ledger entries of a single account.
+pub trait LedgerStore: Debug + Send {
+    /// Returns the entry recorded for the given transaction or `None` if the transaction is not
+    /// known to this ledger.
+    fn get(&mut self, tx_id: TxId) -> TxResult<Option<LedgerEntry>>;
+
+    /// Records the entry for the given transaction, replacing any entry recorded before.
+    fn put(&mut self, tx_id: TxId, entry: LedgerEntry) -> TxResult<()>;
+
+    /// Removes the entry recorded for the given transaction, if any.
+    fn remove(&mut self, tx_id: TxId) -> TxResult<()>;
+
+    /// Removes all entries for which `keep` returns `false` and returns the number of removed
+    /// entries. The ids of removed entries are kept as expired, see [`LedgerStore::is_expired`].
+    fn retain(&mut self, keep: &mut dyn FnMut(TxId, &LedgerEntry) -> bool) -> TxResult<usize>;
+
+    /// Whether the entry recorded for the given transaction was removed by
+    /// [`LedgerStore::retain`].
+    fn is_expired(&mut self, tx_id: TxId) -> TxResult<bool>;
+
+    /// The number of entries recorded in this ledger.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&mut self, tx_id: TxId) -> TxResult<bool> {
+        Ok(self.get(tx_id)?.is_some())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod disk_ledger_store;
+pub mod in_memory_ledger_store;
+pub mod ledger_store;
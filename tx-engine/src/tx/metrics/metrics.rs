@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::tx::engine::result::TxError;
+use crate::tx::engine::transaction::TransactionKind;
+
+/// The state of all accounts of an engine at one point in time, see
+/// [`TransactionEngine::gauges`].
+///
+/// [`TransactionEngine::gauges`]: crate::tx::engine::engine::TransactionEngine::gauges
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct EngineGauges {
+    pub accounts: usize,
+    pub locked_accounts: usize,
+    pub open_disputes: usize,
+    pub ledger_entries: usize,
+}
+
+#[derive(Debug, Default)]
+struct MetricValues {
+    transactions: BTreeMap<&'static str, u64>,
+    rejections: BTreeMap<&'static str, u64>,
+    gauges: EngineGauges,
+    parsed: u64,
+    parse_errors: u64,
+    parse_seconds: f64,
+}
+
+/// Counters and gauges of a running engine and the sources feeding it. Clones share their
+/// values, so the engine, the sources and an exporter can each hold one.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    values: Arc<Mutex<MetricValues>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts an executed transaction of the given kind, and its error if it failed.
+    pub fn record_transaction(&self, kind: &TransactionKind, error: Option<&TxError>) {
+        let mut values = self.values.lock().unwrap();
+        *values.transactions.entry(kind.name()).or_default() += 1;
+        if let Some(error) = error {
+            *values.rejections.entry(error.kind()).or_default() += 1;
+        }
+    }
+
+    /// Counts a record read by a source and the time it took to parse, failed or not.
+    pub fn record_parse(&self, elapsed: Duration, failed: bool) {
+        let mut values = self.values.lock().unwrap();
+        values.parsed += 1;
+        values.parse_seconds += elapsed.as_secs_f64();
+        if failed {
+            values.parse_errors += 1;
+        }
+    }
+
+    pub fn set_gauges(&self, gauges: EngineGauges) {
+        self.values.lock().unwrap().gauges = gauges;
+    }
+
+    pub fn transactions(&self, kind: &str) -> u64 {
+        let values = self.values.lock().unwrap();
+        values.transactions.get(kind).copied().unwrap_or_default()
+    }
+
+    pub fn rejections(&self, error_kind: &str) -> u64 {
+        let values = self.values.lock().unwrap();
+        values
+            .rejections
+            .get(error_kind)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn gauges(&self) -> EngineGauges {
+        self.values.lock().unwrap().gauges
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let values = self.values.lock().unwrap();
+        let mut output = String::new();
+
+        Self::write_header(
+            &mut output,
+            "tx_transactions_total",
+            "counter",
+            "Transactions executed, including failed ones.",
+        );
+        for (kind, count) in &values.transactions {
+            writeln!(
+                output,
+                "tx_transactions_total{{kind=\"{}\"}} {}",
+                kind, count
+            )
+            .unwrap();
+        }
+
+        Self::write_header(
+            &mut output,
+            "tx_rejections_total",
+            "counter",
+            "Transactions that failed, by error.",
+        );
+        for (error, count) in &values.rejections {
+            writeln!(
+                output,
+                "tx_rejections_total{{error=\"{}\"}} {}",
+                error, count
+            )
+            .unwrap();
+        }
+
+        let gauges = [
+            (
+                "tx_accounts",
+                "Accounts of all clients.",
+                values.gauges.accounts,
+            ),
+            (
+                "tx_locked_accounts",
+                "Accounts that are locked.",
+                values.gauges.locked_accounts,
+            ),
+            (
+                "tx_open_disputes",
+                "Transactions that are currently disputed.",
+                values.gauges.open_disputes,
+            ),
+            (
+                "tx_ledger_entries",
+                "Ledger entries of all accounts.",
+                values.gauges.ledger_entries,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            Self::write_header(&mut output, name, "gauge", help);
+            writeln!(output, "{} {}", name, value).unwrap();
+        }
+
+        Self::write_header(
+            &mut output,
+            "tx_parse_errors_total",
+            "counter",
+            "Input records that failed to parse.",
+        );
+        writeln!(output, "tx_parse_errors_total {}", values.parse_errors).unwrap();
+
+        Self::write_header(
+            &mut output,
+            "tx_parse_duration_seconds",
+            "summary",
+            "Time taken to read and parse an input record.",
+        );
+        writeln!(
+            output,
+            "tx_parse_duration_seconds_sum {}",
+            values.parse_seconds
+        )
+        .unwrap();
+        writeln!(output, "tx_parse_duration_seconds_count {}", values.parsed).unwrap();
+
+        output
+    }
+
+    fn write_header(output: &mut String, name: &str, kind: &str, help: &str) {
+        writeln!(output, "# HELP {} {}", name, help).unwrap();
+        writeln!(output, "# TYPE {} {}", name, kind).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::result::TxError;
+    use crate::tx::engine::transaction::TransactionKind;
+    use crate::tx::metrics::metrics::{EngineGauges, Metrics};
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.record_transaction(&TransactionKind::Deposit(dec!(1)), None);
+        metrics.record_transaction(&TransactionKind::Deposit(dec!(2)), None);
+        metrics.record_transaction(
+            &TransactionKind::Withdrawal(dec!(5)),
+            Some(&TxError::InvalidOperation("Insufficient funds".to_string())),
+        );
+        metrics.set_gauges(EngineGauges {
+            accounts: 2,
+            locked_accounts: 1,
+            open_disputes: 0,
+            ledger_entries: 3,
+        });
+        metrics.record_parse(Duration::from_millis(250), false);
+        metrics.record_parse(Duration::from_millis(500), true);
+
+        assert_eq!(
+            metrics.render(),
+            "# HELP tx_transactions_total Transactions executed, including failed ones.\n\
+             # TYPE tx_transactions_total counter\n\
+             tx_transactions_total{kind=\"deposit\"} 2\n\
+             tx_transactions_total{kind=\"withdrawal\"} 1\n\
+             # HELP tx_rejections_total Transactions that failed, by error.\n\
+             # TYPE tx_rejections_total counter\n\
+             tx_rejections_total{error=\"invalid_operation\"} 1\n\
+             # HELP tx_accounts Accounts of all clients.\n\
+             # TYPE tx_accounts gauge\n\
+             tx_accounts 2\n\
+             # HELP tx_locked_accounts Accounts that are locked.\n\
+             # TYPE tx_locked_accounts gauge\n\
+             tx_locked_accounts 1\n\
+             # HELP tx_open_disputes Transactions that are currently disputed.\n\
+             # TYPE tx_open_disputes gauge\n\
+             tx_open_disputes 0\n\
+             # HELP tx_ledger_entries Ledger entries of all accounts.\n\
+             # TYPE tx_ledger_entries gauge\n\
+             tx_ledger_entries 3\n\
+             # HELP tx_parse_errors_total Input records that failed to parse.\n\
+             # TYPE tx_parse_errors_total counter\n\
+             tx_parse_errors_total 1\n\
+             # HELP tx_parse_duration_seconds Time taken to read and parse an input record.\n\
+             # TYPE tx_parse_duration_seconds summary\n\
+             tx_parse_duration_seconds_sum 0.75\n\
+             tx_parse_duration_seconds_count 2\n"
+        );
+    }
+
+    #[test]
+    fn test_clones_share_values() {
+        let metrics = Metrics::new();
+        metrics
+            .clone()
+            .record_transaction(&TransactionKind::Resolve, None);
+
+        assert_eq!(metrics.transactions("resolve"), 1);
+        assert_eq!(metrics.transactions("dispute"), 0);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::task::JoinHandle;
+
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::metrics::metrics::Metrics;
+
+// requests are only read up to the end of their head, bodies are ignored
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Serves the metrics in the Prometheus text format at `GET /metrics` over plain HTTP, until
+/// the server is dropped.
+pub struct MetricsServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MetricsServer {
+    pub async fn bind<A: ToSocketAddrs>(address: A, metrics: Metrics) -> TxResult<Self> {
+        let listener = TcpListener::bind(address).await.map_err(Self::io_error)?;
+        let address = listener.local_addr().map_err(Self::io_error)?;
+
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let metrics = metrics.clone();
+                // a failing scrape only affects its own connection
+                tokio::spawn(async move { Self::serve(stream, &metrics).await });
+            }
+        });
+
+        Ok(Self { address, task })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!("Unable to serve metrics: {}", error))
+    }
+
+    /// The address the server listens on, with the actual port if it was bound to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    async fn serve(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+        let mut head = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).await?;
+            if read == 0 || head.len() + read > MAX_REQUEST_HEAD {
+                return Ok(());
+            }
+            head.extend_from_slice(&buffer[..read]);
+        }
+
+        let request_line = head.split(|byte| *byte == b'\r').next().unwrap_or_default();
+        let (status, body) = match request_line.split(|byte| *byte == b' ').collect::<Vec<_>>()[..]
+        {
+            [b"GET", b"/metrics", _] => ("200 OK", metrics.render()),
+            [b"GET", _, _] => ("404 Not Found", String::new()),
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use crate::tx::engine::transaction::TransactionKind;
+    use crate::tx::metrics::metrics::Metrics;
+    use crate::tx::metrics::metrics_server::MetricsServer;
+
+    async fn request(server: &MetricsServer, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics() {
+        let metrics = Metrics::new();
+        let server = MetricsServer::bind("127.0.0.1:0", metrics.clone())
+            .await
+            .unwrap();
+        metrics.record_transaction(&TransactionKind::Open, None);
+
+        let response = request(&server, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+        assert!(response.ends_with(&format!("\r\n\r\n{}", metrics.render())));
+        assert!(response.contains("tx_transactions_total{kind=\"open\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_other_requests() {
+        let server = MetricsServer::bind("127.0.0.1:0", Metrics::new())
+            .await
+            .unwrap();
+
+        assert!(request(&server, "GET / HTTP/1.1\r\n\r\n")
+            .await
+            .starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(request(&server, "POST /metrics HTTP/1.1\r\n\r\n")
+            .await
+            .starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+}
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod metrics;
+pub mod metrics_server;
@@ -1,4 +1,8 @@
+pub mod accounting;
 pub mod engine;
+pub mod ledger;
+pub mod metrics;
 pub mod reports;
+pub mod rules;
 pub mod sources;
 pub mod tests;
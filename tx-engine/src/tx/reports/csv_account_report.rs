@@ -5,6 +5,8 @@ use csv::Writer;
 use rust_decimal::Decimal;
 
 use crate::tx::engine::account::AccountSummary;
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::multi_tenant_engine::TenantAccountSummary;
 use crate::tx::engine::result::{TxError, TxResult};
 
 pub struct CsvAccountReport<W>
@@ -19,10 +21,31 @@ where
     W: Write + Unpin + Send,
 {
     pub fn from_writer(sink: W) -> TxResult<Self> {
+        Self::with_columns(sink, vec![])
+    }
+
+    /// Creates a report of the accounts of several tenants, with the tenant as first column.
+    /// See [`CsvAccountReport::write_tenant_account`].
+    pub fn from_writer_with_tenants(sink: W) -> TxResult<Self> {
+        Self::with_columns(sink, vec!["tenant"])
+    }
+
+    fn with_columns(sink: W, mut columns: Vec<&str>) -> TxResult<Self> {
         let mut writer = Writer::from_writer(sink);
 
+        columns.extend([
+            "client",
+            "available",
+            "held",
+            "authorized",
+            "total",
+            "locked",
+            "status",
+            "credit_limit",
+            "fees",
+        ]);
         writer
-            .write_record(vec!["client", "available", "held", "total", "locked"])
+            .write_record(columns)
             .map_err(|e| Self::io_error(e))?;
 
         Ok(Self {
@@ -43,7 +66,7 @@ where
         )
     }
 
-    fn serialize_u16(value: u16) -> String {
+    fn serialize_id(value: ClientId) -> String {
         value.to_string()
     }
 
@@ -56,16 +79,33 @@ where
     }
 
     pub fn write_account(&mut self, account: &AccountSummary) -> TxResult<()> {
+        self.write_record(vec![], account)
+    }
+
+    pub fn write_tenant_account(&mut self, account: &TenantAccountSummary) -> TxResult<()> {
+        self.write_record(
+            vec![Self::serialize_id(account.tenant_id)],
+            &account.account,
+        )
+    }
+
+    fn write_record(&mut self, mut record: Vec<String>, account: &AccountSummary) -> TxResult<()> {
+        record.extend([
+            Self::serialize_id(account.id),
+            Self::serialize_decimal(account.available),
+            Self::serialize_decimal(account.held),
+            Self::serialize_decimal(account.authorized),
+            Self::serialize_decimal(account.total),
+            Self::serialize_bool(account.is_locked),
+            account.status.to_string(),
+            Self::serialize_decimal(account.credit_limit),
+            Self::serialize_decimal(account.fees),
+        ]);
+
         self.writer
             .as_mut()
             .ok_or(Self::use_after_flush_error())?
-            .write_record(vec![
-                Self::serialize_u16(account.id),
-                Self::serialize_decimal(account.available),
-                Self::serialize_decimal(account.held),
-                Self::serialize_decimal(account.total),
-                Self::serialize_bool(account.is_locked),
-            ])
+            .write_record(record)
             .map_err(|e| Self::io_error(e))?;
 
         Ok(())
@@ -87,13 +127,17 @@ mod tests {
     use rust_decimal_macros::dec;
 
     use crate::tx::engine::account::Account;
+    use crate::tx::engine::multi_tenant_engine::TenantAccountSummary;
     use crate::tx::reports::csv_account_report::CsvAccountReport;
 
     #[tokio::test]
     async fn test_no_accounts() {
         let mut report = CsvAccountReport::from_writer(Vec::new()).unwrap();
         let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
-        assert_eq!(csv_output, "client,available,held,total,locked\n");
+        assert_eq!(
+            csv_output,
+            "client,available,held,authorized,total,locked,status,credit_limit,fees\n"
+        );
     }
 
     #[tokio::test]
@@ -102,18 +146,42 @@ mod tests {
         let mut account_a = Account::new(1);
         let mut account_b = Account::new(2);
 
-        account_a.deposit(2, dec!(13.28973498)).unwrap();
-        account_a.deposit(3, dec!(1)).unwrap();
-        account_a.dispute(3).unwrap();
-        account_a.chargeback(3).unwrap();
+        account_a.deposit(2.into(), dec!(13.28973498)).unwrap();
+        account_a.deposit(3.into(), dec!(1)).unwrap();
+        account_a.dispute(3.into()).unwrap();
+        account_a.chargeback(3.into()).unwrap();
 
-        account_b.deposit(3, dec!(13898273)).unwrap();
+        account_b.set_credit_limit(dec!(250.5)).unwrap();
+        account_b.deposit(3.into(), dec!(13898273)).unwrap();
 
         report.write_account(&account_a.summary()).unwrap();
         report.write_account(&account_b.summary()).unwrap();
 
         let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
-        assert_eq!(csv_output, "client,available,held,total,locked\n1,13.2897,0,13.2897,true\n2,13898273,0,13898273,false\n");
+        assert_eq!(csv_output, "client,available,held,authorized,total,locked,status,credit_limit,fees\n1,13.2897,0,0,13.2897,true,open,0,0\n2,13898273,0,0,13898273,false,open,250.5,0\n");
+    }
+
+    #[test]
+    fn test_tenant_accounts() {
+        let mut report = CsvAccountReport::from_writer_with_tenants(Vec::new()).unwrap();
+        let mut account = Account::new(1);
+        account.deposit(1.into(), dec!(2.5)).unwrap();
+
+        report
+            .write_tenant_account(&TenantAccountSummary {
+                tenant_id: "acme".parse().unwrap(),
+                account: account.summary(),
+            })
+            .unwrap();
+        report
+            .write_tenant_account(&TenantAccountSummary {
+                tenant_id: "globex".parse().unwrap(),
+                account: Account::new(1).summary(),
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(csv_output, "tenant,client,available,held,authorized,total,locked,status,credit_limit,fees\nacme,1,2.5,0,0,2.5,false,open,0,0\nglobex,1,0,0,0,0,false,open,0,0\n");
     }
 
     #[rstest]
@@ -132,11 +200,16 @@ mod tests {
     }
 
     #[rstest]
-    #[case(0, "0")]
-    #[case(65535, "65535")]
-    fn test_u16_formatting(#[case] given_value: u16, #[case] expected_result: &str) {
+    #[case("0", "0")]
+    #[case("65536", "65536")]
+    #[case("ref-7", "ref-7")]
+    #[case(
+        "67E55044-10B1-426F-9247-BB680E5FE0C8",
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    )]
+    fn test_id_formatting(#[case] given_value: &str, #[case] expected_result: &str) {
         assert_eq!(
-            CsvAccountReport::<Vec<u8>>::serialize_u16(given_value).as_str(),
+            CsvAccountReport::<Vec<u8>>::serialize_id(given_value.parse().unwrap()).as_str(),
             expected_result
         );
     }
@@ -0,0 +1,103 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+use rust_decimal::Decimal;
+
+use crate::tx::engine::interest::InterestAccrual;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// Writes the interest posted by accruals, one row per account and accrual.
+pub struct CsvAccrualReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvAccrualReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec!["tx", "client", "balance", "rate", "interest"])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_decimal(value: Decimal) -> String {
+        value.round_dp(4).to_string()
+    }
+
+    pub fn write_accrual(&mut self, accrual: &InterestAccrual) -> TxResult<()> {
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                accrual.tx_id.to_string(),
+                accrual.client_id.to_string(),
+                Self::serialize_decimal(accrual.balance),
+                accrual.annual_percentage.to_string(),
+                Self::serialize_decimal(accrual.interest),
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::interest::InterestAccrual;
+    use crate::tx::reports::csv_accrual_report::CsvAccrualReport;
+
+    #[test]
+    fn test_accruals() {
+        let mut report = CsvAccrualReport::from_writer(Vec::new()).unwrap();
+
+        report
+            .write_accrual(&InterestAccrual {
+                tx_id: 100.into(),
+                client_id: 2.into(),
+                balance: dec!(1000.123456),
+                annual_percentage: dec!(2.5),
+                interest: dec!(0.0685),
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "tx,client,balance,rate,interest\n100,2,1000.1235,2.5,0.0685\n"
+        );
+    }
+}
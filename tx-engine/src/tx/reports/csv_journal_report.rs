@@ -0,0 +1,107 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+use rust_decimal::Decimal;
+
+use crate::tx::accounting::journal_entry::JournalEntry;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// Writes the entries of a general ledger journal, one row per posting.
+pub struct CsvJournalReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvJournalReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec!["entry", "tx", "account", "debit", "credit"])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_decimal(value: Decimal) -> String {
+        value.round_dp(4).to_string()
+    }
+
+    pub fn write_entry(&mut self, entry: &JournalEntry) -> TxResult<()> {
+        let writer = self.writer.as_mut().ok_or(Self::use_after_flush_error())?;
+
+        for posting in &entry.postings {
+            writer
+                .write_record(vec![
+                    entry.entry_id.to_string(),
+                    entry.tx_id.to_string(),
+                    posting.account.to_string(),
+                    Self::serialize_decimal(posting.debit),
+                    Self::serialize_decimal(posting.credit),
+                ])
+                .map_err(Self::io_error)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::accounting::gl_account::GlAccount;
+    use crate::tx::accounting::journal_entry::{JournalEntry, Posting};
+    use crate::tx::reports::csv_journal_report::CsvJournalReport;
+
+    #[test]
+    fn test_journal() {
+        let mut report = CsvJournalReport::from_writer(Vec::new()).unwrap();
+
+        report
+            .write_entry(&JournalEntry {
+                entry_id: 1,
+                tx_id: 7.into(),
+                postings: vec![
+                    Posting::signed(GlAccount::Settlement, dec!(1.5)),
+                    Posting::signed(GlAccount::Client(2.into()), dec!(-1.5)),
+                ],
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "entry,tx,account,debit,credit\n1,7,settlement,1.5,0\n1,7,client:2,0,1.5\n"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+use rust_decimal::Decimal;
+
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+
+/// Writes the transactions waiting for their value date, one row per transaction. The
+/// `to_client` column is only filled for transfers.
+pub struct CsvPendingReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvPendingReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec![
+                "value_date",
+                "type",
+                "client",
+                "tx",
+                "amount",
+                "to_client",
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_decimal(value: Decimal) -> String {
+        value.round_dp(4).to_string()
+    }
+
+    pub fn write_transaction(&mut self, transaction: &Transaction) -> TxResult<()> {
+        let (kind, amount, to_client) = match transaction.kind() {
+            TransactionKind::Deposit(amount) => ("deposit", amount, String::new()),
+            TransactionKind::Withdrawal(amount) => ("withdrawal", amount, String::new()),
+            TransactionKind::Transfer { to_client, amount } => {
+                ("transfer", amount, to_client.to_string())
+            }
+            kind => {
+                return Err(TxError::InvalidArgument(format!(
+                    "Attempt to report transaction [{}] of kind [{:?}] as pending.",
+                    transaction.tx_id(),
+                    kind
+                )))
+            }
+        };
+
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                transaction
+                    .value_date()
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                kind.to_string(),
+                transaction.client_id().to_string(),
+                transaction.tx_id().to_string(),
+                Self::serialize_decimal(amount),
+                to_client,
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::reports::csv_pending_report::CsvPendingReport;
+
+    #[test]
+    fn test_pending_transactions() {
+        let mut report = CsvPendingReport::from_writer(Vec::new()).unwrap();
+        let value_date = "2024-03-01".parse().unwrap();
+
+        report
+            .write_transaction(
+                &Transaction::new_deposit(1, 2, dec!(10.5)).with_value_date(value_date),
+            )
+            .unwrap();
+        report
+            .write_transaction(
+                &Transaction::new_transfer(2, 2, 3, dec!(1.23456)).with_value_date(value_date),
+            )
+            .unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                report
+                    .write_transaction(&Transaction::new_dispute(1, 2))
+                    .unwrap_err()
+            ),
+            "InvalidArgument(\"Attempt to report transaction [1] of kind [Dispute(None)] as pending.\")"
+        );
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "value_date,type,client,tx,amount,to_client\n2024-03-01,deposit,2,1,10.5,\n2024-03-01,transfer,2,2,1.2346,3\n"
+        );
+    }
+}
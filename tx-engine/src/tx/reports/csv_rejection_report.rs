@@ -0,0 +1,142 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+
+use crate::tx::engine::id::TenantId;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::simulation::Rejection;
+
+/// Writes the transactions rejected in a simulation, one row per transaction.
+pub struct CsvRejectionReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvRejectionReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        Self::with_columns(sink, vec![])
+    }
+
+    /// Creates a report of the rejections of several tenants, with the tenant as first column.
+    /// See [`CsvRejectionReport::write_tenant_rejection`].
+    pub fn from_writer_with_tenants(sink: W) -> TxResult<Self> {
+        Self::with_columns(sink, vec!["tenant"])
+    }
+
+    fn with_columns(sink: W, mut columns: Vec<&str>) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        columns.extend(["index", "tx", "client", "error"]);
+        writer.write_record(columns).map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    pub fn write_rejection(&mut self, rejection: &Rejection) -> TxResult<()> {
+        self.write_record(vec![], rejection)
+    }
+
+    pub fn write_tenant_rejection(
+        &mut self,
+        tenant_id: TenantId,
+        rejection: &Rejection,
+    ) -> TxResult<()> {
+        self.write_record(vec![tenant_id.to_string()], rejection)
+    }
+
+    fn write_record(&mut self, mut record: Vec<String>, rejection: &Rejection) -> TxResult<()> {
+        record.extend([
+            rejection.index.to_string(),
+            rejection.tx_id.to_string(),
+            rejection.client_id.to_string(),
+            format!("{:?}", rejection.error),
+        ]);
+
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(record)
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::engine::result::TxError;
+    use crate::tx::engine::simulation::Rejection;
+    use crate::tx::reports::csv_rejection_report::CsvRejectionReport;
+
+    #[test]
+    fn test_rejection() {
+        let mut report = CsvRejectionReport::from_writer(Vec::new()).unwrap();
+
+        report
+            .write_rejection(&Rejection {
+                index: 4,
+                tx_id: 12.into(),
+                client_id: 2.into(),
+                error: TxError::InvalidOperation("Account is locked.".to_string()),
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "index,tx,client,error\n4,12,2,\"InvalidOperation(\"\"Account is locked.\"\")\"\n"
+        );
+    }
+
+    #[test]
+    fn test_tenant_rejection() {
+        let mut report = CsvRejectionReport::from_writer_with_tenants(Vec::new()).unwrap();
+
+        report
+            .write_tenant_rejection(
+                "acme".parse().unwrap(),
+                &Rejection {
+                    index: 0,
+                    tx_id: 3.into(),
+                    client_id: 1.into(),
+                    error: TxError::InvalidOperation("Account is locked.".to_string()),
+                },
+            )
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "tenant,index,tx,client,error\nacme,0,3,1,\"InvalidOperation(\"\"Account is locked.\"\")\"\n"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::rules::transaction_rule::{RuleAction, RuleDecision};
+
+/// Writes the rule decisions made by the engine, one row per evaluated rule and transaction.
+/// The reason is empty for passed rules.
+pub struct CsvRuleAuditReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvRuleAuditReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec!["tx", "client", "rule", "outcome", "action", "reason"])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_outcome(decision: &RuleDecision) -> String {
+        if decision.is_violation() {
+            "violated"
+        } else {
+            "passed"
+        }
+        .to_string()
+    }
+
+    fn serialize_action(action: RuleAction) -> String {
+        match action {
+            RuleAction::Reject => "reject",
+            RuleAction::Flag => "flag",
+            RuleAction::Lock => "lock",
+        }
+        .to_string()
+    }
+
+    pub fn write_decision(&mut self, decision: &RuleDecision) -> TxResult<()> {
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                decision.tx_id.to_string(),
+                decision.client_id.to_string(),
+                decision.rule.clone(),
+                Self::serialize_outcome(decision),
+                Self::serialize_action(decision.action),
+                decision.reason.clone().unwrap_or_default(),
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::reports::csv_rule_audit_report::CsvRuleAuditReport;
+    use crate::tx::rules::transaction_rule::{RuleAction, RuleDecision};
+
+    #[test]
+    fn test_decisions() {
+        let mut report = CsvRuleAuditReport::from_writer(Vec::new()).unwrap();
+
+        report
+            .write_decision(&RuleDecision {
+                tx_id: 3.into(),
+                client_id: 2.into(),
+                rule: "blocked_client".to_string(),
+                action: RuleAction::Lock,
+                reason: Some("Client [2] is blocked".to_string()),
+            })
+            .unwrap();
+        report
+            .write_decision(&RuleDecision {
+                tx_id: 4.into(),
+                client_id: 1.into(),
+                rule: "blocked_client".to_string(),
+                action: RuleAction::Lock,
+                reason: None,
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "tx,client,rule,outcome,action,reason\n\
+             3,2,blocked_client,violated,lock,Client [2] is blocked\n\
+             4,1,blocked_client,passed,lock,\n"
+        );
+    }
+}
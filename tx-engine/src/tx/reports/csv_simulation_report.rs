@@ -0,0 +1,124 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+use rust_decimal::Decimal;
+
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::simulation::AccountChange;
+
+/// Writes the accounts resulting from a simulation together with their changes, one row per
+/// account.
+pub struct CsvSimulationReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvSimulationReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec![
+                "client",
+                "available",
+                "held",
+                "total",
+                "locked",
+                "status",
+                "available_delta",
+                "held_delta",
+                "total_delta",
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_decimal(value: Decimal) -> String {
+        value.round_dp(4).to_string()
+    }
+
+    fn serialize_bool(value: bool) -> String {
+        (if value { "true" } else { "false" }).to_string()
+    }
+
+    pub fn write_change(&mut self, change: &AccountChange) -> TxResult<()> {
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                change.after.id.to_string(),
+                Self::serialize_decimal(change.after.available),
+                Self::serialize_decimal(change.after.held),
+                Self::serialize_decimal(change.after.total),
+                Self::serialize_bool(change.after.is_locked),
+                change.after.status.to_string(),
+                Self::serialize_decimal(change.available_delta()),
+                Self::serialize_decimal(change.held_delta()),
+                Self::serialize_decimal(change.total_delta()),
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::account::Account;
+    use crate::tx::engine::simulation::AccountChange;
+    use crate::tx::reports::csv_simulation_report::CsvSimulationReport;
+
+    #[test]
+    fn test_simulation() {
+        let mut account = Account::new(3);
+        account.deposit(1.into(), dec!(10)).unwrap();
+        let before = account.summary();
+        account.dispute(1.into()).unwrap();
+
+        let mut report = CsvSimulationReport::from_writer(Vec::new()).unwrap();
+        report
+            .write_change(&AccountChange {
+                before: Some(before),
+                after: account.summary(),
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "client,available,held,total,locked,status,available_delta,held_delta,total_delta\n3,0,10,10,false,open,-10,10,0\n"
+        );
+    }
+}
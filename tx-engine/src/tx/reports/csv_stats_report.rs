@@ -0,0 +1,107 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::engine::transition::TransitionCount;
+
+/// Writes how often each dispute step had each outcome, one row per event, outcome and the state
+/// an ignored step found its transaction in.
+pub struct CsvStatsReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvStatsReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec!["event", "outcome", "from", "count"])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    pub fn write_count(&mut self, count: &TransitionCount) -> TxResult<()> {
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                count.event.to_string(),
+                count.outcome.to_string(),
+                count
+                    .outcome
+                    .from_state()
+                    .map(|from| from.to_string())
+                    .unwrap_or_default(),
+                count.count.to_string(),
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::engine::ledger_entry::LedgerEntryState;
+    use crate::tx::engine::transition::{DisputeEvent, TransitionOutcome, TransitionStats};
+    use crate::tx::reports::csv_stats_report::CsvStatsReport;
+
+    #[test]
+    fn test_transition_counts() {
+        let mut stats = TransitionStats::default();
+        stats.record(DisputeEvent::Dispute, TransitionOutcome::Applied);
+        stats.record(DisputeEvent::Dispute, TransitionOutcome::Applied);
+        stats.record(DisputeEvent::Resolve, TransitionOutcome::IgnoredUnknownTx);
+        stats.record(
+            DisputeEvent::Chargeback,
+            TransitionOutcome::of(
+                Some(LedgerEntryState::ChargedBack),
+                DisputeEvent::Chargeback,
+            ),
+        );
+
+        let mut report = CsvStatsReport::from_writer(Vec::new()).unwrap();
+        stats
+            .counts()
+            .iter()
+            .for_each(|count| report.write_count(count).unwrap());
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(
+            csv_output,
+            "event,outcome,from,count\ndispute,applied,,2\nresolve,ignored_unknown_tx,,1\nchargeback,ignored_invalid_state,charged_back,1\n"
+        );
+    }
+}
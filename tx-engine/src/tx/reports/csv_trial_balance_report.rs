@@ -0,0 +1,97 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use csv::Writer;
+use rust_decimal::Decimal;
+
+use crate::tx::accounting::general_ledger::TrialBalanceLine;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// Writes the trial balance of a general ledger, one row per account.
+pub struct CsvTrialBalanceReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<Writer<W>>,
+}
+
+impl<W> CsvTrialBalanceReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        let mut writer = Writer::from_writer(sink);
+
+        writer
+            .write_record(vec!["account", "debit", "credit"])
+            .map_err(Self::io_error)?;
+
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing CSV record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    fn serialize_decimal(value: Decimal) -> String {
+        value.round_dp(4).to_string()
+    }
+
+    pub fn write_line(&mut self, line: &TrialBalanceLine) -> TxResult<()> {
+        self.writer
+            .as_mut()
+            .ok_or(Self::use_after_flush_error())?
+            .write_record(vec![
+                line.account.to_string(),
+                Self::serialize_decimal(line.debit),
+                Self::serialize_decimal(line.credit),
+            ])
+            .map_err(Self::io_error)?;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let mut writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.flush().map_err(Self::io_error)?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::accounting::general_ledger::TrialBalanceLine;
+    use crate::tx::accounting::gl_account::GlAccount;
+    use crate::tx::reports::csv_trial_balance_report::CsvTrialBalanceReport;
+
+    #[test]
+    fn test_trial_balance() {
+        let mut report = CsvTrialBalanceReport::from_writer(Vec::new()).unwrap();
+
+        report
+            .write_line(&TrialBalanceLine {
+                account: GlAccount::DisputesHeld,
+                debit: dec!(0),
+                credit: dec!(3),
+            })
+            .unwrap();
+
+        let csv_output = String::from_utf8(report.flush().unwrap()).unwrap();
+        assert_eq!(csv_output, "account,debit,credit\ndisputes_held,0,3\n");
+    }
+}
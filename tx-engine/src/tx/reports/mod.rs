@@ -1 +1,10 @@
 pub mod csv_account_report;
+pub mod csv_accrual_report;
+pub mod csv_journal_report;
+pub mod csv_pending_report;
+pub mod csv_rejection_report;
+pub mod csv_rule_audit_report;
+pub mod csv_simulation_report;
+pub mod csv_stats_report;
+pub mod csv_trial_balance_report;
+pub mod ndjson_event_report;
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+use std::io::{BufWriter, Write};
+
+use crate::tx::engine::event::AccountEvent;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// Writes account events as newline delimited JSON, one event per line.
+pub struct NdjsonEventReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    writer: Option<BufWriter<W>>,
+}
+
+impl<W> NdjsonEventReport<W>
+where
+    W: Write + Unpin + Send,
+{
+    pub fn from_writer(sink: W) -> TxResult<Self> {
+        Ok(Self {
+            writer: Some(BufWriter::new(sink)),
+        })
+    }
+
+    fn io_error<E: Display>(error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while writing JSON record: {}",
+            error
+        ))
+    }
+
+    fn use_after_flush_error() -> TxError {
+        TxError::InvalidOperation(
+            "The report was already written, no further action possible.".to_string(),
+        )
+    }
+
+    pub fn write_event(&mut self, event: &AccountEvent) -> TxResult<()> {
+        let writer = self.writer.as_mut().ok_or(Self::use_after_flush_error())?;
+
+        serde_json::to_writer(&mut *writer, event).map_err(Self::io_error)?;
+        writer.write_all(b"\n").map_err(Self::io_error)
+    }
+
+    pub fn flush(&mut self) -> TxResult<W> {
+        let writer = self.writer.take().ok_or(Self::use_after_flush_error())?;
+
+        writer.into_inner().map_err(Self::io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::account::Account;
+    use crate::tx::engine::event::AccountEvent;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::reports::ndjson_event_report::NdjsonEventReport;
+
+    #[test]
+    fn test_one_event_per_line() {
+        let mut report = NdjsonEventReport::from_writer(Vec::new()).unwrap();
+        let mut account = Account::new(3);
+        let before = account.summary();
+        account.deposit(1.into(), dec!(2)).unwrap();
+        let deposited = account.summary();
+        account.withdraw(2.into(), dec!(1)).unwrap();
+
+        for (transaction, before, after) in [
+            (Transaction::new_deposit(1, 3, dec!(2)), before, deposited),
+            (
+                Transaction::new_withdrawal(2, 3, dec!(1)),
+                deposited,
+                account.summary(),
+            ),
+        ] {
+            for event in AccountEvent::between(&transaction, before, after) {
+                report.write_event(&event).unwrap();
+            }
+        }
+
+        let output = String::from_utf8(report.flush().unwrap()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].starts_with("{\"type\":\"credited\",\"amount\":\"2\",\"tx\":1,\"client\":3,")
+        );
+        assert!(
+            lines[1].starts_with("{\"type\":\"debited\",\"amount\":\"1\",\"tx\":2,\"client\":3,")
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+/// Matches any transaction of a blocked client, including transfers to it.
+#[derive(Debug)]
+pub struct BlockedClientRule {
+    client_id: ClientId,
+}
+
+impl BlockedClientRule {
+    pub fn new(client_id: impl Into<ClientId>) -> Self {
+        Self {
+            client_id: client_id.into(),
+        }
+    }
+}
+
+impl TransactionRule for BlockedClientRule {
+    fn name(&self) -> &str {
+        "blocked_client"
+    }
+
+    fn evaluate(&mut self, transaction: &Transaction, _context: &RuleContext) -> Option<String> {
+        if transaction.client_id() == self.client_id {
+            return Some(format!("Client [{}] is blocked", self.client_id));
+        }
+
+        match transaction.kind() {
+            TransactionKind::Transfer { to_client, .. } if to_client == self.client_id => {
+                Some(format!("Transfer to blocked client [{}]", self.client_id))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::dispute_window::LedgerPosition;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::rules::blocked_client_rule::BlockedClientRule;
+    use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+    #[test]
+    fn test_blocked_client() {
+        let mut rule = BlockedClientRule::new(2);
+        let context = RuleContext {
+            position: LedgerPosition::default(),
+            account: None,
+        };
+
+        assert_eq!(
+            rule.evaluate(&Transaction::new_deposit(1, 1, dec!(5)), &context),
+            None
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_deposit(2, 2, dec!(5)), &context),
+            Some("Client [2] is blocked".to_string())
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_transfer(3, 1, 3, dec!(5)), &context),
+            None
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_transfer(4, 1, 2, dec!(5)), &context),
+            Some("Transfer to blocked client [2]".to_string())
+        );
+    }
+}
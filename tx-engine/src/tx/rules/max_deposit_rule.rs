@@ -0,0 +1,64 @@
+use rust_decimal::Decimal;
+
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+/// Limits the amount of a single deposit.
+#[derive(Debug)]
+pub struct MaxDepositRule {
+    limit: Decimal,
+}
+
+impl MaxDepositRule {
+    pub fn new(limit: Decimal) -> Self {
+        Self { limit }
+    }
+}
+
+impl TransactionRule for MaxDepositRule {
+    fn name(&self) -> &str {
+        "max_deposit"
+    }
+
+    fn evaluate(&mut self, transaction: &Transaction, _context: &RuleContext) -> Option<String> {
+        match transaction.kind() {
+            TransactionKind::Deposit(amount) if amount > self.limit => Some(format!(
+                "Deposit of [{}] exceeds the maximum of [{}]",
+                amount, self.limit
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::dispute_window::LedgerPosition;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::rules::max_deposit_rule::MaxDepositRule;
+    use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+    #[test]
+    fn test_max_deposit() {
+        let mut rule = MaxDepositRule::new(dec!(100));
+        let context = RuleContext {
+            position: LedgerPosition::default(),
+            account: None,
+        };
+
+        assert_eq!(
+            rule.evaluate(&Transaction::new_deposit(1, 1, dec!(100)), &context),
+            None
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_withdrawal(2, 1, dec!(101)), &context),
+            None
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_deposit(3, 1, dec!(100.01)), &context),
+            Some("Deposit of [100.01] exceeds the maximum of [100]".to_string())
+        );
+    }
+}
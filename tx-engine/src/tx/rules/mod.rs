@@ -0,0 +1,5 @@
+pub mod blocked_client_rule;
+pub mod max_deposit_rule;
+pub mod open_disputes_rule;
+pub mod transaction_rule;
+pub mod withdrawal_velocity_rule;
@@ -0,0 +1,34 @@
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+/// Limits the number of disputes an account can have open at the same time.
+#[derive(Debug)]
+pub struct OpenDisputesRule {
+    max_open_disputes: usize,
+}
+
+impl OpenDisputesRule {
+    pub fn new(max_open_disputes: usize) -> Self {
+        Self { max_open_disputes }
+    }
+}
+
+impl TransactionRule for OpenDisputesRule {
+    fn name(&self) -> &str {
+        "max_open_disputes"
+    }
+
+    fn evaluate(&mut self, transaction: &Transaction, context: &RuleContext) -> Option<String> {
+        let open_disputes = context.account.map_or(0, |account| account.open_disputes());
+
+        match transaction.kind() {
+            TransactionKind::Dispute(_) if open_disputes >= self.max_open_disputes => {
+                Some(format!(
+                    "Account already has [{}] open disputes, the maximum is [{}]",
+                    open_disputes, self.max_open_disputes
+                ))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt::Debug;
+
+use crate::tx::engine::account::Account;
+use crate::tx::engine::dispute_window::LedgerPosition;
+use crate::tx::engine::id::{ClientId, TxId};
+use crate::tx::engine::transaction::Transaction;
+
+/// What happens to a transaction that violates a rule.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RuleAction {
+    /// The transaction is rejected with [`TxError::RuleViolation`].
+    ///
+    /// [`TxError::RuleViolation`]: crate::tx::engine::result::TxError::RuleViolation
+    Reject,
+    /// The transaction is executed, the violation is only recorded.
+    Flag,
+    /// The transaction is rejected and the account of its client is locked.
+    Lock,
+}
+
+/// The state a rule can base its decision on, besides the transaction itself.
+#[derive(Debug, Copy, Clone)]
+pub struct RuleContext<'a> {
+    /// The position the transaction is executed at.
+    pub position: LedgerPosition,
+    /// The account of the transaction's client, if it exists yet.
+    pub account: Option<&'a Account>,
+}
+
+/// A check the engine evaluates before a transaction reaches an account.
+pub trait TransactionRule: Debug + Send {
+    /// Name of the rule as recorded in [`RuleDecision`]s.
+    fn name(&self) -> &str;
+
+    /// Returns why the transaction violates the rule, or `None` if it doesn't.
+    fn evaluate(&mut self, transaction: &Transaction, context: &RuleContext) -> Option<String>;
+
+    /// Called after a transaction was executed successfully, allowing rules to keep track of
+    /// past transactions.
+    fn executed(&mut self, _transaction: &Transaction, _context: &RuleContext) {}
+
+    /// Called before a batch of transactions is executed, rules keeping track of past
+    /// transactions should remember their current state until [`TransactionRule::commit`] or
+    /// [`TransactionRule::rollback`] is called.
+    fn checkpoint(&mut self) {}
+
+    /// Called once all transactions of the batch were kept.
+    fn commit(&mut self) {}
+
+    /// Called if the transactions of the batch were rolled back, rules should return to the
+    /// state of the last checkpoint.
+    fn rollback(&mut self) {}
+}
+
+/// The outcome of evaluating a rule for a transaction, recorded by the engine for auditing.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleDecision {
+    pub tx_id: TxId,
+    pub client_id: ClientId,
+    pub rule: String,
+    /// The action configured for the rule, only taken if the rule was violated.
+    pub action: RuleAction,
+    /// Why the transaction violates the rule, `None` if it passed the rule.
+    pub reason: Option<String>,
+}
+
+impl RuleDecision {
+    pub fn is_violation(&self) -> bool {
+        self.reason.is_some()
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::transaction::{Transaction, TransactionKind};
+use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+
+/// Limits the number of withdrawals a client can make within a number of subsequently executed
+/// transactions (of any client).
+#[derive(Debug)]
+pub struct WithdrawalVelocityRule {
+    max_withdrawals: usize,
+    window: u64,
+    // sequence numbers of the recent withdrawals per client
+    withdrawals: HashMap<ClientId, VecDeque<u64>>,
+    // the withdrawals at the last checkpoint
+    checkpoint: Option<HashMap<ClientId, VecDeque<u64>>>,
+}
+
+impl WithdrawalVelocityRule {
+    pub fn new(max_withdrawals: usize, window: u64) -> Self {
+        Self {
+            max_withdrawals,
+            window,
+            withdrawals: HashMap::new(),
+            checkpoint: None,
+        }
+    }
+
+    fn recent_withdrawals(&mut self, client_id: ClientId, sequence: u64) -> &mut VecDeque<u64> {
+        let window = self.window;
+        let withdrawals = self.withdrawals.entry(client_id).or_default();
+
+        while withdrawals
+            .front()
+            .is_some_and(|withdrawn_at| sequence - withdrawn_at >= window)
+        {
+            withdrawals.pop_front();
+        }
+
+        withdrawals
+    }
+}
+
+impl TransactionRule for WithdrawalVelocityRule {
+    fn name(&self) -> &str {
+        "max_withdrawals"
+    }
+
+    fn evaluate(&mut self, transaction: &Transaction, context: &RuleContext) -> Option<String> {
+        if !matches!(transaction.kind(), TransactionKind::Withdrawal(_)) {
+            return None;
+        }
+
+        let (max_withdrawals, window) = (self.max_withdrawals, self.window);
+        let recent = self
+            .recent_withdrawals(transaction.client_id(), context.position.sequence)
+            .len();
+
+        (recent >= max_withdrawals).then(|| {
+            format!(
+                "Client already made [{}] withdrawals within the last [{}] transactions",
+                recent, window
+            )
+        })
+    }
+
+    fn executed(&mut self, transaction: &Transaction, context: &RuleContext) {
+        if matches!(transaction.kind(), TransactionKind::Withdrawal(_)) {
+            self.recent_withdrawals(transaction.client_id(), context.position.sequence)
+                .push_back(context.position.sequence);
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        self.checkpoint = Some(self.withdrawals.clone());
+    }
+
+    fn commit(&mut self) {
+        self.checkpoint = None;
+    }
+
+    fn rollback(&mut self) {
+        if let Some(withdrawals) = self.checkpoint.take() {
+            self.withdrawals = withdrawals;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::dispute_window::LedgerPosition;
+    use crate::tx::engine::transaction::Transaction;
+    use crate::tx::rules::transaction_rule::{RuleContext, TransactionRule};
+    use crate::tx::rules::withdrawal_velocity_rule::WithdrawalVelocityRule;
+
+    fn context(sequence: u64) -> RuleContext<'static> {
+        RuleContext {
+            position: LedgerPosition {
+                sequence,
+                timestamp: None,
+            },
+            account: None,
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_velocity() {
+        let mut rule = WithdrawalVelocityRule::new(2, 10);
+
+        for (sequence, tx_id) in [(0, 1), (5, 2)] {
+            let withdrawal = Transaction::new_withdrawal(tx_id, 1, dec!(1));
+            assert_eq!(rule.evaluate(&withdrawal, &context(sequence)), None);
+            rule.executed(&withdrawal, &context(sequence));
+        }
+
+        assert_eq!(
+            rule.evaluate(&Transaction::new_withdrawal(3, 1, dec!(1)), &context(9)),
+            Some(
+                "Client already made [2] withdrawals within the last [10] transactions".to_string()
+            )
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_withdrawal(3, 2, dec!(1)), &context(9)),
+            None
+        );
+        assert_eq!(
+            rule.evaluate(&Transaction::new_withdrawal(3, 1, dec!(1)), &context(10)),
+            None
+        );
+
+        // withdrawals of a rolled back batch are forgotten
+        rule.checkpoint();
+        for (sequence, tx_id) in [(11, 4), (12, 5)] {
+            rule.executed(
+                &Transaction::new_withdrawal(tx_id, 2, dec!(1)),
+                &context(sequence),
+            );
+        }
+        rule.rollback();
+        assert_eq!(
+            rule.evaluate(&Transaction::new_withdrawal(6, 2, dec!(1)), &context(13)),
+            None
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::id::{ClientId, Id, IdKind};
+use crate::tx::engine::result::{TxError, TxResult};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClientTier {
+    pub client_id: ClientId,
+    pub tier: String,
+}
+
+/// Reads the fee tiers of clients from a CSV file with the columns `client` and `tier`.
+pub struct CsvClientTierSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    id_kind: IdKind,
+    client_index: usize,
+    tier_index: usize,
+}
+
+impl<R> CsvClientTierSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut client_index = None;
+        let mut tier_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "client" => client_index = Some(i),
+                "tier" => tier_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            id_kind: IdKind::default(),
+            client_index: client_index.ok_or(Self::error_missing_column("client"))?,
+            tier_index: tier_index.ok_or(Self::error_missing_column("tier"))?,
+        })
+    }
+
+    /// The kinds of client ids accepted, only integers by default.
+    pub fn with_id_kind(mut self, id_kind: IdKind) -> Self {
+        self.id_kind = id_kind;
+        self
+    }
+
+    /// Reads the next client tier, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<ClientTier>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        let client_str = csv_record
+            .get(self.client_index)
+            .ok_or(self.missing_value_error("client"))?;
+        let tier_str = csv_record
+            .get(self.tier_index)
+            .ok_or(self.missing_value_error("tier"))?;
+
+        let client_id = Id::parse_as(client_str, self.id_kind)
+            .map_err(|e| self.invalid_value_error("client", client_str, e))?;
+
+        if tier_str.trim().is_empty() {
+            return Err(self.missing_value_error("tier"));
+        }
+
+        Ok(Some(ClientTier {
+            client_id,
+            tier: tier_str.trim().to_string(),
+        }))
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::sources::csv_client_tier_source::{ClientTier, CsvClientTierSource};
+
+    #[tokio::test]
+    async fn test_can_read_client_tiers() {
+        let mut source = CsvClientTierSource::from_reader("client, tier\n1, gold\n".as_bytes())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(ClientTier {
+                client_id: 1.into(),
+                tier: "gold".to_string(),
+            })
+        );
+        assert_eq!(source.read().await.unwrap(), None);
+    }
+}
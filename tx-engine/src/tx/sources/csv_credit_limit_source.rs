@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::id::{ClientId, Id, IdKind};
+use crate::tx::engine::result::{TxError, TxResult};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CreditLimit {
+    pub client_id: ClientId,
+    pub limit: Decimal,
+}
+
+/// Reads per client credit limits from a CSV file with the columns `client` and `credit_limit`.
+pub struct CsvCreditLimitSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    id_kind: IdKind,
+    client_index: usize,
+    limit_index: usize,
+}
+
+impl<R> CsvCreditLimitSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut client_index = None;
+        let mut limit_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "client" => client_index = Some(i),
+                "credit_limit" => limit_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            id_kind: IdKind::default(),
+            client_index: client_index.ok_or(Self::error_missing_column("client"))?,
+            limit_index: limit_index.ok_or(Self::error_missing_column("credit_limit"))?,
+        })
+    }
+
+    /// The kinds of client ids accepted, only integers by default.
+    pub fn with_id_kind(mut self, id_kind: IdKind) -> Self {
+        self.id_kind = id_kind;
+        self
+    }
+
+    /// Reads the next credit limit, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<CreditLimit>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        let client_str = csv_record
+            .get(self.client_index)
+            .ok_or(self.missing_value_error("client"))?;
+        let limit_str = csv_record
+            .get(self.limit_index)
+            .ok_or(self.missing_value_error("credit_limit"))?;
+
+        let client_id = Id::parse_as(client_str, self.id_kind)
+            .map_err(|e| self.invalid_value_error("client", client_str, e))?;
+        let limit = Decimal::from_str_exact(limit_str.trim())
+            .map_err(|e| self.invalid_value_error("credit_limit", limit_str, e))?;
+
+        if limit < dec!(0) {
+            return Err(self.invalid_value_error(
+                "credit_limit",
+                limit_str,
+                "Negative values are not allowed",
+            ));
+        }
+
+        Ok(Some(CreditLimit { client_id, limit }))
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::sources::csv_credit_limit_source::{CreditLimit, CsvCreditLimitSource};
+
+    #[tokio::test]
+    async fn test_can_read_credit_limits() {
+        let mut source =
+            CsvCreditLimitSource::from_reader("client, credit_limit\n1, 100\n 7 ,0.5\n".as_bytes())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(CreditLimit {
+                client_id: 1.into(),
+                limit: dec!(100),
+            })
+        );
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(CreditLimit {
+                client_id: 7.into(),
+                limit: dec!(0.5),
+            })
+        );
+        assert_eq!(source.read().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_negative_credit_limits() {
+        let mut source =
+            CsvCreditLimitSource::from_reader("client,credit_limit\n1,-1\n".as_bytes())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            format!("{:?}", source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [-1] for column [credit_limit]: Negative values are not allowed (line: 3, byte: 25, record: 2).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requires_columns() {
+        let error = CsvCreditLimitSource::from_reader("client,limit\n".as_bytes())
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            format!("{:?}", error),
+            "InvalidArgument(\"Expected a column named [credit_limit].\")"
+        );
+    }
+}
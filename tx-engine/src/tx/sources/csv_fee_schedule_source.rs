@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::fee_schedule::{Fee, FeeKind};
+use crate::tx::engine::result::{TxError, TxResult};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FeeScheduleEntry {
+    pub kind: FeeKind,
+    /// The client tier the fee applies to, `None` for the default fee of the kind.
+    pub tier: Option<String>,
+    pub fee: Fee,
+}
+
+/// Reads fees from a CSV file with the columns `kind` (`withdrawal`, `transfer` or
+/// `chargeback`), `tier` (empty for the default fee), `flat` and `percentage`.
+pub struct CsvFeeScheduleSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    kind_index: usize,
+    tier_index: usize,
+    flat_index: usize,
+    percentage_index: usize,
+}
+
+impl<R> CsvFeeScheduleSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut kind_index = None;
+        let mut tier_index = None;
+        let mut flat_index = None;
+        let mut percentage_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "kind" => kind_index = Some(i),
+                "tier" => tier_index = Some(i),
+                "flat" => flat_index = Some(i),
+                "percentage" => percentage_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            kind_index: kind_index.ok_or(Self::error_missing_column("kind"))?,
+            tier_index: tier_index.ok_or(Self::error_missing_column("tier"))?,
+            flat_index: flat_index.ok_or(Self::error_missing_column("flat"))?,
+            percentage_index: percentage_index.ok_or(Self::error_missing_column("percentage"))?,
+        })
+    }
+
+    /// Reads the next fee, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<FeeScheduleEntry>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        let kind_str = csv_record
+            .get(self.kind_index)
+            .ok_or(self.missing_value_error("kind"))?;
+        let tier_str = csv_record
+            .get(self.tier_index)
+            .ok_or(self.missing_value_error("tier"))?;
+
+        let kind = match kind_str.trim().to_lowercase().as_str() {
+            "withdrawal" => FeeKind::Withdrawal,
+            "transfer" => FeeKind::Transfer,
+            "chargeback" => FeeKind::Chargeback,
+            _ => {
+                return Err(self.invalid_value_error("kind", kind_str, "Unknown fee kind"));
+            }
+        };
+        let tier = Some(tier_str.trim())
+            .filter(|tier| !tier.is_empty())
+            .map(str::to_string);
+        let fee = Fee {
+            flat: self.parse_amount(&csv_record, self.flat_index, "flat")?,
+            percentage: self.parse_amount(&csv_record, self.percentage_index, "percentage")?,
+        };
+
+        Ok(Some(FeeScheduleEntry { kind, tier, fee }))
+    }
+
+    fn parse_amount(&self, record: &StringRecord, index: usize, column: &str) -> TxResult<Decimal> {
+        let value_str = record.get(index).ok_or(self.missing_value_error(column))?;
+        let value = Decimal::from_str_exact(value_str.trim())
+            .map_err(|e| self.invalid_value_error(column, value_str, e))?;
+
+        if value < dec!(0) {
+            return Err(self.invalid_value_error(
+                column,
+                value_str,
+                "Negative values are not allowed",
+            ));
+        }
+
+        Ok(value)
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::fee_schedule::{Fee, FeeKind};
+    use crate::tx::sources::csv_fee_schedule_source::{CsvFeeScheduleSource, FeeScheduleEntry};
+
+    #[tokio::test]
+    async fn test_can_read_fees() {
+        let mut source = CsvFeeScheduleSource::from_reader(
+            "kind,tier,flat,percentage\nwithdrawal,,0.5,1\n Chargeback , gold ,15,0\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(FeeScheduleEntry {
+                kind: FeeKind::Withdrawal,
+                tier: None,
+                fee: Fee {
+                    flat: dec!(0.5),
+                    percentage: dec!(1),
+                },
+            })
+        );
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(FeeScheduleEntry {
+                kind: FeeKind::Chargeback,
+                tier: Some("gold".to_string()),
+                fee: Fee {
+                    flat: dec!(15),
+                    percentage: dec!(0),
+                },
+            })
+        );
+        assert_eq!(source.read().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_kinds() {
+        let mut source = CsvFeeScheduleSource::from_reader(
+            "kind,tier,flat,percentage\ndeposit,,1,0\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            format!("{:?}", source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [deposit] for column [kind]: Unknown fee kind (line: 3, byte: 39, record: 2).\")"
+        );
+    }
+}
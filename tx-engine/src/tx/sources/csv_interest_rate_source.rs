@@ -0,0 +1,175 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::interest::InterestRate;
+use crate::tx::engine::result::{TxError, TxResult};
+
+/// Reads an interest rate table from a CSV file with the columns `minimum_balance` and `rate`,
+/// the latter being the annual rate in percent.
+pub struct CsvInterestRateSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    minimum_balance_index: usize,
+    rate_index: usize,
+}
+
+impl<R> CsvInterestRateSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut minimum_balance_index = None;
+        let mut rate_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "minimum_balance" => minimum_balance_index = Some(i),
+                "rate" => rate_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            minimum_balance_index: minimum_balance_index
+                .ok_or(Self::error_missing_column("minimum_balance"))?,
+            rate_index: rate_index.ok_or(Self::error_missing_column("rate"))?,
+        })
+    }
+
+    /// Reads the next interest rate, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<InterestRate>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(InterestRate {
+            minimum_balance: self.parse_decimal(
+                &csv_record,
+                self.minimum_balance_index,
+                "minimum_balance",
+            )?,
+            annual_percentage: self.parse_decimal(&csv_record, self.rate_index, "rate")?,
+        }))
+    }
+
+    fn parse_decimal(
+        &self,
+        record: &StringRecord,
+        index: usize,
+        column: &str,
+    ) -> TxResult<Decimal> {
+        let value_str = record.get(index).ok_or(self.missing_value_error(column))?;
+        let value = Decimal::from_str_exact(value_str.trim())
+            .map_err(|e| self.invalid_value_error(column, value_str, e))?;
+
+        if value < dec!(0) {
+            return Err(self.invalid_value_error(
+                column,
+                value_str,
+                "Negative values are not allowed",
+            ));
+        }
+
+        Ok(value)
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::tx::engine::interest::InterestRate;
+    use crate::tx::sources::csv_interest_rate_source::CsvInterestRateSource;
+
+    #[tokio::test]
+    async fn test_can_read_interest_rates() {
+        let mut source = CsvInterestRateSource::from_reader(
+            "minimum_balance, rate\n0, 1.5\n10000,2\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(InterestRate {
+                minimum_balance: dec!(0),
+                annual_percentage: dec!(1.5),
+            })
+        );
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(InterestRate {
+                minimum_balance: dec!(10000),
+                annual_percentage: dec!(2),
+            })
+        );
+        assert_eq!(source.read().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_negative_rates() {
+        let mut source =
+            CsvInterestRateSource::from_reader("minimum_balance,rate\n0,-1\n".as_bytes())
+                .await
+                .unwrap();
+
+        assert!(source.read().await.is_err());
+    }
+}
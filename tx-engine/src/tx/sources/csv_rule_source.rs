@@ -0,0 +1,226 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use rust_decimal::Decimal;
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::id::ClientId;
+use crate::tx::engine::result::{TxError, TxResult};
+use crate::tx::rules::blocked_client_rule::BlockedClientRule;
+use crate::tx::rules::max_deposit_rule::MaxDepositRule;
+use crate::tx::rules::open_disputes_rule::OpenDisputesRule;
+use crate::tx::rules::transaction_rule::{RuleAction, TransactionRule};
+use crate::tx::rules::withdrawal_velocity_rule::WithdrawalVelocityRule;
+
+#[derive(Debug)]
+pub struct ConfiguredRule {
+    pub rule: Box<dyn TransactionRule>,
+    pub action: RuleAction,
+}
+
+/// Reads rules from a CSV file with the columns `rule`, `limit`, `window` and `action`
+/// (`reject`, `flag` or `lock`). Supported rules are:
+///
+/// * `max_deposit` with the maximum amount of a single deposit as limit
+/// * `max_withdrawals` with the maximum number of withdrawals of a client within a window of
+///   subsequent transactions
+/// * `max_open_disputes` with the maximum number of open disputes of an account as limit
+/// * `blocked_client` with the id of the blocked client as limit
+pub struct CsvRuleSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    rule_index: usize,
+    limit_index: usize,
+    window_index: usize,
+    action_index: usize,
+}
+
+impl<R> CsvRuleSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut rule_index = None;
+        let mut limit_index = None;
+        let mut window_index = None;
+        let mut action_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "rule" => rule_index = Some(i),
+                "limit" => limit_index = Some(i),
+                "window" => window_index = Some(i),
+                "action" => action_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            rule_index: rule_index.ok_or(Self::error_missing_column("rule"))?,
+            limit_index: limit_index.ok_or(Self::error_missing_column("limit"))?,
+            window_index: window_index.ok_or(Self::error_missing_column("window"))?,
+            action_index: action_index.ok_or(Self::error_missing_column("action"))?,
+        })
+    }
+
+    /// Reads the next rule, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<ConfiguredRule>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        let rule_str = self.value(&csv_record, self.rule_index, "rule")?;
+        let limit_str = self.value(&csv_record, self.limit_index, "limit")?;
+        let action_str = self.value(&csv_record, self.action_index, "action")?;
+
+        let action = match action_str.trim().to_lowercase().as_str() {
+            "reject" => RuleAction::Reject,
+            "flag" => RuleAction::Flag,
+            "lock" => RuleAction::Lock,
+            _ => return Err(self.invalid_value_error("action", action_str, "Unknown action")),
+        };
+        let rule: Box<dyn TransactionRule> = match rule_str.trim().to_lowercase().as_str() {
+            "max_deposit" => Box::new(MaxDepositRule::new(
+                Decimal::from_str_exact(limit_str.trim())
+                    .map_err(|e| self.invalid_value_error("limit", limit_str, e))?,
+            )),
+            "max_withdrawals" => {
+                let window_str = self.value(&csv_record, self.window_index, "window")?;
+                Box::new(WithdrawalVelocityRule::new(
+                    self.parse_number("limit", limit_str)?,
+                    self.parse_number("window", window_str)?,
+                ))
+            }
+            "max_open_disputes" => Box::new(OpenDisputesRule::new(
+                self.parse_number("limit", limit_str)?,
+            )),
+            "blocked_client" => Box::new(BlockedClientRule::new(
+                self.parse_number::<ClientId>("limit", limit_str)?,
+            )),
+            _ => return Err(self.invalid_value_error("rule", rule_str, "Unknown rule")),
+        };
+
+        Ok(Some(ConfiguredRule { rule, action }))
+    }
+
+    fn value<'r>(&self, record: &'r StringRecord, index: usize, column: &str) -> TxResult<&'r str> {
+        record
+            .get(index)
+            .filter(|value| !value.trim().is_empty())
+            .ok_or(self.missing_value_error(column))
+    }
+
+    fn parse_number<T>(&self, column: &str, value: &str) -> TxResult<T>
+    where
+        T: std::str::FromStr,
+        T::Err: Display,
+    {
+        value
+            .trim()
+            .parse::<T>()
+            .map_err(|e| self.invalid_value_error(column, value, e))
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::rules::transaction_rule::RuleAction;
+    use crate::tx::sources::csv_rule_source::CsvRuleSource;
+
+    #[tokio::test]
+    async fn test_can_read_rules() {
+        let mut source = CsvRuleSource::from_reader(
+            "rule,limit,window,action\nmax_deposit,1000,,flag\nmax_withdrawals,3,100,reject\nmax_open_disputes,2,,reject\nblocked_client,7,,lock\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        let mut rules = Vec::new();
+        while let Some(rule) = source.read().await.unwrap() {
+            rules.push((rule.rule.name().to_string(), rule.action));
+        }
+
+        assert_eq!(
+            rules,
+            vec![
+                ("max_deposit".to_string(), RuleAction::Flag),
+                ("max_withdrawals".to_string(), RuleAction::Reject),
+                ("max_open_disputes".to_string(), RuleAction::Reject),
+                ("blocked_client".to_string(), RuleAction::Lock),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_rules() {
+        let mut source = CsvRuleSource::from_reader(
+            "rule,limit,window,action\nmax_withdrawals,3,,reject\nmax_amount,3,,reject\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            format!("{:?}", source.read().await.unwrap_err()),
+            "InvalidArgument(\"Expected a value for column [window] (line: 3, byte: 51, record: 2).\")"
+        );
+        assert_eq!(
+            format!("{:?}", source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [max_amount] for column [rule]: Unknown rule (line: 4, byte: 72, record: 3).\")"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use csv_async::{AsyncReader, StringRecord};
+use tokio::io::AsyncRead;
+
+use crate::tx::engine::id::{Id, IdKind, TenantId};
+use crate::tx::engine::result::{TxError, TxResult};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TenantConfig {
+    pub tenant_id: TenantId,
+    /// Path of the rules of the tenant, `None` for the default rules.
+    pub rules: Option<String>,
+    /// Path of the credit limits of the tenant, `None` for the default credit limits.
+    pub credit_limits: Option<String>,
+}
+
+/// Reads the files configuring each tenant from a CSV file with the columns `tenant`, `rules`
+/// and `credit_limits`, the latter two empty for the defaults.
+pub struct CsvTenantConfigSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    reader: AsyncReader<R>,
+    tenant_index: usize,
+    rules_index: usize,
+    credit_limits_index: usize,
+}
+
+impl<R> CsvTenantConfigSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    pub async fn from_reader(source: R) -> TxResult<Self> {
+        let mut reader = AsyncReader::from_reader(source);
+        let headers = reader
+            .headers()
+            .await
+            .map_err(|e| TxError::IoError(format!("Unable to read CSV headers: {}", e)))?;
+        let mut tenant_index = None;
+        let mut rules_index = None;
+        let mut credit_limits_index = None;
+
+        for (i, header) in headers.iter().enumerate() {
+            match header.trim().to_lowercase().as_str() {
+                "tenant" => tenant_index = Some(i),
+                "rules" => rules_index = Some(i),
+                "credit_limits" => credit_limits_index = Some(i),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            reader,
+            tenant_index: tenant_index.ok_or(Self::error_missing_column("tenant"))?,
+            rules_index: rules_index.ok_or(Self::error_missing_column("rules"))?,
+            credit_limits_index: credit_limits_index
+                .ok_or(Self::error_missing_column("credit_limits"))?,
+        })
+    }
+
+    /// Reads the config of the next tenant, `None` is returned once all records were read.
+    pub async fn read(&mut self) -> TxResult<Option<TenantConfig>> {
+        let mut csv_record = StringRecord::new();
+        if !self
+            .reader
+            .read_record(&mut csv_record)
+            .await
+            .map_err(|e| self.io_error(e))?
+        {
+            return Ok(None);
+        }
+
+        let tenant_str = csv_record
+            .get(self.tenant_index)
+            .ok_or(self.missing_value_error("tenant"))?;
+        let rules_str = csv_record
+            .get(self.rules_index)
+            .ok_or(self.missing_value_error("rules"))?;
+        let credit_limits_str = csv_record
+            .get(self.credit_limits_index)
+            .ok_or(self.missing_value_error("credit_limits"))?;
+
+        let tenant_id = Id::parse_as(tenant_str, IdKind::Mixed)
+            .map_err(|e| self.invalid_value_error("tenant", tenant_str, e))?;
+        let path = |value: &str| {
+            Some(value.trim())
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+        };
+
+        Ok(Some(TenantConfig {
+            tenant_id,
+            rules: path(rules_str),
+            credit_limits: path(credit_limits_str),
+        }))
+    }
+
+    fn error_missing_column(column: &str) -> TxError {
+        TxError::InvalidArgument(format!("Expected a column named [{}].", column))
+    }
+
+    fn missing_value_error(&self, column: &str) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Expected a value for column [{}] ({}).",
+            column,
+            self.position_to_string()
+        ))
+    }
+
+    fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
+        TxError::InvalidArgument(format!(
+            "Could not parse value [{}] for column [{}]: {} ({}).",
+            value,
+            column,
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn io_error<E: Display>(&self, error: E) -> TxError {
+        TxError::IoError(format!(
+            "Unexpected I/O error while reading CSV record: {} ({}).",
+            error,
+            self.position_to_string()
+        ))
+    }
+
+    fn position_to_string(&self) -> String {
+        format!(
+            "line: {}, byte: {}, record: {}",
+            self.reader.position().line(),
+            self.reader.position().byte(),
+            self.reader.position().record()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tx::sources::csv_tenant_config_source::{CsvTenantConfigSource, TenantConfig};
+
+    #[tokio::test]
+    async fn test_can_read_tenant_configs() {
+        let mut source = CsvTenantConfigSource::from_reader(
+            "tenant,rules,credit_limits\nacme, acme/rules.csv ,\n7,,limits.csv\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(TenantConfig {
+                tenant_id: "acme".parse().unwrap(),
+                rules: Some("acme/rules.csv".to_string()),
+                credit_limits: None,
+            })
+        );
+        assert_eq!(
+            source.read().await.unwrap(),
+            Some(TenantConfig {
+                tenant_id: 7.into(),
+                rules: None,
+                credit_limits: Some("limits.csv".to_string()),
+            })
+        );
+        assert_eq!(source.read().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_requires_columns() {
+        let error = CsvTenantConfigSource::from_reader("tenant,rules\n".as_bytes())
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            format!("{:?}", error),
+            "InvalidArgument(\"Expected a column named [credit_limits].\")"
+        );
+    }
+}
@@ -1,13 +1,20 @@
 use std::fmt::Display;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use csv_async::{AsyncReader, StringRecord};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use tokio::io::AsyncRead;
+use tracing::field::Empty;
+use tracing::{trace, trace_span, warn, Instrument};
 
+use crate::tx::engine::amount_policy::AmountPolicy;
+use crate::tx::engine::business_date::BusinessDate;
+use crate::tx::engine::id::{ClientId, Id, IdKind, TenantId, TxId};
 use crate::tx::engine::result::{TxError, TxResult};
 use crate::tx::engine::transaction::Transaction;
+use crate::tx::metrics::metrics::Metrics;
 use crate::tx::sources::transaction_source::TransactionSource;
 
 pub struct CsvTransactionSource<R>
@@ -16,6 +23,10 @@ where
 {
     reader: AsyncReader<R>,
     indices: CsvHeaderIndices,
+    amount_policy: AmountPolicy,
+    metrics: Option<Metrics>,
+    default_tenant: Option<TenantId>,
+    id_kind: IdKind,
 }
 
 struct CsvHeaderIndices {
@@ -23,6 +34,10 @@ struct CsvHeaderIndices {
     pub tx_index: usize,
     pub client_index: usize,
     pub amount_index: usize,
+    pub timestamp_index: Option<usize>,
+    pub to_client_index: Option<usize>,
+    pub value_date_index: Option<usize>,
+    pub tenant_index: Option<usize>,
 }
 
 impl<R> CsvTransactionSource<R>
@@ -36,6 +51,10 @@ where
         let mut client_index = None;
         let mut tx_index = None;
         let mut amount_index = None;
+        let mut timestamp_index = None;
+        let mut to_client_index = None;
+        let mut value_date_index = None;
+        let mut tenant_index = None;
 
         for i in 0..headers.len() {
             if let Some(header) = headers.get(i) {
@@ -44,6 +63,10 @@ where
                     "client" => client_index = Some(i),
                     "tx" => tx_index = Some(i),
                     "amount" => amount_index = Some(i),
+                    "timestamp" => timestamp_index = Some(i),
+                    "to_client" => to_client_index = Some(i),
+                    "value_date" => value_date_index = Some(i),
+                    "tenant" => tenant_index = Some(i),
                     _ => {}
                 }
             }
@@ -54,9 +77,51 @@ where
             tx_index: tx_index.ok_or(Self::error_missing_column("tx"))?,
             client_index: client_index.ok_or(Self::error_missing_column("client"))?,
             amount_index: amount_index.ok_or(Self::error_missing_column("amount"))?,
+            timestamp_index,
+            to_client_index,
+            value_date_index,
+            tenant_index,
         };
 
-        Ok(Self { reader, indices })
+        Ok(Self {
+            reader,
+            indices,
+            amount_policy: AmountPolicy::default(),
+            metrics: None,
+            default_tenant: None,
+            id_kind: IdKind::default(),
+        })
+    }
+
+    /// Validates all amounts against the policy, amounts it rejects fail to parse.
+    pub fn with_amount_policy(mut self, amount_policy: AmountPolicy) -> Self {
+        self.amount_policy = amount_policy;
+        self
+    }
+
+    /// The kinds of client and transaction ids accepted, only integers by default. Tenants may
+    /// always use any kind of id.
+    pub fn with_id_kind(mut self, id_kind: IdKind) -> Self {
+        self.id_kind = id_kind;
+        self
+    }
+
+    /// The tenant of records without a value in the tenant column, or of all records if there is
+    /// no such column. See [`CsvTransactionSource::read_with_tenant`].
+    pub fn with_default_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.default_tenant = Some(tenant_id);
+        self
+    }
+
+    /// Whether the input has a column naming the tenant of each record.
+    pub fn has_tenant_column(&self) -> bool {
+        self.indices.tenant_index.is_some()
+    }
+
+    /// Records how long each record takes to read and parse.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     fn error_missing_column(column: &str) -> TxError {
@@ -71,33 +136,74 @@ where
         ))
     }
 
-    fn parse_tx_id(&self, value: &str) -> TxResult<u32> {
-        value
-            .trim()
-            .to_lowercase()
-            .as_str()
-            .parse::<u32>()
-            .map_err(|e| self.invalid_value_error("tx", value, e))
+    fn parse_tx_id(&self, value: &str) -> TxResult<TxId> {
+        Id::parse_as(value, self.id_kind).map_err(|e| self.invalid_value_error("tx", value, e))
     }
 
-    fn parse_client_id(&self, value: &str) -> TxResult<u16> {
-        value
-            .trim()
-            .to_lowercase()
-            .as_str()
-            .parse::<u16>()
-            .map_err(|e| self.invalid_value_error("client", value, e))
+    fn parse_client_id(&self, value: &str) -> TxResult<ClientId> {
+        self.parse_client_id_column("client", value)
+    }
+
+    fn parse_client_id_column(&self, column: &str, value: &str) -> TxResult<ClientId> {
+        Id::parse_as(value, self.id_kind).map_err(|e| self.invalid_value_error(column, value, e))
     }
 
     fn parse_amount(&self, value: &str) -> TxResult<Decimal> {
+        self.amount_policy
+            .apply(self.parse_non_negative(value)?)
+            .map_err(|e| self.invalid_value_error("amount", value, e))
+    }
+
+    /// Parses the amount of rows for which it is optional, i.e. disputes, chargebacks and
+    /// captures. These are partial amounts of a transaction, so the bounds of the amount policy
+    /// don't apply.
+    fn parse_optional_amount(&self, value: Option<&str>) -> TxResult<Option<Decimal>> {
+        match value {
+            Some(value) if !value.trim().is_empty() => self
+                .amount_policy
+                .apply_precision(self.parse_non_negative(value)?)
+                .map(Some)
+                .map_err(|e| self.invalid_value_error("amount", value, e)),
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_non_negative(&self, value: &str) -> TxResult<Decimal> {
         let amount = Decimal::from_str_exact(value.trim().to_lowercase().as_str())
             .map_err(|e| self.invalid_value_error("amount", value, e))?;
 
         if amount < dec!(0) {
-            Err(self.invalid_value_error("amount", value, "Negative values are not allowed"))
-        } else {
-            Ok(amount)
+            return Err(self.invalid_value_error(
+                "amount",
+                value,
+                "Negative values are not allowed",
+            ));
+        }
+
+        Ok(amount)
+    }
+
+    fn parse_timestamp(&self, value: &str) -> TxResult<Option<u64>> {
+        if value.trim().is_empty() {
+            return Ok(None);
         }
+
+        value
+            .trim()
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|e| self.invalid_value_error("timestamp", value, e))
+    }
+
+    fn parse_value_date(&self, value: &str) -> TxResult<Option<BusinessDate>> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+
+        value
+            .parse::<BusinessDate>()
+            .map(Some)
+            .map_err(|e| self.invalid_value_error("value_date", value, e))
     }
 
     fn invalid_value_error<E: Display>(&self, column: &str, value: &str, error: E) -> TxError {
@@ -128,12 +234,52 @@ where
     }
 }
 
-#[async_trait]
-impl<R> TransactionSource for CsvTransactionSource<R>
+impl<R> CsvTransactionSource<R>
 where
     R: AsyncRead + Unpin + Send,
 {
-    async fn read(&mut self) -> TxResult<Option<Transaction>> {
+    /// Reads a single transaction together with the tenant it belongs to, taken from the tenant
+    /// column or the default tenant. Fails if neither is given.
+    pub async fn read_with_tenant(&mut self) -> TxResult<Option<(TenantId, Transaction)>> {
+        match self.read_record().await? {
+            Some((Some(tenant_id), transaction)) => Ok(Some((tenant_id, transaction))),
+            Some((None, _)) => Err(self.missing_value_error("tenant")),
+            None => Ok(None),
+        }
+    }
+
+    async fn read_record(&mut self) -> TxResult<Option<(Option<TenantId>, Transaction)>> {
+        let span = trace_span!(
+            "read",
+            tenant = Empty,
+            client = Empty,
+            tx = Empty,
+            kind = Empty
+        );
+        let started = Instant::now();
+        let result = self.read_transaction().instrument(span.clone()).await;
+
+        match &result {
+            Ok(Some((tenant_id, transaction))) => {
+                if let Some(tenant_id) = tenant_id {
+                    span.record("tenant", tenant_id.as_field());
+                }
+                span.record("client", transaction.client_id().as_field());
+                span.record("tx", transaction.tx_id().as_field());
+                span.record("kind", transaction.kind().name());
+                trace!(parent: &span, "record parsed");
+            }
+            Ok(None) => return result,
+            Err(error) => warn!(parent: &span, error = ?error, "record rejected"),
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record_parse(started.elapsed(), result.is_err());
+        }
+
+        result
+    }
+
+    async fn read_transaction(&mut self) -> TxResult<Option<(Option<TenantId>, Transaction)>> {
         let mut csv_record: StringRecord = StringRecord::new();
         if !self
             .reader
@@ -154,37 +300,133 @@ where
             .get(self.indices.client_index)
             .ok_or(self.missing_value_error("client"))?;
         let amount_str = csv_record.get(self.indices.amount_index);
-        let tx_id = self.parse_tx_id(tx_id_str)?;
-        let client_id = self.parse_client_id(client_id_str)?;
-
-        match (kind_str.trim().to_lowercase().as_str(), amount_str) {
-            ("deposit", Some(amount_str)) => Ok(Some(Transaction::new_deposit(
-                tx_id,
-                client_id,
-                self.parse_amount(amount_str)?,
-            ))),
-            ("withdrawal", Some(amount_str)) => Ok(Some(Transaction::new_withdrawal(
-                tx_id,
-                client_id,
-                self.parse_amount(amount_str)?,
-            ))),
-            ("dispute", _) => Ok(Some(Transaction::new_dispute(tx_id, client_id))),
-            ("resolve", _) => Ok(Some(Transaction::new_resolve(tx_id, client_id))),
-            ("chargeback", _) => Ok(Some(Transaction::new_charge_back(tx_id, client_id))),
-            _ => Err(self.invalid_value_error("type", kind_str, "Unsupported value")),
+        let timestamp_str = self
+            .indices
+            .timestamp_index
+            .and_then(|index| csv_record.get(index));
+        let tenant_id = match self
+            .indices
+            .tenant_index
+            .and_then(|index| csv_record.get(index))
+            .filter(|value| !value.trim().is_empty())
+        {
+            Some(tenant_str) => Some(
+                Id::parse_as(tenant_str, IdKind::Mixed)
+                    .map_err(|e| self.invalid_value_error("tenant", tenant_str, e))?,
+            ),
+            None => self.default_tenant,
+        };
+        let value_date = match self
+            .indices
+            .value_date_index
+            .and_then(|index| csv_record.get(index))
+        {
+            Some(value_date_str) => self.parse_value_date(value_date_str)?,
+            None => None,
+        };
+
+        // business date markers only consist of the date, given in the value date column
+        if kind_str.trim().eq_ignore_ascii_case("business_date") {
+            let date = value_date.ok_or(self.missing_value_error("value_date"))?;
+            return Ok(Some((tenant_id, Transaction::new_business_date(date))));
         }
+
+        // only parsed for rows that need them, accrual rows leave the client empty and open rows
+        // the transaction
+        let tx_id = self.parse_tx_id(tx_id_str);
+        let client_id = self.parse_client_id(client_id_str);
+
+        let transaction = match (kind_str.trim().to_lowercase().as_str(), amount_str) {
+            ("deposit", Some(amount_str)) => {
+                Transaction::new_deposit(tx_id?, client_id?, self.parse_amount(amount_str)?)
+            }
+            ("withdrawal", Some(amount_str)) => {
+                Transaction::new_withdrawal(tx_id?, client_id?, self.parse_amount(amount_str)?)
+            }
+            ("transfer", Some(amount_str)) => {
+                let to_client_str = self
+                    .indices
+                    .to_client_index
+                    .and_then(|index| csv_record.get(index))
+                    .filter(|value| !value.trim().is_empty())
+                    .ok_or(self.missing_value_error("to_client"))?;
+
+                Transaction::new_transfer(
+                    tx_id?,
+                    client_id?,
+                    self.parse_client_id_column("to_client", to_client_str)?,
+                    self.parse_amount(amount_str)?,
+                )
+            }
+            ("dispute", amount_str) => match self.parse_optional_amount(amount_str)? {
+                Some(amount) => Transaction::new_partial_dispute(tx_id?, client_id?, amount),
+                None => Transaction::new_dispute(tx_id?, client_id?),
+            },
+            ("resolve", _) => Transaction::new_resolve(tx_id?, client_id?),
+            ("chargeback", amount_str) => match self.parse_optional_amount(amount_str)? {
+                Some(amount) => Transaction::new_partial_charge_back(tx_id?, client_id?, amount),
+                None => Transaction::new_charge_back(tx_id?, client_id?),
+            },
+            ("representment" | "chargeback_reversal", _) => {
+                Transaction::new_representment(tx_id?, client_id?)
+            }
+            ("authorize", Some(amount_str)) => {
+                Transaction::new_authorization(tx_id?, client_id?, self.parse_amount(amount_str)?)
+            }
+            ("capture", amount_str) => match self.parse_optional_amount(amount_str)? {
+                Some(amount) => Transaction::new_partial_capture(tx_id?, client_id?, amount),
+                None => Transaction::new_capture(tx_id?, client_id?),
+            },
+            ("release", _) => Transaction::new_release(tx_id?, client_id?),
+            ("open", _) => Transaction::new_opening(client_id?),
+            ("close", _) => Transaction::new_closing(tx_id?, client_id?),
+            ("cancel", _) => Transaction::new_cancellation(tx_id?, client_id?),
+            ("accrual", _) => Transaction::new_interest_accrual(tx_id?),
+            _ => return Err(self.invalid_value_error("type", kind_str, "Unsupported value")),
+        };
+
+        let transaction = match value_date {
+            Some(value_date) => transaction.with_value_date(value_date),
+            None => transaction,
+        };
+
+        let transaction = match timestamp_str {
+            Some(timestamp_str) => match self.parse_timestamp(timestamp_str)? {
+                Some(timestamp) => transaction.with_timestamp(timestamp),
+                None => transaction,
+            },
+            None => transaction,
+        };
+
+        Ok(Some((tenant_id, transaction)))
+    }
+}
+
+#[async_trait]
+impl<R> TransactionSource for CsvTransactionSource<R>
+where
+    R: AsyncRead + Unpin + Send,
+{
+    async fn read(&mut self) -> TxResult<Option<Transaction>> {
+        Ok(self
+            .read_record()
+            .await?
+            .map(|(_, transaction)| transaction))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use rstest::*;
-    use rust_decimal::Decimal;
+    use rust_decimal::{Decimal, RoundingStrategy};
     use rust_decimal_macros::dec;
     use tokio::fs::File;
 
     use crate::test_resource_path;
+    use crate::tx::engine::amount_policy::{AmountPolicy, ExcessPrecision};
+    use crate::tx::engine::id::IdKind;
     use crate::tx::engine::transaction::Transaction;
+    use crate::tx::metrics::metrics::Metrics;
     use crate::tx::sources::csv_transaction_source::CsvTransactionSource;
     use crate::tx::sources::transaction_source::TransactionSource;
 
@@ -273,34 +515,52 @@ mod tests {
     }
 
     #[rstest]
-    #[case("0", 0)]
-    #[case(" 1", 1)]
-    #[case("    83     ", 83)]
-    #[case(" +2 ", 2)]
-    #[case(" 4294967295 ", 4294967295)]
+    #[case("0", IdKind::Numeric, "0")]
+    #[case(" 1", IdKind::Numeric, "1")]
+    #[case("    83     ", IdKind::Numeric, "83")]
+    #[case(" +2 ", IdKind::Numeric, "2")]
+    #[case(" 007 ", IdKind::Numeric, "7")]
+    #[case(" 18446744073709551615 ", IdKind::Numeric, "18446744073709551615")]
+    #[case("007", IdKind::Mixed, "7")]
+    #[case("ACME-tx_42", IdKind::Mixed, "ACME-tx_42")]
+    #[case(
+        "67E55044-10B1-426F-9247-BB680E5FE0C8",
+        IdKind::Mixed,
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    )]
     #[tokio::test]
-    async fn test_parse_tx_id_success(#[case] given_value: &str, #[case] expected_result: u32) {
+    async fn test_parse_tx_id_success(
+        #[case] given_value: &str,
+        #[case] id_kind: IdKind,
+        #[case] expected_result: &str,
+    ) {
         let csv_source = CsvTransactionSource::from_reader("type,tx,client,amount".as_bytes())
             .await
-            .unwrap();
+            .unwrap()
+            .with_id_kind(id_kind);
 
         assert_eq!(
-            csv_source.parse_tx_id(given_value).unwrap(),
+            csv_source.parse_tx_id(given_value).unwrap().to_string(),
             expected_result
         );
     }
 
     #[rstest]
-    #[case("0.0", "InvalidArgument(\"Could not parse value [0.0] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case("hello", "InvalidArgument(\"Could not parse value [hello] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case(" -1 ", "InvalidArgument(\"Could not parse value [ -1 ] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case(" 4294967296 ", "InvalidArgument(\"Could not parse value [ 4294967296 ] for column [tx]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
+    #[case("0.0", IdKind::Numeric, "InvalidArgument(\"Could not parse value [0.0] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case("hello", IdKind::Numeric, "InvalidArgument(\"Could not parse value [hello] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case("1O", IdKind::Numeric, "InvalidArgument(\"Could not parse value [1O] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case(" -1 ", IdKind::Numeric, "InvalidArgument(\"Could not parse value [ -1 ] for column [tx]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case(" 18446744073709551616 ", IdKind::Numeric, "InvalidArgument(\"Could not parse value [ 18446744073709551616 ] for column [tx]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
+    #[case("hello world", IdKind::Mixed, "InvalidArgument(\"Could not parse value [hello world] for column [tx]: Expected an integer, a UUID or up to [32] letters, digits, '-' or '_' (line: 1, byte: 21, record: 1).\")")]
+    #[case(" -1 ", IdKind::Mixed, "InvalidArgument(\"Could not parse value [ -1 ] for column [tx]: Expected an integer, a UUID or up to [32] letters, digits, '-' or '_' (line: 1, byte: 21, record: 1).\")")]
+    #[case(" 123456789012345678901234567890123 ", IdKind::Mixed, "InvalidArgument(\"Could not parse value [ 123456789012345678901234567890123 ] for column [tx]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
     #[tokio::test]
     async fn test_parse_tx_id_failures(
         #[case] given_value: &str,
+        #[case] id_kind: IdKind,
         #[case] expected_error_message: &str,
     ) {
-        let csv_source = create_empty_csv_source().await;
+        let csv_source = create_empty_csv_source().await.with_id_kind(id_kind);
         let actual_error_message =
             format!("{:?}", csv_source.parse_tx_id(given_value).unwrap_err());
 
@@ -308,34 +568,52 @@ mod tests {
     }
 
     #[rstest]
-    #[case("0", 0)]
-    #[case(" 1", 1)]
-    #[case("    83     ", 83)]
-    #[case(" +2 ", 2)]
-    #[case(" 65535 ", 65535)]
+    #[case("0", IdKind::Numeric, "0")]
+    #[case(" 1", IdKind::Numeric, "1")]
+    #[case("    83     ", IdKind::Numeric, "83")]
+    #[case(" +2 ", IdKind::Numeric, "2")]
+    #[case(" 007 ", IdKind::Numeric, "7")]
+    #[case(" 18446744073709551615 ", IdKind::Numeric, "18446744073709551615")]
+    #[case("007", IdKind::Mixed, "7")]
+    #[case("ACME-client_42", IdKind::Mixed, "ACME-client_42")]
+    #[case(
+        "67E55044-10B1-426F-9247-BB680E5FE0C8",
+        IdKind::Mixed,
+        "67e55044-10b1-426f-9247-bb680e5fe0c8"
+    )]
     #[tokio::test]
-    async fn test_parse_client_id_success(#[case] given_value: &str, #[case] expected_result: u16) {
+    async fn test_parse_client_id_success(
+        #[case] given_value: &str,
+        #[case] id_kind: IdKind,
+        #[case] expected_result: &str,
+    ) {
         let csv_source = CsvTransactionSource::from_reader("type,tx,client,amount".as_bytes())
             .await
-            .unwrap();
+            .unwrap()
+            .with_id_kind(id_kind);
 
         assert_eq!(
-            csv_source.parse_client_id(given_value).unwrap(),
+            csv_source.parse_client_id(given_value).unwrap().to_string(),
             expected_result
         );
     }
 
     #[rstest]
-    #[case("0.0", "InvalidArgument(\"Could not parse value [0.0] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case("hello", "InvalidArgument(\"Could not parse value [hello] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case(" -1 ", "InvalidArgument(\"Could not parse value [ -1 ] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
-    #[case(" 65536 ", "InvalidArgument(\"Could not parse value [ 65536 ] for column [client]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
+    #[case("0.0", IdKind::Numeric, "InvalidArgument(\"Could not parse value [0.0] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case("hello", IdKind::Numeric, "InvalidArgument(\"Could not parse value [hello] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case("1O", IdKind::Numeric, "InvalidArgument(\"Could not parse value [1O] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case(" -1 ", IdKind::Numeric, "InvalidArgument(\"Could not parse value [ -1 ] for column [client]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case(" 18446744073709551616 ", IdKind::Numeric, "InvalidArgument(\"Could not parse value [ 18446744073709551616 ] for column [client]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
+    #[case("hello world", IdKind::Mixed, "InvalidArgument(\"Could not parse value [hello world] for column [client]: Expected an integer, a UUID or up to [32] letters, digits, '-' or '_' (line: 1, byte: 21, record: 1).\")")]
+    #[case(" -1 ", IdKind::Mixed, "InvalidArgument(\"Could not parse value [ -1 ] for column [client]: Expected an integer, a UUID or up to [32] letters, digits, '-' or '_' (line: 1, byte: 21, record: 1).\")")]
+    #[case(" 123456789012345678901234567890123 ", IdKind::Mixed, "InvalidArgument(\"Could not parse value [ 123456789012345678901234567890123 ] for column [client]: number too large to fit in target type (line: 1, byte: 21, record: 1).\")")]
     #[tokio::test]
     async fn test_parse_client_id_failures(
         #[case] given_value: &str,
+        #[case] id_kind: IdKind,
         #[case] expected_error_message: &str,
     ) {
-        let csv_source = create_empty_csv_source().await;
+        let csv_source = create_empty_csv_source().await.with_id_kind(id_kind);
         let actual_error_message =
             format!("{:?}", csv_source.parse_client_id(given_value).unwrap_err());
 
@@ -380,6 +658,323 @@ mod tests {
         assert_eq!(actual_error_message, expected_error_message);
     }
 
+    #[tokio::test]
+    async fn test_amounts_are_validated_against_policy() {
+        let policy = AmountPolicy {
+            max_decimal_places: Some(4),
+            excess_precision: ExcessPrecision::Round(RoundingStrategy::MidpointNearestEven),
+            min_amount: Some(dec!(1)),
+            max_amount: Some(dec!(1000000)),
+            reject_zero: true,
+        };
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\ndeposit,1,1,1.23456\ndispute,1,1,0.00001\nwithdrawal,1,2,1792837984777619.5189873\nchargeback,1,1,0.50001\ndeposit,1,3,0.5\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap()
+        .with_amount_policy(policy);
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_deposit(1, 1, dec!(1.2346))
+        );
+        // partial amounts are rounded, but not checked against the bounds
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_partial_dispute(1, 1, dec!(0.0000))
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [1792837984777619.5189873] for column [amount]: Expected an amount of at most [1000000] (line: 5, byte: 102, record: 4).\")"
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_partial_charge_back(1, 1, dec!(0.5000))
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [0.5] for column [amount]: Expected an amount of at least [1] (line: 7, byte: 141, record: 6).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_tenants() {
+        let input = "type,tenant,client,tx,amount\ndeposit,acme,1,1,1.0\ndeposit,,1,1,2.0\n";
+        let acme = "acme".parse().unwrap();
+        let globex = "globex".parse().unwrap();
+
+        let mut csv_source = CsvTransactionSource::from_reader(input.as_bytes())
+            .await
+            .unwrap()
+            .with_default_tenant(globex);
+        assert!(csv_source.has_tenant_column());
+        assert_eq!(
+            csv_source.read_with_tenant().await.unwrap().unwrap(),
+            (acme, Transaction::new_deposit(1, 1, dec!(1.0)))
+        );
+        assert_eq!(
+            csv_source.read_with_tenant().await.unwrap().unwrap(),
+            (globex, Transaction::new_deposit(1, 1, dec!(2.0)))
+        );
+
+        let mut csv_source = CsvTransactionSource::from_reader(input.as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_deposit(1, 1, dec!(1.0))
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read_with_tenant().await.unwrap_err()),
+            "InvalidArgument(\"Expected a value for column [tenant] (line: 4, byte: 67, record: 3).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parsing_is_recorded_in_metrics() {
+        let metrics = Metrics::new();
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\ndeposit,1,1,1.0\nwithdrawal,1,2,abc\n".as_bytes(),
+        )
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+
+        assert!(csv_source.read().await.unwrap().is_some());
+        assert!(csv_source.read().await.is_err());
+        assert!(csv_source.read().await.unwrap().is_none());
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("tx_parse_errors_total 1\n"));
+        assert!(rendered.contains("tx_parse_duration_seconds_count 2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_optional_timestamps() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount,timestamp\ndeposit,1,1,1.0,1718000000\ndispute,1,1,,\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_deposit(1, 1, dec!(1.0)).with_timestamp(1718000000)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_dispute(1, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_transfers() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount,to_client\ntransfer,1,1,12.5,2\ndeposit,1,2,1,\ntransfer,1,3,1,\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_transfer(1, 1, 2, dec!(12.5))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_deposit(2, 1, dec!(1))
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Expected a value for column [to_client] (line: 5, byte: 83, record: 4).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_accrual_markers() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\naccrual,,100,\ndeposit,,1,1\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_interest_accrual(100)
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [] for column [client]: cannot parse integer from empty string (line: 4, byte: 49, record: 3).\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_optional_dispute_amounts() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\ndispute,1,1,12.5\nchargeback,1,1, 2 \ndispute,1,2,\nchargeback,1,2,\nresolve,1,1,3\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_partial_dispute(1, 1, dec!(12.5))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_partial_charge_back(1, 1, dec!(2))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_dispute(2, 1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_charge_back(2, 1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_resolve(1, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_representments() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\nrepresentment,1,1,\nChargeback_Reversal,1,2,\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_representment(1, 1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_representment(2, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_authorizations() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\nauthorize,1,1,12.5\ncapture,1,1,10\ncapture,1,2,\nrelease,1,3,\nauthorize,1,4,\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_authorization(1, 1, dec!(12.5))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_partial_capture(1, 1, dec!(10))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_capture(2, 1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_release(3, 1)
+        );
+        assert!(csv_source.read().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_account_lifecycle() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount\nopen,1,,\nclose,1,5,\nclose,1,,\nopen,,,\n".as_bytes(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_opening(1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_closing(5, 1)
+        );
+        assert!(csv_source.read().await.is_err());
+        assert!(csv_source.read().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_can_parse_value_dates() {
+        let mut csv_source = CsvTransactionSource::from_reader(
+            "type,client,tx,amount,value_date\ndeposit,1,1,2,2024-03-01\nwithdrawal,1,2,1,\ncancel,1,1,,\nbusiness_date,,,,2024-03-02\nbusiness_date,,,,\ndeposit,1,3,1,2024-02-30\n"
+                .as_bytes(),
+        )
+        .await
+        .unwrap();
+        let date = |value: &str| value.parse().unwrap();
+
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_deposit(1, 1, dec!(2)).with_value_date(date("2024-03-01"))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_withdrawal(2, 1, dec!(1))
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_cancellation(1, 1)
+        );
+        assert_eq!(
+            csv_source.read().await.unwrap().unwrap(),
+            Transaction::new_business_date(date("2024-03-02"))
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Expected a value for column [value_date] (line: 7, byte: 135, record: 6).\")"
+        );
+        assert_eq!(
+            format!("{:?}", csv_source.read().await.unwrap_err()),
+            "InvalidArgument(\"Could not parse value [2024-02-30] for column [value_date]: Expected a date formatted as YYYY-MM-DD, got [2024-02-30] (line: 8, byte: 160, record: 7).\")"
+        );
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case(" 0 ", Some(0))]
+    #[case("1718000000", Some(1718000000))]
+    #[tokio::test]
+    async fn test_parse_timestamp_success(
+        #[case] given_value: &str,
+        #[case] expected_result: Option<u64>,
+    ) {
+        let csv_source = create_empty_csv_source().await;
+
+        assert_eq!(
+            csv_source.parse_timestamp(given_value).unwrap(),
+            expected_result
+        );
+    }
+
+    #[rstest]
+    #[case("hello", "InvalidArgument(\"Could not parse value [hello] for column [timestamp]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[case("-1", "InvalidArgument(\"Could not parse value [-1] for column [timestamp]: invalid digit found in string (line: 1, byte: 21, record: 1).\")")]
+    #[tokio::test]
+    async fn test_parse_timestamp_failures(
+        #[case] given_value: &str,
+        #[case] expected_error_message: &str,
+    ) {
+        let csv_source = create_empty_csv_source().await;
+        let actual_error_message =
+            format!("{:?}", csv_source.parse_timestamp(given_value).unwrap_err());
+
+        assert_eq!(actual_error_message, expected_error_message);
+    }
+
     #[rstest]
     #[case(
         "type,client,amount,other column",
@@ -1,2 +1,8 @@
+pub mod csv_client_tier_source;
+pub mod csv_credit_limit_source;
+pub mod csv_fee_schedule_source;
+pub mod csv_interest_rate_source;
+pub mod csv_rule_source;
+pub mod csv_tenant_config_source;
 pub mod csv_transaction_source;
 pub mod transaction_source;